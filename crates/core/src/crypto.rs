@@ -0,0 +1,59 @@
+//! Passphrase-based sealing of small secrets (identity keys, tickets)
+//!
+//! A 256-bit key is derived from the passphrase with Argon2id and a random
+//! salt, then the payload is encrypted with XChaCha20-Poly1305.
+//! Sealed layout: `salt (16) || nonce (24) || ciphertext + tag`
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use crate::error::{StreamError, StreamResult};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Encrypt `plaintext` under a key derived from `passphrase`
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> StreamResult<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| StreamError::Decryption("Encryption failed".to_string()))?;
+
+    let mut sealed = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt data produced by [`seal`]
+///
+/// A wrong passphrase (or tampered data) fails authentication and returns
+/// `StreamError::Decryption` rather than garbage.
+pub fn open(passphrase: &str, sealed: &[u8]) -> StreamResult<Vec<u8>> {
+    if sealed.len() < SALT_LEN + NONCE_LEN {
+        return Err(StreamError::Decryption("Sealed data is truncated".to_string()));
+    }
+
+    let (salt, rest) = sealed.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, salt)?.into());
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| StreamError::Decryption("Wrong passphrase or corrupted data".to_string()))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> StreamResult<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| StreamError::Decryption(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
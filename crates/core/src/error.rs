@@ -1,10 +1,53 @@
+use std::fmt::Formatter;
 use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+/// Shared [`std::io::Error`] that can be cloned and compared
+///
+/// Errors are equal when their kind and message match.
+#[derive(Debug, Clone)]
+pub struct IoError(Arc<std::io::Error>);
+
+impl IoError {
+    pub fn kind(&self) -> std::io::ErrorKind {
+        self.0.kind()
+    }
+
+    /// The underlying error
+    pub fn get_ref(&self) -> &std::io::Error {
+        &self.0
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.0.to_string() == other.0.to_string()
+    }
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl From<std::io::Error> for IoError {
+    fn from(e: std::io::Error) -> Self {
+        Self(Arc::new(e))
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum StreamError {
     #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(#[source] IoError),
 
     #[error("Database error: {0}")]
     Database(String),
@@ -15,6 +58,10 @@ pub enum StreamError {
     #[error("Transcoding error: {0}")]
     Transcode(String),
 
+    /// An external tool such as ffmpeg or ffprobe isn't installed or runnable
+    #[error("{tool} not found. Please ensure it is installed and in PATH, or check the configured path")]
+    DependencyMissing { tool: String },
+
     #[error("Invalid hash: {0}")]
     InvalidHash(String),
 
@@ -23,6 +70,30 @@ pub enum StreamError {
 
     #[error("Not connected to peer")]
     NotConnected,
+
+    #[error("Decryption failed: {0}")]
+    Decryption(String),
+
+    #[error("File is not ready to share: {0}")]
+    NotReady(PathBuf),
+
+    #[error("File is withheld from sharing: {0}")]
+    NotServable(PathBuf),
+
+    #[error("Transfer cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Ticket expired at {0}")]
+    TicketExpired(u64),
+
+    #[error("Invalid config: {0}")]
+    Config(String),
+}
+
+impl From<std::io::Error> for StreamError {
+    fn from(e: std::io::Error) -> Self {
+        StreamError::Io(e.into())
+    }
 }
 
 // Result type alias
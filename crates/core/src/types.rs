@@ -1,16 +1,74 @@
-use crate::error::StreamError;
+use crate::error::{StreamError, StreamResult};
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
 use std::path::PathBuf;
 use base64::prelude::*;
 
+/// Byte length of node ids and content hashes
+const KEY_LEN: usize = 32;
+
+/// Decode a 32-byte node id or hash written as hex or unpadded base32,
+/// the two encodings iroh accepts
+pub(crate) fn decode_key(s: &str) -> Option<[u8; KEY_LEN]> {
+    let bytes = match s.len() {
+        64 => data_encoding::HEXLOWER_PERMISSIVE.decode(s.as_bytes()).ok()?,
+        52 => data_encoding::BASE32_NOPAD.decode(s.to_ascii_uppercase().as_bytes()).ok()?,
+        _ => return None,
+    };
+    bytes.try_into().ok()
+}
+
+/// Number of characters kept by [`MediaHash::short`]
+pub const SHORT_HASH_LEN: usize = 12;
+
 /// Wrapper for content hashes (BLAKE3) used by Iroh
+///
+/// The tuple constructor accepts any string; prefer [`MediaHash::parse`] for
+/// untrusted input so invalid hashes are rejected up front.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MediaHash(pub String);
 
+impl MediaHash {
+    /// Parse a 32-byte BLAKE3 hash written in hex or base32
+    pub fn parse(s: &str) -> StreamResult<Self> {
+        if decode_key(s).is_none() {
+            return Err(StreamError::InvalidHash(format!(
+                "Invalid content hash {:?}: expected 64 hex or 52 base32 characters encoding 32 bytes, got {} characters",
+                s,
+                s.len()
+            )));
+        }
+        Ok(MediaHash(s.to_string()))
+    }
+
+    /// Abbreviated form of the hash for logs and UIs (like git short hashes)
+    ///
+    /// This is purely cosmetic: distinct hashes can share a prefix at this
+    /// length, so never use the result as a lookup key.
+    pub fn short(&self) -> &str {
+        match self.0.char_indices().nth(SHORT_HASH_LEN) {
+            Some((end, _)) => &self.0[..end],
+            None => &self.0,
+        }
+    }
+}
+
 impl std::fmt::Display for MediaHash {
+    /// `{}` prints the full hash, `{:#}` prints the [`MediaHash::short`] form
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        if f.alternate() {
+            write!(f, "{}", self.short())
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl TryFrom<&str> for MediaHash {
+    type Error = StreamError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        MediaHash::parse(s)
     }
 }
 
@@ -33,18 +91,146 @@ pub struct FileMetadata {
     pub mime_type: String,
     /// Unix timestamp of file creation/modification
     pub created_at: u64,
+    /// Unix timestamp of when the file was last indexed
+    #[serde(default)]
+    pub updated_at: u64,
+    /// File modification time in nanoseconds since the Unix epoch, 0 if unknown
+    #[serde(default)]
+    pub modified_ns: u64,
+}
+
+impl FileMetadata {
+    /// Whether a file of `size` last modified at `modified_ns` still has this
+    /// entry's content, so its hash can be reused without reading the file
+    ///
+    /// An unknown modification time never matches.
+    pub fn is_unchanged(&self, size: u64, modified_ns: u64) -> bool {
+        modified_ns != 0 && self.modified_ns == modified_ns && self.size == size
+    }
+}
+
+/// Progress of a file's import into the blob store
+///
+/// A file can be indexed well before its blob is servable; only `Ready`
+/// files can be shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportState {
+    /// Discovered, import not started
+    Pending,
+    /// Import (hashing/outboard computation) in progress
+    Importing,
+    /// Imported and servable
+    Ready,
+    /// Import failed
+    Failed,
+}
+
+/// Current manifest format version
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Named listing of shared content (e.g. the files in a collection)
+///
+/// Encoded as JSON with an explicit version so fields can be added later;
+/// unknown fields are ignored on decode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub entries: Vec<ManifestEntry>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A single named item in a [`Manifest`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name (or relative path) of the item
+    pub name: String,
+    /// Content hash (BLAKE3)
+    pub hash: MediaHash,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl Manifest {
+    pub fn new(entries: Vec<ManifestEntry>) -> Self {
+        Self { version: MANIFEST_VERSION, entries }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Manifest serialization error")
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, StreamError> {
+        let manifest: Manifest = serde_json::from_slice(bytes)
+            .map_err(|e| StreamError::InvalidHash(format!("Manifest decode failed: {}", e)))?;
+
+        if manifest.version > MANIFEST_VERSION {
+            return Err(StreamError::InvalidHash(format!(
+                "Unsupported manifest version {} (max {})",
+                manifest.version, MANIFEST_VERSION
+            )));
+        }
+
+        Ok(manifest)
+    }
+}
+
+/// Relay URL reported by nodes running in direct-only mode
+pub const RELAY_DISABLED: &str = "direct-only";
+
+/// Scheme of ticket links, see [`ShareTicket::to_url`]
+pub const URL_SCHEME: &str = "ghostdrive";
+
+/// Host part of ticket links
+const URL_SHARE_HOST: &str = "share";
+
+/// Smallest width and height in pixels of [`ShareTicket::to_qr_svg`] output
+const QR_MIN_SIZE: u32 = 256;
+
+/// Version byte leading every compact ticket
+const COMPACT_TICKET_VERSION: u8 = 1;
+
+/// Binary layout of [`ShareTicket::encode_compact`]
+///
+/// Keys are stored as raw bytes rather than text, which is most of the saving.
+#[derive(Serialize, Deserialize)]
+struct CompactTicket {
+    node_id: [u8; KEY_LEN],
+    hash: [u8; KEY_LEN],
+    relay_url: String,
+    name: String,
+    created_at: u64,
+    expires_at: Option<u64>,
+    direct_addrs: Vec<String>,
+    hash_seq: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShareTicket {
     pub node_id: String,
     pub relay_url: String,
     pub hash: MediaHash,
     pub name: String, // File or collection name
     pub created_at: u64,
+    /// Unix timestamp from which the ticket is no longer honored (None = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Socket addresses (`ip:port`) the sharing node can be reached on
+    /// directly, tried alongside the relay; malformed entries are ignored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub direct_addrs: Vec<String>,
+    /// Whether `hash` is a hash sequence (a raw collection of member hashes)
+    /// rather than a single blob, as iroh's `BlobFormat::HashSeq`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hash_seq: bool,
 }
 
 impl ShareTicket {
+    /// Whether the ticket has expired at unix time `now`
+    ///
+    /// A ticket is valid up to, but not including, its `expires_at` second.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
     pub fn encode(&self) -> String {
         let json = serde_json::to_string(self).expect("ShareTicket serialization error");
         BASE64_STANDARD.encode(json)
@@ -60,4 +246,198 @@ impl ShareTicket {
 
         Ok(ticket)
     }
+
+    /// Encode as lowercase, unpadded base32 of a binary layout
+    ///
+    /// Much shorter than [`ShareTicket::encode`] and safe in URLs and QR
+    /// codes, so this is the preferred form for sharing links. Node id and
+    /// hash come back hex-encoded from [`ShareTicket::decode_compact`].
+    ///
+    /// Unlike [`ShareTicket::encode`] this returns a `Result`: the keys are
+    /// packed as raw bytes, and since the fields are public a ticket can hold
+    /// a node id or hash that doesn't parse. Those fail with
+    /// [`StreamError::InvalidHash`] as in [`ShareTicket::validate`].
+    pub fn encode_compact(&self) -> StreamResult<String> {
+        self.validate()?;
+        let compact = CompactTicket {
+            node_id: decode_key(&self.node_id).expect("validated node id"),
+            hash: decode_key(&self.hash.0).expect("validated hash"),
+            relay_url: self.relay_url.clone(),
+            name: self.name.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            direct_addrs: self.direct_addrs.clone(),
+            hash_seq: self.hash_seq,
+        };
+
+        let mut bytes = vec![COMPACT_TICKET_VERSION];
+        bytes.extend(bincode::serde::encode_to_vec(&compact, bincode::config::standard())
+            .expect("CompactTicket serialization error"));
+
+        Ok(data_encoding::BASE32_NOPAD.encode(&bytes).to_ascii_lowercase())
+    }
+
+    /// Encrypt the ticket so it can only be opened with `passphrase`
+    ///
+    /// The serialized ticket is sealed with [`crypto::seal`](crate::crypto::seal),
+    /// so the salt and nonce travel with it; the result is lowercase,
+    /// unpadded base32.
+    pub fn encode_encrypted(&self, passphrase: &str) -> StreamResult<String> {
+        let json = serde_json::to_vec(self)
+            .map_err(|e| StreamError::InvalidHash(format!("Ticket encode failed: {}", e)))?;
+        let sealed = crate::crypto::seal(passphrase, &json)?;
+        Ok(data_encoding::BASE32_NOPAD.encode(&sealed).to_ascii_lowercase())
+    }
+
+    /// Decrypt a ticket produced by [`ShareTicket::encode_encrypted`]
+    ///
+    /// A wrong passphrase fails with [`StreamError::Decryption`]; input that
+    /// isn't an encrypted ticket at all fails with [`StreamError::InvalidHash`].
+    pub fn decode_encrypted(ticket: &str, passphrase: &str) -> StreamResult<Self> {
+        let sealed = data_encoding::BASE32_NOPAD
+            .decode(ticket.trim().to_ascii_uppercase().as_bytes())
+            .map_err(|e| StreamError::InvalidHash(format!("Base32 decode failed: {}", e)))?;
+        let json = crate::crypto::open(passphrase, &sealed)?;
+
+        serde_json::from_slice(&json)
+            .map_err(|e| StreamError::InvalidHash(format!("JSON decode failed: {}", e)))
+    }
+
+    /// Link form of the ticket, `ghostdrive://share/<compact ticket>`
+    ///
+    /// Meant for OS deep links; see [`ShareTicket::encode_compact`], whose
+    /// validation error this passes through.
+    pub fn to_url(&self) -> StreamResult<String> {
+        Ok(format!("{}://{}/{}", URL_SCHEME, URL_SHARE_HOST, self.encode_compact()?))
+    }
+
+    /// Parse and validate a link produced by [`ShareTicket::to_url`]
+    pub fn from_url(url: &str) -> StreamResult<Self> {
+        let parsed = url::Url::parse(url.trim())
+            .map_err(|e| StreamError::InvalidHash(format!("Invalid ticket URL {:?}: {}", url, e)))?;
+        if parsed.scheme() != URL_SCHEME || parsed.host_str() != Some(URL_SHARE_HOST) {
+            return Err(StreamError::InvalidHash(format!(
+                "Invalid ticket URL {:?}: expected {}://{}/<ticket>",
+                url, URL_SCHEME, URL_SHARE_HOST
+            )));
+        }
+
+        let payload = parsed.path().trim_start_matches('/');
+        if payload.is_empty() || payload.contains('/') || parsed.query().is_some() {
+            return Err(StreamError::InvalidHash(format!("Invalid ticket URL {:?}: malformed ticket", url)));
+        }
+
+        let ticket = Self::decode_compact(payload)?;
+        ticket.validate()?;
+        Ok(ticket)
+    }
+
+    /// Render the compact form as an SVG QR code, for scanning with a phone
+    ///
+    /// The code holds the upper-case [`ShareTicket::encode_compact`] string,
+    /// which QR encodes more densely and decodes the same. Tickets too large
+    /// for a single code (e.g. very long names) are an error.
+    pub fn to_qr_svg(&self) -> StreamResult<String> {
+        let payload = self.encode_compact()?.to_ascii_uppercase();
+        let code = qrcode::QrCode::new(payload.as_bytes())
+            .map_err(|e| StreamError::InvalidHash(format!("Ticket does not fit in a QR code: {}", e)))?;
+
+        Ok(code.render::<qrcode::render::svg::Color>()
+            .min_dimensions(QR_MIN_SIZE, QR_MIN_SIZE)
+            .build())
+    }
+
+    /// Decode a ticket produced by [`ShareTicket::encode_compact`]
+    pub fn decode_compact(ticket: &str) -> Result<Self, StreamError> {
+        let bytes = data_encoding::BASE32_NOPAD
+            .decode(ticket.trim().to_ascii_uppercase().as_bytes())
+            .map_err(|e| StreamError::InvalidHash(format!("Base32 decode failed: {}", e)))?;
+
+        let Some((&version, payload)) = bytes.split_first() else {
+            return Err(StreamError::InvalidHash("Empty ticket".to_string()));
+        };
+        if version != COMPACT_TICKET_VERSION {
+            return Err(StreamError::InvalidHash(format!("Unsupported ticket version {}", version)));
+        }
+        let (compact, _): (CompactTicket, usize) = bincode::serde::decode_from_slice(payload, bincode::config::standard())
+            .map_err(|e| StreamError::InvalidHash(format!("Ticket decode failed: {}", e)))?;
+
+        Ok(ShareTicket {
+            node_id: data_encoding::HEXLOWER.encode(&compact.node_id),
+            relay_url: compact.relay_url,
+            hash: MediaHash(data_encoding::HEXLOWER.encode(&compact.hash)),
+            name: compact.name,
+            created_at: compact.created_at,
+            expires_at: compact.expires_at,
+            direct_addrs: compact.direct_addrs,
+            hash_seq: compact.hash_seq,
+        })
+    }
+
+    /// Check that the ticket is well-formed without touching the network
+    ///
+    /// Verifies that the node id and hash are 32-byte values in hex or
+    /// base32, that the relay URL (if any) is an http(s) URL, and that the
+    /// name is usable as a file name. The first problem found is returned as
+    /// [`StreamError::InvalidHash`] naming the offending field.
+    pub fn validate(&self) -> StreamResult<()> {
+        if decode_key(&self.node_id).is_none() {
+            return Err(StreamError::InvalidHash(format!(
+                "Invalid node id {:?}: expected a 32-byte key in hex or base32",
+                self.node_id
+            )));
+        }
+
+        if decode_key(&self.hash.0).is_none() {
+            return Err(StreamError::InvalidHash(format!(
+                "Invalid content hash {:?}: expected a 32-byte BLAKE3 hash in hex or base32",
+                self.hash.0
+            )));
+        }
+
+        // Nodes without a relay write "None" or RELAY_DISABLED
+        if !self.relay_url.is_empty() && self.relay_url != "None" && self.relay_url != RELAY_DISABLED {
+            let url = url::Url::parse(&self.relay_url).map_err(|e| {
+                StreamError::InvalidHash(format!("Invalid relay URL {:?}: {}", self.relay_url, e))
+            })?;
+            if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+                return Err(StreamError::InvalidHash(format!(
+                    "Invalid relay URL {:?}: expected an http(s) URL with a host",
+                    self.relay_url
+                )));
+            }
+        }
+
+        let unsafe_name = self.name.is_empty()
+            || self.name == "."
+            || self.name == ".."
+            || self.name.contains(['/', '\\', '\0']);
+        if unsafe_name {
+            return Err(StreamError::InvalidHash(format!(
+                "Invalid ticket name {:?}: must be a plain file name",
+                self.name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A ticket handed out by this node, kept so shares can be listed and revoked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareRecord {
+    /// Stable id derived from the ticket's node, hash and creation time
+    pub id: String,
+    pub ticket: ShareTicket,
+    /// Unix timestamp the share was recorded
+    pub created_at: u64,
+    /// Unix timestamp the share was revoked, `None` while active
+    pub revoked_at: Option<u64>,
+}
+
+impl ShareRecord {
+    /// Whether the share has not been revoked
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
 }
\ No newline at end of file
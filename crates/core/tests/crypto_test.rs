@@ -0,0 +1,16 @@
+use ghostdrive_core::{crypto, StreamError};
+
+#[test]
+fn test_seal_and_open() {
+    let secret = b"32 bytes of very secret material";
+
+    let sealed = crypto::seal("correct horse", secret).unwrap();
+    assert_ne!(&sealed[sealed.len() - secret.len()..], secret.as_slice());
+
+    let opened = crypto::open("correct horse", &sealed).unwrap();
+    assert_eq!(opened, secret);
+
+    // Wrong passphrase and truncated input are distinct decryption failures
+    assert!(matches!(crypto::open("battery staple", &sealed), Err(StreamError::Decryption(_))));
+    assert!(matches!(crypto::open("correct horse", &sealed[..10]), Err(StreamError::Decryption(_))));
+}
@@ -0,0 +1,23 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use ghostdrive_core::{StreamError, StreamResult};
+
+#[test]
+fn test_errors_clone_and_compare() {
+    let not_found = StreamError::FileNotFound(PathBuf::from("/media/missing.mp4"));
+    assert_eq!(not_found.clone(), not_found);
+    assert_ne!(not_found, StreamError::NotReady(PathBuf::from("/media/missing.mp4")));
+
+    // IO errors keep `?` conversion and compare by kind and message
+    let read: StreamResult<String> = std::fs::read_to_string("/nonexistent/ghostdrive/file").map_err(Into::into);
+    let err = read.unwrap_err();
+    let StreamError::Io(io) = &err else { panic!("Expected an IO error, got {:?}", err) };
+    assert_eq!(io.kind(), ErrorKind::NotFound);
+    assert_eq!(err.clone(), err);
+
+    let denied = StreamError::from(std::io::Error::new(ErrorKind::PermissionDenied, "denied"));
+    assert_eq!(denied, StreamError::from(std::io::Error::new(ErrorKind::PermissionDenied, "denied")));
+    assert_ne!(denied, StreamError::from(std::io::Error::new(ErrorKind::PermissionDenied, "other")));
+    assert_ne!(denied, err);
+    assert_eq!(denied.to_string(), "IO error: denied");
+}
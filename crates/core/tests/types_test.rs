@@ -0,0 +1,190 @@
+use ghostdrive_core::{MediaHash, ShareTicket, StreamError, SHORT_HASH_LEN};
+
+#[test]
+fn test_media_hash_short() {
+    let full = "6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b";
+    let hash = MediaHash(full.to_string());
+
+    assert_eq!(hash.short().len(), SHORT_HASH_LEN);
+    assert!(full.starts_with(hash.short()));
+
+    // Display keeps the full hash, alternate form is the short one
+    assert_eq!(format!("{}", hash), full);
+    assert_eq!(format!("{:#}", hash), hash.short());
+
+    // Hashes shorter than the display length are returned unchanged
+    let tiny = MediaHash("abc".into());
+    assert_eq!(tiny.short(), "abc");
+}
+
+#[test]
+fn test_media_hash_parse() {
+    let hex = "6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b";
+    assert_eq!(MediaHash::parse(hex).unwrap(), MediaHash(hex.to_string()));
+    assert_eq!(MediaHash::try_from(hex).unwrap().0, hex);
+
+    for garbage in ["", "not a hash", &hex[..63], "zz86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b"] {
+        assert!(
+            matches!(MediaHash::parse(garbage), Err(StreamError::InvalidHash(_))),
+            "{:?} should be rejected",
+            garbage
+        );
+    }
+}
+
+fn valid_ticket() -> ShareTicket {
+    ShareTicket {
+        node_id: "ae58ff8833241ac82d6ff7611046ed67b5072d142c588d0063e942d9a75502b6".into(),
+        relay_url: "https://euw1-1.relay.iroh.network./".into(),
+        hash: MediaHash("d74981efa70a0c880b8d8c1985d075dbcbf679b99a5f9914e5aaf96b831a9e24".into()),
+        name: "movie.mp4".into(),
+        created_at: 1_700_000_000,
+        expires_at: None,
+        direct_addrs: vec!["192.168.1.20:41641".into()],
+        hash_seq: false,
+    }
+}
+
+#[test]
+fn test_ticket_validate() {
+    valid_ticket().validate().expect("Valid ticket rejected");
+
+    // No relay and base32 encodings are fine too
+    let ticket = ShareTicket {
+        relay_url: "None".into(),
+        hash: MediaHash("25eyd35hbigiqc4nrqmyludv3pf7m6nztjpzsfhfvl4wxay2tysa".into()),
+        ..valid_ticket()
+    };
+    ticket.validate().expect("Ticket without relay rejected");
+
+    let broken = vec![
+        ("node id", ShareTicket { node_id: "not-a-key".into(), ..valid_ticket() }),
+        ("content hash", ShareTicket { hash: MediaHash("abcd".into()), ..valid_ticket() }),
+        ("relay URL", ShareTicket { relay_url: "relay.example.com".into(), ..valid_ticket() }),
+        ("relay URL", ShareTicket { relay_url: "ftp://relay.example.com".into(), ..valid_ticket() }),
+        ("name", ShareTicket { name: "../escape.mp4".into(), ..valid_ticket() }),
+        ("name", ShareTicket { name: String::new(), ..valid_ticket() }),
+    ];
+
+    for (field, ticket) in broken {
+        let err = ticket.validate().expect_err(field).to_string();
+        assert!(err.contains(field), "Error {:?} doesn't mention {}", err, field);
+    }
+}
+
+#[test]
+fn test_ticket_expiry() {
+    let ticket = ShareTicket { expires_at: Some(1_700_003_600), ..valid_ticket() };
+    assert!(!ticket.is_expired(1_700_003_599));
+    assert!(ticket.is_expired(1_700_003_600));
+    assert!(ticket.is_expired(1_700_003_601));
+
+    // Tickets without an expiry never expire
+    assert!(!valid_ticket().is_expired(u64::MAX));
+}
+
+#[test]
+fn test_ticket_without_expiry_field_decodes() {
+    use base64::prelude::*;
+
+    let legacy = r#"{"node_id":"ae58ff8833241ac82d6ff7611046ed67b5072d142c588d0063e942d9a75502b6","relay_url":"None","hash":"d74981efa70a0c880b8d8c1985d075dbcbf679b99a5f9914e5aaf96b831a9e24","name":"movie.mp4","created_at":1700000000}"#;
+    let ticket = ShareTicket::decode(&BASE64_STANDARD.encode(legacy)).unwrap();
+    assert_eq!(ticket.expires_at, None);
+    assert_eq!(ticket.name, "movie.mp4");
+
+    // Expiring tickets round-trip
+    let expiring = ShareTicket { expires_at: Some(42), ..valid_ticket() };
+    assert_eq!(ShareTicket::decode(&expiring.encode()).unwrap().expires_at, Some(42));
+}
+
+#[test]
+fn test_compact_ticket_round_trip() {
+    let ticket = ShareTicket { expires_at: Some(1_700_086_400), ..valid_ticket() };
+
+    let compact = ticket.encode_compact().unwrap();
+    assert!(compact.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+
+    let decoded = ShareTicket::decode_compact(&compact).unwrap();
+    assert_eq!(decoded.node_id, ticket.node_id);
+    assert_eq!(decoded.hash, ticket.hash);
+    assert_eq!(decoded.relay_url, ticket.relay_url);
+    assert_eq!(decoded.name, ticket.name);
+    assert_eq!(decoded.created_at, ticket.created_at);
+    assert_eq!(decoded.expires_at, ticket.expires_at);
+    assert!(!decoded.hash_seq);
+
+    // Collection tickets keep their format
+    let collection = ShareTicket { hash_seq: true, ..ticket.clone() };
+    assert_eq!(ShareTicket::decode_compact(&collection.encode_compact().unwrap()).unwrap(), collection);
+    assert_eq!(ShareTicket::decode(&collection.encode()).unwrap(), collection);
+
+    // Meaningfully shorter than base64 JSON
+    let json = ticket.encode();
+    assert!(compact.len() * 3 < json.len() * 2, "compact {} vs json {}", compact.len(), json.len());
+
+    assert!(ShareTicket::decode_compact("not a ticket!").is_err());
+}
+
+#[test]
+fn test_ticket_qr_svg() {
+    let svg = valid_ticket().to_qr_svg().unwrap();
+    assert!(svg.contains("<svg"), "Not an SVG: {}", svg);
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains("<rect") || svg.contains("<path"), "No modules drawn: {}", svg);
+
+    // Far beyond the capacity of a single QR code
+    let huge = ShareTicket { name: "x".repeat(8000), ..valid_ticket() };
+    assert!(matches!(huge.to_qr_svg(), Err(StreamError::InvalidHash(_))));
+}
+
+#[test]
+fn test_ticket_url_round_trip() {
+    let ticket = ShareTicket { expires_at: Some(1_700_086_400), ..valid_ticket() };
+
+    let url = ticket.to_url().unwrap();
+    assert_eq!(url, format!("ghostdrive://share/{}", ticket.encode_compact().unwrap()));
+    assert_eq!(ShareTicket::from_url(&url).unwrap(), ticket);
+}
+
+#[test]
+fn test_ticket_url_rejects_other_urls() {
+    let compact = valid_ticket().encode_compact().unwrap();
+
+    for url in [
+        format!("http://share/{}", compact),
+        format!("ghostdrive://download/{}", compact),
+        "ghostdrive://share/".to_string(),
+        "ghostdrive://share/not-a-ticket".to_string(),
+        compact,
+    ] {
+        assert!(
+            matches!(ShareTicket::from_url(&url), Err(StreamError::InvalidHash(_))),
+            "{:?} should be rejected",
+            url
+        );
+    }
+}
+
+#[test]
+fn test_encrypted_ticket_round_trip() {
+    let ticket = ShareTicket { expires_at: Some(1_700_086_400), ..valid_ticket() };
+
+    let encrypted = ticket.encode_encrypted("correct horse").unwrap();
+    assert!(!encrypted.contains(&ticket.hash.0), "Hash visible in encrypted ticket");
+    assert!(ShareTicket::decode(&encrypted).is_err());
+    assert!(ShareTicket::decode_compact(&encrypted).is_err());
+
+    assert_eq!(ShareTicket::decode_encrypted(&encrypted, "correct horse").unwrap(), ticket);
+
+    // Salted: the same ticket encrypts differently every time
+    assert_ne!(ticket.encode_encrypted("correct horse").unwrap(), encrypted);
+
+    assert!(matches!(
+        ShareTicket::decode_encrypted(&encrypted, "battery staple"),
+        Err(StreamError::Decryption(_))
+    ));
+    assert!(matches!(
+        ShareTicket::decode_encrypted("not a ticket!", "correct horse"),
+        Err(StreamError::InvalidHash(_))
+    ));
+}
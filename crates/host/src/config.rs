@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+use ghostdrive_core::{StreamError, StreamResult};
+use ghostdrive_transcoder::{HwAccel, TranscodeOptions};
+use serde::Deserialize;
+
+use crate::daemon::HostConfig;
+
+/// On-disk layout of [`HostConfig`], every key optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    data_dir: Option<PathBuf>,
+    watch_paths: Vec<PathBuf>,
+    transcode: TranscodeSection,
+}
+
+/// `[transcode]` table, omitted keys keep the [`TranscodeOptions`] defaults
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TranscodeSection {
+    video_codec: Option<String>,
+    video_bitrate: Option<String>,
+    audio_codec: Option<String>,
+    audio_bitrate: Option<String>,
+    format: Option<String>,
+    resolution: Option<String>,
+    frame_rate: Option<u32>,
+    threads: Option<u32>,
+    cpu_affinity: Option<Vec<usize>>,
+    hw_accel: Option<HwAccelName>,
+    copy: Option<bool>,
+    audio_only: Option<bool>,
+    ffmpeg_path: Option<PathBuf>,
+    extra_args: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum HwAccelName {
+    None,
+    Nvenc,
+    Vaapi,
+    VideoToolbox,
+    Qsv,
+}
+
+impl From<HwAccelName> for HwAccel {
+    fn from(name: HwAccelName) -> Self {
+        match name {
+            HwAccelName::None => HwAccel::None,
+            HwAccelName::Nvenc => HwAccel::Nvenc,
+            HwAccelName::Vaapi => HwAccel::Vaapi,
+            HwAccelName::VideoToolbox => HwAccel::VideoToolbox,
+            HwAccelName::Qsv => HwAccel::Qsv,
+        }
+    }
+}
+
+impl TranscodeSection {
+    fn into_options(self) -> TranscodeOptions {
+        let defaults = TranscodeOptions::default();
+        TranscodeOptions {
+            video_codec: self.video_codec.unwrap_or(defaults.video_codec),
+            video_bitrate: self.video_bitrate.unwrap_or(defaults.video_bitrate),
+            audio_codec: self.audio_codec.unwrap_or(defaults.audio_codec),
+            audio_bitrate: self.audio_bitrate.or(defaults.audio_bitrate),
+            format: self.format.unwrap_or(defaults.format),
+            resolution: self.resolution.or(defaults.resolution),
+            frame_rate: self.frame_rate.or(defaults.frame_rate),
+            threads: self.threads.or(defaults.threads),
+            cpu_affinity: self.cpu_affinity.or(defaults.cpu_affinity),
+            hw_accel: self.hw_accel.map(HwAccel::from).unwrap_or(defaults.hw_accel),
+            copy: self.copy.unwrap_or(defaults.copy),
+            audio_only: self.audio_only.unwrap_or(defaults.audio_only),
+            ffmpeg_path: self.ffmpeg_path.or(defaults.ffmpeg_path),
+            extra_args: self.extra_args.unwrap_or(defaults.extra_args),
+            ..defaults
+        }
+    }
+}
+
+impl HostConfig {
+    /// Load a config from the TOML file at `path`
+    ///
+    /// Relative `data_dir` and `watch_paths` are resolved against the file's
+    /// directory, which is also the data dir when none is given. Keys left
+    /// out of the file (and the `[transcode]` table) keep their defaults;
+    /// settings without a file representation, such as hooks, are defaulted too.
+    pub fn from_toml_file(path: &Path) -> StreamResult<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let file: ConfigFile = toml::from_str(&contents)
+            .map_err(|e| StreamError::Config(format!("{}: {}", path.display(), e)))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        let transcode_options = file.transcode.into_options();
+        transcode_options
+            .validate()
+            .map_err(|e| StreamError::Config(format!("{}: {}", path.display(), e)))?;
+
+        Ok(HostConfig {
+            data_dir: file.data_dir.map_or_else(|| base.to_path_buf(), |dir| base.join(dir)),
+            watch_paths: file.watch_paths.into_iter().map(|p| base.join(p)).collect(),
+            transcode_options,
+            ..Default::default()
+        })
+    }
+}
@@ -1,28 +1,115 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, Instant};
 
-use ghostdrive_core::{FileMetadata, MediaHash, StreamError, StreamResult};
-use ghostdrive_indexer::{FileIndex, FileWatcher};
-use ghostdrive_network::StreamNode;
+use futures::stream::{self, StreamExt};
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash, ShareRecord, ShareTicket, StreamError, StreamResult};
+use ghostdrive_indexer::{modified_ns, EvictionPolicy, FileIndex, FileWatcher, IndexEvent, SymlinkPolicy, WatchHandle, WatchStrategy, WatcherConfig};
+use ghostdrive_network::{BlobStatus, ImportStrategy, StreamNode, TransferHandle};
 use ghostdrive_transcoder::TranscodeOptions;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
-use tracing::{error, info, instrument, warn};
-use async_recursion::async_recursion;
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::http::stream_router;
+use crate::importer::{FileReadyHook, Importer, Registered};
+use crate::ingest::{walk, IngestProgress, IngestProgressHook, IngestionSummary, RescanSummary, UnreadableDirPolicy, WalkEntry, WalkOptions};
+
+/// Upper bound on the number of indexed files, for constrained hosts
+#[derive(Debug, Clone, Copy)]
+pub struct IndexLimit {
+    /// Most files kept in the index
+    pub max_files: usize,
+    /// Which files are evicted once the cap is exceeded
+    pub policy: EvictionPolicy,
+    /// Also delete evicted files' blobs so they are no longer served
+    pub unshare_evicted: bool,
+}
+
+/// What registering one walked entry did
+enum Ingested {
+    File(Registered),
+    /// A link indexed without following it, with the target indexed before
+    Link { path: PathBuf, target: PathBuf, previous: Option<PathBuf> },
+}
+
+/// Handle to a download started or resumed by the daemon
+pub type DownloadHandle = TransferHandle;
+
+/// Outcome of sharing a folder as a collection
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolderShareResult {
+    /// Encoded ticket for the collection
+    pub ticket: String,
+    /// Hash of the collection blob
+    pub collection_hash: MediaHash,
+    /// Included files as (path relative to the folder, hash, size), in collection order
+    pub entries: Vec<(String, MediaHash, u64)>,
+    /// Files left out, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
 
+#[derive(Debug, Clone, Default)]
 pub struct HostConfig {
     pub data_dir: PathBuf,
     pub watch_paths: Vec<PathBuf>,
-    pub transcode_options: TranscodeOptions
+    pub transcode_options: TranscodeOptions,
+    /// What to do when a directory under a watch path can't be read
+    pub unreadable_dirs: UnreadableDirPolicy,
+    /// Whether files are referenced in place or copied into the blob store
+    pub import_strategy: ImportStrategy,
+    /// Treatment of symbolic links, for both scans and the watcher
+    pub symlink_policy: SymlinkPolicy,
+    /// Native events or polling for watch roots (network mounts need polling)
+    pub watch_strategy: WatchStrategy,
+    /// Poll interval for polled roots, `None` uses the watcher default
+    pub poll_interval: Option<Duration>,
+    /// How often to check and refresh relay connectivity while idle
+    ///
+    /// Keeps old tickets reachable for "share now, download later" use.
+    /// Shorter intervals recover faster from a lapsed relay but wake the
+    /// device and use bandwidth more often, so prefer minutes on battery
+    /// powered machines. `None` disables the keep-alive.
+    pub keep_alive_interval: Option<Duration>,
+    /// Evict files beyond this many, `None` indexes without limit
+    pub index_limit: Option<IndexLimit>,
+    /// Called for every file that becomes ready to share, see [`HostDaemon::on_file_ready`]
+    pub on_file_ready: FileReadyHook,
+    /// Files registered at once during the startup scan, `None` uses
+    /// [`DEFAULT_INGEST_CONCURRENCY`]
+    ///
+    /// Each file in flight holds an open handle while it is hashed, so the
+    /// value is capped at [`MAX_INGEST_CONCURRENCY`].
+    pub ingest_concurrency: Option<usize>,
+    /// Called as the startup scan works through the watch paths
+    pub on_ingest_progress: IngestProgressHook,
 }
 
+/// Files registered at once during the startup scan by default
+pub const DEFAULT_INGEST_CONCURRENCY: usize = 8;
+
+/// Upper bound on [`HostConfig::ingest_concurrency`], to stay well clear of
+/// open file limits
+pub const MAX_INGEST_CONCURRENCY: usize = 64;
+
+/// How long a keep-alive tick waits for the relay before forcing a refresh
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest collection blob read when protecting shared files from eviction
+const MAX_COLLECTION_READ: u64 = 16 * 1024 * 1024;
+
 pub struct HostDaemon {
     index: Arc<FileIndex>,
     node: Arc<StreamNode>,
+    importer: Importer,
     config: HostConfig,
     _watcher_handle: JoinHandle<()>,
+    watch_handle: WatchHandle,
     shutdown_token: CancellationToken,
+    ingestion_summary: IngestionSummary,
 }
 
 impl HostDaemon {
@@ -32,9 +119,14 @@ impl HostDaemon {
         // Initialize components
         let db_path = config.data_dir.join("index.db");
         let index = Arc::new(FileIndex::open(db_path)?);
+        let interrupted = index.reset_interrupted_imports()?;
+        if !interrupted.is_empty() {
+            warn!("{} imports were interrupted by the last shutdown, marked pending again", interrupted.len());
+        }
 
         // Initialize node (handles identity and Iroh connection)
         let node = Arc::new(StreamNode::new(config.data_dir.clone()).await?);
+        restore_withheld(&index, &node)?;
 
         // Start FileWatcher
         let watcher_index = index.clone();
@@ -42,7 +134,21 @@ impl HostDaemon {
 
         // Start watcher in background
         // Watcher currently manages its own internal loop, so we wrap it
-        let watcher = FileWatcher::new(watcher_index, watch_paths.clone())?;
+        let defaults = WatcherConfig::default();
+        let watcher_config = WatcherConfig {
+            symlink_policy: config.symlink_policy,
+            watch_strategy: config.watch_strategy,
+            poll_interval: config.poll_interval.unwrap_or(defaults.poll_interval),
+            ..defaults
+        };
+        let mut watcher = FileWatcher::with_config(watcher_index, watch_paths.clone(), watcher_config)?;
+        let indexed_rx = watcher.subscribe_indexed();
+        let watch_handle = watcher.handle();
+
+        let importer = Importer {
+            on_ready: config.on_file_ready.clone(),
+            ..Importer::new(index.clone(), node.clone(), config.import_strategy)
+        };
 
         let shutdown_token = CancellationToken::new();
         let child_token = shutdown_token.clone();
@@ -60,150 +166,685 @@ impl HostDaemon {
             }
         });
 
-        let daemon = Self {
+        spawn_watcher_imports(
+            importer.clone(),
+            index.clone(),
+            config.index_limit,
+            indexed_rx,
+            shutdown_token.clone()
+        );
+
+        if let Some(interval) = config.keep_alive_interval {
+            spawn_keep_alive(node.clone(), interval, shutdown_token.clone());
+        }
+
+        let mut daemon = Self {
             index,
             node,
+            importer,
             config,
             _watcher_handle: watcher_handle,
-            shutdown_token
+            watch_handle,
+            shutdown_token,
+            ingestion_summary: IngestionSummary::default(),
         };
 
         // Initial Ingestion
         // Scan watch paths to ensure both Index and Node are up to date
-        daemon.ingest_existing_files().await?;
+        daemon.ingestion_summary = daemon.ingest_existing_files().await?;
 
         info!("Host daemon started successfully. Node ID: {}", daemon.node.node_id());
         Ok(daemon)
     }
 
     /// Perform a recursive scan of watch paths to register files
-    async fn ingest_existing_files(&self) -> StreamResult<()> {
+    async fn ingest_existing_files(&self) -> StreamResult<IngestionSummary> {
         info!("Starting initial ingestion scan...");
+        let started = Instant::now();
+        let mut summary = IngestionSummary::default();
+
+        // Walk every root first, so progress can be reported against a total
+        let mut entries = Vec::new();
         for path in &self.config.watch_paths {
-            if path.exists() {
-                self.scan_recursive(path).await?;
+            if !path.exists() {
+                continue;
             }
-        }
-        info!("Ingestion complete");
-        Ok(())
-    }
 
-    #[async_recursion]
-    async fn scan_recursive(&self, dir: &Path) -> StreamResult<()> {
-        let mut entries = tokio::fs::read_dir(dir).await.map_err(StreamError::Io)?;
+            let walked = walk(path, self.walk_options()).await?;
+            summary.inaccessible.extend(walked.inaccessible);
+            entries.extend(walked.entries);
+        }
 
-        while let Some(entry) = entries.next_entry().await.map_err(StreamError::Io)? {
-            let path = entry.path();
-            if path.is_dir() {
-                self.scan_recursive(&path).await?;
-            } else {
-                // Process file
-                if let Err(e) = self.register_file(&path).await {
-                    warn!("Failed to ingest {:?}: {}", path, e);
+        // Everything that needs importing is pending until its import
+        // finishes; unchanged files stay as they are
+        let mut files = Vec::new();
+        for entry in &entries {
+            if let WalkEntry::File(path) = entry {
+                if !self.looks_unchanged(path)? {
+                    files.push(path.clone());
                 }
             }
         }
-        Ok(())
+        self.index.set_import_states(&files, ImportState::Pending)?;
+
+        let total = entries.len() as u64;
+        let mut scanned = 0;
+
+        // Hash and index several files at once, in no particular order
+        let mut registered = stream::iter(entries)
+            .map(|entry| async move {
+                let result = self.register_entry(&entry).await;
+                (entry, result)
+            })
+            .buffer_unordered(self.ingest_concurrency());
+
+        while let Some((entry, result)) = registered.next().await {
+            match result {
+                Ok(Ingested::File(registered)) => {
+                    if let Registered::Unchanged(_) = registered {
+                        summary.unchanged += 1;
+                    }
+                    summary.record(registered.meta().size);
+                }
+                Ok(Ingested::Link { .. }) => summary.record(0),
+                Err(e) => {
+                    summary.failed += 1;
+                    warn!("Failed to ingest {:?}: {}", entry.path(), e);
+                }
+            }
+
+            scanned += 1;
+            self.config.on_ingest_progress.report(&IngestProgress {
+                scanned,
+                total,
+                current_path: entry.path().to_path_buf(),
+            });
+        }
+
+        // Evict once for the whole scan rather than per file
+        enforce_index_limit(&self.index, &self.node, self.config.index_limit, None).await;
+
+        summary.elapsed = started.elapsed();
+        if !summary.inaccessible.is_empty() {
+            warn!("Skipped {} inaccessible directories: {:?}", summary.inaccessible.len(), summary.inaccessible);
+        }
+        info!(
+            files = summary.files,
+            failed = summary.failed,
+            bytes = summary.bytes,
+            elapsed_ms = summary.elapsed.as_millis() as u64,
+            "Ingestion complete ({:.1} files/s, {:.1} MB/s)",
+            summary.files_per_sec(),
+            summary.mb_per_sec()
+        );
+        Ok(summary)
+    }
+
+    fn ingest_concurrency(&self) -> usize {
+        self.config.ingest_concurrency
+            .unwrap_or(DEFAULT_INGEST_CONCURRENCY)
+            .clamp(1, MAX_INGEST_CONCURRENCY)
     }
 
-    /// Helper to register a file with both Iroh (Node) and Redb (Index)
-    async fn register_file(&self, path: &PathBuf) -> StreamResult<MediaHash> {
-        // Add to Iroh Node (computes/verifies hash)
-        // Using node to get the hash first, as it's the source of truth for network
-        let hash = self.node.add_file_reference(path.clone()).await?;
-
-        // Gather metadata
-        let metadata = tokio::fs::metadata(path).await.map_err(StreamError::Io)?;
-        let mime = mime_guess::from_path(path).first_or_octet_stream().to_string();
-        let created_at = metadata.created()
-            .unwrap_or(SystemTime::now())
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-
-        let meta = FileMetadata {
-            path: path.clone(),
-            hash: hash.clone(),
-            size: metadata.len(),
-            mime_type: mime,
-            created_at
+    fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            unreadable_dirs: self.config.unreadable_dirs,
+            symlinks: self.config.symlink_policy,
+        }
+    }
+
+    /// Whether the index entry for `path` has the file's current size and mtime
+    fn looks_unchanged(&self, path: &Path) -> StreamResult<bool> {
+        let Some(previous) = self.index.get_by_path(path)? else {
+            return Ok(false);
         };
+        Ok(std::fs::metadata(path)
+            .map(|metadata| previous.is_unchanged(metadata.len(), modified_ns(&metadata)))
+            .unwrap_or(false))
+    }
 
-        // Update index
-        self.index.upsert_file(&meta)?;
+    /// Register a walked entry: files go to Iroh and the index, links only to the index
+    async fn register_entry(&self, entry: &WalkEntry) -> StreamResult<Ingested> {
+        let registered = match entry {
+            WalkEntry::File(path) => self.importer.register_file(path).await?,
+            WalkEntry::Link(path) => {
+                let target = std::fs::read_link(path).map_err(StreamError::from)?;
+                let previous = self.index.upsert_link(path, &target)?;
+                return Ok(Ingested::Link { path: path.clone(), target, previous });
+            }
+        };
 
-        Ok(hash)
+        apply_servable(&self.index, &self.node, registered.meta())?;
+        Ok(Ingested::File(registered))
+    }
+
+    /// Re-scan a single subtree on demand and reconcile it with the index
+    ///
+    /// New files are registered, changed files re-registered, and index entries
+    /// under `path` whose file has vanished are removed. `path` should be spelled
+    /// the same way as the watch roots, since indexed paths are compared by prefix.
+    #[instrument(skip(self))]
+    pub async fn rescan(&self, path: PathBuf) -> StreamResult<RescanSummary> {
+        let mut summary = RescanSummary::default();
+
+        if path.is_dir() {
+            let walked = walk(&path, self.walk_options()).await?;
+            summary.inaccessible = walked.inaccessible;
+
+            for entry in walked.entries {
+                let previous = self.index.get_by_path(entry.path())?;
+                match self.register_entry(&entry).await {
+                    Ok(Ingested::File(Registered::New(meta))) => summary.added.push(meta.path),
+                    Ok(Ingested::File(Registered::Updated(meta))) if previous.as_ref().is_some_and(|prev| prev.hash != meta.hash) => {
+                        summary.updated.push(meta.path)
+                    }
+                    Ok(Ingested::Link { path, previous: None, .. }) => summary.added.push(path),
+                    Ok(Ingested::Link { path, target, previous: Some(before) }) if before != target => {
+                        summary.updated.push(path)
+                    }
+                    Ok(_) => summary.unchanged += 1,
+                    Err(e) => {
+                        summary.failed += 1;
+                        warn!("Failed to rescan {:?}: {}", entry.path(), e);
+                    }
+                }
+            }
+        }
+
+        enforce_index_limit(&self.index, &self.node, self.config.index_limit, None).await;
+
+        // Drop entries that no longer exist on disk
+        for meta in self.index.list_all()? {
+            if meta.path.starts_with(&path) && !meta.path.exists() {
+                self.index.remove_file(&meta.path)?;
+                summary.removed.push(meta.path);
+            }
+        }
+        for (link, _) in self.index.list_links()? {
+            if link.starts_with(&path) && std::fs::symlink_metadata(&link).is_err() {
+                self.index.remove_file(&link)?;
+                summary.removed.push(link);
+            }
+        }
+
+        info!(
+            added = summary.added.len(),
+            updated = summary.updated.len(),
+            removed = summary.removed.len(),
+            unchanged = summary.unchanged,
+            "Rescan of {:?} complete",
+            path
+        );
+        Ok(summary)
+    }
+
+    /// Register a callback for files that become ready to share
+    ///
+    /// Fires after a file has been hashed, imported and indexed, whether it
+    /// came from a scan, a rescan, a share or the watcher. Replaces any
+    /// previously registered callback, including [`HostConfig::on_file_ready`].
+    /// Files ingested during [`HostDaemon::new`] only reach a callback set in
+    /// the config.
+    pub fn on_file_ready<F>(&self, callback: F)
+    where
+        F: FnMut(FileMetadata) + Send + 'static,
+    {
+        self.importer.on_ready.set(callback);
+    }
+
+    /// Timing summary of the initial ingestion scan
+    pub fn ingestion_summary(&self) -> &IngestionSummary {
+        &self.ingestion_summary
     }
 
     /// Share a specific file by path
     #[instrument(skip(self))]
     pub async fn share_file(&self, path: PathBuf) -> StreamResult<String> {
-        let canonical = path.canonicalize().map_err(StreamError::Io)?;
+        let canonical = path.canonicalize().map_err(StreamError::from)?;
+
+        if !self.index.is_servable(&canonical)? {
+            return Err(StreamError::NotServable(canonical));
+        }
+
+        // Another import of this file is still running
+        if self.index.get_import_state(&canonical)? == Some(ImportState::Importing) {
+            return Err(StreamError::NotReady(canonical));
+        }
 
         // Ensure file is ready in Iroh
-        let hash = self.register_file(&canonical).await?;
+        let hash = self.importer.register_file(&canonical).await?.into_meta().hash;
+        // The file being shared must survive the eviction it may trigger
+        enforce_index_limit(&self.index, &self.node, self.config.index_limit, Some(&canonical)).await;
+        if self.index.get_import_state(&canonical)? != Some(ImportState::Ready) {
+            return Err(StreamError::NotReady(canonical));
+        }
 
         let file_name = canonical.file_name()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
         let ticket = self.node.generate_ticket(hash, file_name);
+        self.index.record_share(&ticket)?;
 
         Ok(ticket.encode())
     }
 
-    /// Share a folder as a collection
+    /// Share several files, each with its own independent ticket
+    ///
+    /// Results are returned per path in input order, so one missing or
+    /// unreadable file doesn't abort the rest. Unlike [`HostDaemon::share_folder`]
+    /// no collection is created.
+    pub async fn share_files(&self, paths: Vec<PathBuf>) -> Vec<(PathBuf, StreamResult<String>)> {
+        let mut results = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let result = self.share_file(path.clone()).await;
+            if let Err(e) = &result {
+                warn!("Failed to share {:?}: {}", path, e);
+            }
+            results.push((path, result));
+        }
+
+        results
+    }
+
+    /// Share a folder as a collection, returning only the encoded ticket
+    ///
+    /// See [`HostDaemon::share_folder_detailed`] for what was included.
     pub async fn share_folder(&self, path: PathBuf) -> StreamResult<String> {
-        let canonical = path.canonicalize().map_err(StreamError::Io)?;
+        Ok(self.share_folder_detailed(path).await?.ticket)
+    }
+
+    /// Share a folder as a collection and report what went into it
+    ///
+    /// Subfolders are included, each file named by its `/`-separated path
+    /// within the folder so the receiver can recreate the tree; empty
+    /// directories are left out. Files that can't be registered, are
+    /// withheld or aren't done importing are skipped with a reason instead
+    /// of failing the whole share. Fails only if the folder can't be read
+    /// or no file could be included.
+    #[instrument(skip(self))]
+    pub async fn share_folder_detailed(&self, path: PathBuf) -> StreamResult<FolderShareResult> {
+        let canonical = path.canonicalize().map_err(StreamError::from)?;
 
         if !canonical.is_dir() {
-            return Err(StreamError::Io(std::io::Error::new(
+            return Err(StreamError::from(std::io::Error::new(
                 std::io::ErrorKind::NotADirectory,
                 "Path is not a directory"
             )));
         }
 
-        // Collect all file hashes in the folder (flat list for now)
-        let mut hashes = Vec::new();
-        let mut read_dir = tokio::fs::read_dir(&canonical).await.map_err(StreamError::Io)?;
+        // Collect the files in the whole tree, in a stable order
+        let walked = walk(&canonical, self.walk_options()).await?;
+        let mut files: Vec<_> = walked.entries.into_iter().map(|entry| match entry {
+            WalkEntry::File(path) => (path, true),
+            WalkEntry::Link(path) => (path, false),
+        }).collect();
+        files.sort();
+
+        let mut entries = Vec::new();
+        let mut skipped: Vec<_> = walked.inaccessible.into_iter()
+            .map(|dir| (dir, "directory could not be read".to_string()))
+            .collect();
+
+        for (file, is_file) in files {
+            if !is_file {
+                skipped.push((file, "symbolic link not followed".to_string()));
+                continue;
+            }
+            if !self.index.is_servable(&file)? {
+                skipped.push((file, "withheld from sharing".to_string()));
+                continue;
+            }
+            // Another import of this file is still running
+            if self.index.get_import_state(&file)? == Some(ImportState::Importing) {
+                skipped.push((file, "still being imported".to_string()));
+                continue;
+            }
 
-        while let Some(entry) = read_dir.next_entry().await.map_err(StreamError::Io)? {
-            let entry_path = entry.path();
-            if entry_path.is_file() {
-                // Ensure registered
-                let hash = self.register_file(&entry_path).await?;
-                hashes.push(hash);
+            // Ensure registered
+            let meta = match self.importer.register_file(&file).await.map(Registered::into_meta) {
+                Ok(meta) => meta,
+                Err(e) => {
+                    warn!("Skipping {:?} in shared folder: {}", file, e);
+                    skipped.push((file, e.to_string()));
+                    continue;
+                }
+            };
+            if self.index.get_import_state(&file)? != Some(ImportState::Ready) {
+                skipped.push((file, "import not finished".to_string()));
+                continue;
             }
+            entries.push((relative_name(&canonical, &file), meta.hash, meta.size));
         }
 
-        if hashes.is_empty() {
-            return Err(StreamError::Io(std::io::Error::new(
+        if entries.is_empty() {
+            return Err(StreamError::from(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No files found in directory"
             )));
         }
 
         // Create collection
-        let collection_hash = self.node.create_collection(hashes).await?;
+        let named = entries.iter().map(|(name, hash, _)| (name.clone(), hash.clone())).collect();
+        let collection_hash = self.node.create_named_collection(named).await?;
 
         let folder_name = canonical.file_name()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_else(|| "collection".to_string());
 
-        let ticket = self.node.generate_ticket(collection_hash, folder_name);
+        let ticket = self.node.generate_ticket(collection_hash.clone(), folder_name);
+        self.index.record_share(&ticket)?;
+        // Registering may have gone over the limit; the recorded share keeps its members
+        enforce_index_limit(&self.index, &self.node, self.config.index_limit, None).await;
+
+        Ok(FolderShareResult {
+            ticket: ticket.encode(),
+            collection_hash,
+            entries,
+            skipped,
+        })
+    }
+
+    /// Tickets generated by [`HostDaemon::share_file`] and
+    /// [`HostDaemon::share_folder`], revoked ones included, oldest first
+    pub fn list_shares(&self) -> StreamResult<Vec<ShareRecord>> {
+        self.index.list_shares()
+    }
 
-        Ok(ticket.encode())
+    /// Mark the share `id` as revoked in the share list
+    ///
+    /// Tickets are bearer tokens for content, so revoking one doesn't stop
+    /// serving: use [`HostDaemon::set_servable`] to withhold the file itself.
+    /// Returns the updated record, or `None` for an unknown id.
+    pub fn revoke_share(&self, id: &str) -> StreamResult<Option<ShareRecord>> {
+        let revoked = self.index.revoke_share(id)?;
+        if let Some(record) = &revoked {
+            info!("Revoked share {} of {:?}", record.id, record.ticket.name);
+        }
+        Ok(revoked)
+    }
+
+    /// Start downloading an encoded ticket to the file `dest` in the background
+    ///
+    /// The download is persisted until it completes, see
+    /// [`HostDaemon::resume_downloads`].
+    pub fn download(&self, ticket: &str, dest: PathBuf) -> StreamResult<DownloadHandle> {
+        let ticket = ShareTicket::decode(ticket)?;
+        Ok(self.node.start_fetch(&ticket, dest))
+    }
+
+    /// Resume downloads that were interrupted, e.g. by a restart
+    ///
+    /// Already fetched chunks are kept, so each download continues from its
+    /// partial state. Returns a handle per resumed download.
+    pub fn resume_downloads(&self) -> Vec<DownloadHandle> {
+        match self.node.resume_downloads() {
+            Ok(handles) => {
+                info!("Resumed {} interrupted downloads", handles.len());
+                handles
+            }
+            Err(e) => {
+                warn!("Failed to read pending downloads: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Stop or resume serving an indexed file without removing it from the index
+    ///
+    /// While withheld, peers' requests for the file's content are refused and
+    /// no new tickets are generated for it. The setting is persisted and
+    /// survives restarts. Serving is controlled per content hash, so other
+    /// paths with identical content are affected too.
+    #[instrument(skip(self))]
+    pub async fn set_servable(&self, path: PathBuf, servable: bool) -> StreamResult<()> {
+        let canonical = path.canonicalize().map_err(StreamError::from)?;
+        let meta = self.index.get_by_path(&canonical)?
+            .ok_or_else(|| StreamError::FileNotFound(canonical.clone()))?;
+
+        self.index.set_servable(&canonical, servable)?;
+        self.node.set_servable(&meta.hash, servable)?;
+
+        info!("{:?} is now {}", canonical, if servable { "served" } else { "withheld" });
+        Ok(())
+    }
+
+    /// Transcode options for an indexed file, adapted to its media type
+    ///
+    /// Starts from [`HostConfig::transcode_options`]; audio files get an
+    /// audio-only transcode.
+    pub fn transcode_options_for(&self, path: &Path) -> StreamResult<TranscodeOptions> {
+        let canonical = path.canonicalize().map_err(StreamError::from)?;
+        let meta = self.index.get_by_path(&canonical)?
+            .ok_or(StreamError::FileNotFound(canonical))?;
+
+        Ok(self.config.transcode_options.for_mime(&meta.mime_type))
+    }
+
+    /// Serve [`stream_router`] on `listener` until the daemon is dropped
+    pub async fn serve_http(&self, listener: TcpListener) -> StreamResult<()> {
+        let router = stream_router(self.index.clone(), self.config.transcode_options.clone());
+        let shutdown = self.shutdown_token.clone();
+        info!("Serving streams on {:?}", listener.local_addr().ok());
+
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async move { shutdown.cancelled().await })
+            .await
+            .map_err(StreamError::from)
+    }
+
+    /// Receive changes the file watcher makes to the index
+    ///
+    /// Covers files added, changed or removed while the daemon runs; the
+    /// startup scan and [`HostDaemon::rescan`] are reported by their summaries
+    /// instead. Slow receivers miss events rather than holding up indexing.
+    pub fn subscribe_index_events(&self) -> broadcast::Receiver<IndexEvent> {
+        self.watch_handle.subscribe()
     }
 
     /// Get reference to the node
     pub fn node(&self) -> Arc<StreamNode> {
         self.node.clone()
     }
+
+    /// Get reference to the file index
+    pub fn index(&self) -> Arc<FileIndex> {
+        self.index.clone()
+    }
+}
+
+/// Evict files over the configured cap, optionally deleting their blobs
+///
+/// `keep` is never evicted, e.g. a file that is being shared. Failures are
+/// logged rather than returned: the file that triggered the check was
+/// registered fine either way.
+async fn enforce_index_limit(index: &FileIndex, node: &StreamNode, limit: Option<IndexLimit>, keep: Option<&Path>) {
+    let Some(limit) = limit else { return };
+
+    // Only look at shares once something has to go
+    match index.count() {
+        Ok(count) if count as usize <= limit.max_files => return,
+        Ok(_) => {}
+        Err(e) => {
+            warn!("Failed to count indexed files: {}", e);
+            return;
+        }
+    }
+
+    let shared = shared_hashes(index, node).await;
+    let evicted = index.evict_except(limit.max_files, limit.policy, |meta| {
+        keep == Some(meta.path.as_path()) || shared.contains(&meta.hash)
+    });
+    let evicted = match evicted {
+        Ok(evicted) => evicted,
+        Err(e) => {
+            warn!("Failed to evict files over the index limit: {}", e);
+            return;
+        }
+    };
+
+    for meta in evicted {
+        debug!("Evicted {:?} from the index", meta.path);
+        if !limit.unshare_evicted {
+            continue;
+        }
+        // Keep content that another indexed file still points at
+        match index.get_by_hash(&meta.hash) {
+            Ok(None) => {
+                if let Err(e) = node.delete_blob(&meta.hash).await {
+                    warn!("Failed to unshare evicted {:?}: {}", meta.path, e);
+                }
+            }
+            Ok(Some(_)) => {}
+            Err(e) => warn!("Failed to check references to {:#}: {}", meta.hash, e),
+        }
+    }
+}
+
+/// Content reachable through active shares: shared files and collection members
+///
+/// A share whose hash isn't an indexed file is a folder share, so its
+/// collection is read for the members. Blobs larger than
+/// [`MAX_COLLECTION_READ`] can't be one of our collections and aren't read.
+async fn shared_hashes(index: &FileIndex, node: &StreamNode) -> HashSet<MediaHash> {
+    let records = match index.list_shares() {
+        Ok(records) => records,
+        Err(e) => {
+            warn!("Failed to list shares, evicting without protecting them: {}", e);
+            return HashSet::new();
+        }
+    };
+
+    let mut shared = HashSet::new();
+    for record in records.into_iter().filter(ShareRecord::is_active) {
+        let hash = record.ticket.hash;
+        if !matches!(index.get_by_hash(&hash), Ok(None)) {
+            shared.insert(hash);
+            continue;
+        }
+        shared.insert(hash.clone());
+        let small = matches!(
+            node.blob_status(&hash).await,
+            Ok(BlobStatus::Complete { size }) if size <= MAX_COLLECTION_READ
+        );
+        if !small {
+            continue;
+        }
+        let members = if record.ticket.hash_seq {
+            node.read_collection(&hash).await
+        } else {
+            node.read_named_collection(&hash)
+                .await
+                .map(|manifest| manifest.entries.into_iter().map(|entry| entry.hash).collect())
+        };
+        match members {
+            Ok(members) => shared.extend(members),
+            Err(e) => debug!("Share {} has no readable collection: {}", record.id, e),
+        }
+    }
+    shared
+}
+
+/// Withhold the content of every file marked as not servable in the index
+fn restore_withheld(index: &FileIndex, node: &StreamNode) -> StreamResult<()> {
+    for path in index.list_withheld()? {
+        if let Some(meta) = index.get_by_path(&path)? {
+            node.set_servable(&meta.hash, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// Keep a withheld file withheld after it is re-imported with new content
+fn apply_servable(index: &FileIndex, node: &StreamNode, meta: &FileMetadata) -> StreamResult<()> {
+    if !index.is_servable(&meta.path)? {
+        node.set_servable(&meta.hash, false)?;
+    }
+    Ok(())
+}
+
+/// Import files indexed by the watcher so they become servable
+fn spawn_watcher_imports(
+    importer: Importer,
+    index: Arc<FileIndex>,
+    limit: Option<IndexLimit>,
+    mut indexed_rx: mpsc::UnboundedReceiver<FileMetadata>,
+    token: CancellationToken
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(meta) = indexed_rx.recv() => {
+                    // Import everything queued so far, then evict once for the batch
+                    let mut batch = vec![meta];
+                    while let Ok(meta) = indexed_rx.try_recv() {
+                        batch.push(meta);
+                    }
+                    for meta in batch {
+                        match importer.register_file(&meta.path).await.map(Registered::into_meta) {
+                            Ok(meta) => {
+                                if let Err(e) = apply_servable(&index, &importer.node, &meta) {
+                                    warn!("Failed to apply serving flag for {:?}: {}", meta.path, e);
+                                }
+                            }
+                            Err(e) => warn!("Failed to import watched file {:?}: {}", meta.path, e),
+                        }
+                    }
+                    enforce_index_limit(&index, &importer.node, limit, None).await;
+                }
+                _ = token.cancelled() => break,
+                else => break,
+            }
+        }
+    });
+}
+
+/// Periodically refresh relay connectivity until `token` is cancelled
+fn spawn_keep_alive(node: Arc<StreamNode>, interval: Duration, token: CancellationToken) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick fires immediately, the node has only just come up
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    node.keep_alive(KEEP_ALIVE_TIMEOUT).await;
+                }
+                _ = token.cancelled() => {
+                    debug!("Keep-alive shutting down via token");
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Path of `file` within `root` as a collection entry name, `/`-separated
+fn relative_name(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root).unwrap_or(file);
+    relative.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 impl Drop for HostDaemon {
     fn drop(&mut self) {
         // Signal watcher to stop
         self.shutdown_token.cancel();
+
+        // Background tasks may still share the node, so close it through its handles
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let shutdown = self.node.shutdown_handle();
+        runtime.spawn(async move {
+            if let Err(e) = shutdown.shutdown().await {
+                warn!("Node shutdown failed: {}", e);
+            }
+        });
     }
 }
\ No newline at end of file
@@ -0,0 +1,198 @@
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use futures::stream::{self, StreamExt};
+use ghostdrive_core::{FileMetadata, MediaHash, StreamError};
+use ghostdrive_indexer::FileIndex;
+use ghostdrive_transcoder::{Transcoder, TranscodeOptions};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::mpsc;
+use tokio_util::io::ReaderStream;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// Bytes read from ffmpeg per response chunk
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks buffered between ffmpeg and a slow client
+const STREAM_BUFFER_CHUNKS: usize = 8;
+
+#[derive(Clone)]
+struct StreamState {
+    index: Arc<FileIndex>,
+    options: TranscodeOptions,
+}
+
+/// Router serving indexed files by hash
+///
+/// `GET /stream/{hash}` transcodes with `options` (adapted to the media type)
+/// into `video/mp2t`, playable by browsers and VLC. `GET /file/{hash}` serves
+/// the original file, honouring `Range` so players can seek. Withheld files
+/// are refused.
+pub fn stream_router(index: Arc<FileIndex>, options: TranscodeOptions) -> Router {
+    Router::new()
+        .route("/stream/{hash}", get(stream_hash))
+        .route("/file/{hash}", get(serve_file))
+        .with_state(StreamState { index, options })
+}
+
+/// Indexed, servable file for `hash`, or the error response to send
+fn resolve(state: &StreamState, hash: &str) -> Result<FileMetadata, Response> {
+    let hash = MediaHash::parse(hash)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()).into_response())?;
+
+    let meta = match state.index.get_by_hash(&hash) {
+        Ok(Some(meta)) => meta,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, format!("Unknown hash {}", hash)).into_response()),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    };
+    match state.index.is_servable(&meta.path) {
+        Ok(true) => Ok(meta),
+        Ok(false) => {
+            let err = StreamError::NotServable(meta.path);
+            Err((StatusCode::FORBIDDEN, err.to_string()).into_response())
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    }
+}
+
+async fn stream_hash(State(state): State<StreamState>, Path(hash): Path<String>) -> Response {
+    let meta = match resolve(&state, &hash) {
+        Ok(meta) => meta,
+        Err(response) => return response,
+    };
+    let hash = &meta.hash;
+
+    let options = TranscodeOptions {
+        format: "mpegts".to_string(),
+        ..state.options.for_mime(&meta.mime_type)
+    };
+    let transcoder = match Transcoder::new(meta.path.clone(), options).await {
+        Ok(transcoder) => transcoder,
+        Err(StreamError::FileNotFound(path)) => {
+            return (StatusCode::NOT_FOUND, format!("File not found: {:?}", path)).into_response();
+        }
+        Err(e @ StreamError::DependencyMissing { .. }) => {
+            return (StatusCode::NOT_IMPLEMENTED, e.to_string()).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    debug!("Streaming {:?} for {:#}", meta.path, hash);
+    let cancel = CancellationToken::new();
+    let chunks = transcoder.with_cancel(cancel.clone()).stream_chunks(STREAM_CHUNK_SIZE);
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER_CHUNKS);
+
+    // Pump chunks into the response; once the client is gone the transcode
+    // is cancelled, which kills and reaps ffmpeg before the stream ends
+    tokio::spawn(async move {
+        let mut chunks = std::pin::pin!(chunks);
+        loop {
+            tokio::select! {
+                chunk = chunks.next() => match chunk {
+                    Some(chunk) => {
+                        if let Err(e) = &chunk {
+                            warn!("Transcode of {:?} failed: {}", meta.path, e);
+                        }
+                        if tx.send(chunk).await.is_err() {
+                            cancel.cancel();
+                        }
+                    }
+                    None => break,
+                },
+                _ = tx.closed(), if !cancel.is_cancelled() => {
+                    debug!("Client disconnected from {:?}", meta.path);
+                    cancel.cancel();
+                }
+            }
+        }
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+    ([(header::CONTENT_TYPE, "video/mp2t")], Body::from_stream(body)).into_response()
+}
+
+async fn serve_file(State(state): State<StreamState>, Path(hash): Path<String>, headers: HeaderMap) -> Response {
+    let meta = match resolve(&state, &hash) {
+        Ok(meta) => meta,
+        Err(response) => return response,
+    };
+
+    let mut file = match File::open(&meta.path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return (StatusCode::NOT_FOUND, format!("File not found: {:?}", meta.path)).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    // The file may have changed since it was indexed
+    let len = match file.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let range = headers.get(header::RANGE).and_then(|value| value.to_str().ok());
+    let (status, start, end) = match range.map(|value| parse_range(value, len)) {
+        None => (StatusCode::OK, 0, len),
+        Some(Some((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+        Some(None) => {
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", len))],
+            ).into_response();
+        }
+    };
+
+    if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    let body = ReaderStream::with_capacity(file.take(end - start), STREAM_CHUNK_SIZE);
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, &meta.mime_type)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, end - start);
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end - 1, len));
+    }
+    response
+        .body(Body::from_stream(body))
+        .unwrap_or_else(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+}
+
+/// First range of a `Range` header as a half-open `[start, end)`, `None` if unsatisfiable
+///
+/// Later ranges of a multi-range request are ignored.
+fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (first, last) = spec.split_once('-')?;
+
+    let (start, end) = match (first.trim(), last.trim()) {
+        // Suffix range: the final `last` bytes
+        ("", last) => {
+            let count: u64 = last.parse().ok()?;
+            (len.saturating_sub(count), len)
+        }
+        (first, "") => (first.parse().ok()?, len),
+        (first, last) => {
+            let start: u64 = first.parse().ok()?;
+            let last: u64 = last.parse().ok()?;
+            if last < start {
+                return None;
+            }
+            (start, last.saturating_add(1).min(len))
+        }
+    };
+
+    (start < end).then_some((start, end))
+}
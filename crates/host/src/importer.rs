@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash, StreamError, StreamResult};
+use ghostdrive_indexer::{modified_ns, FileStore};
+use ghostdrive_network::{ImportStrategy, StreamNode};
+use tracing::{debug, instrument, warn};
+
+type FileReadyCallback = Box<dyn FnMut(FileMetadata) + Send>;
+
+/// Callback invoked once a file has been hashed, imported and is servable
+///
+/// Calls run on the blocking pool, one at a time, so a slow callback never
+/// stalls ingestion. Clones share the same callback.
+#[derive(Clone, Default)]
+pub struct FileReadyHook(Arc<Mutex<Option<FileReadyCallback>>>);
+
+impl FileReadyHook {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(FileMetadata) + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(Some(Box::new(callback)))))
+    }
+
+    /// Replace the callback
+    pub(crate) fn set<F>(&self, callback: F)
+    where
+        F: FnMut(FileMetadata) + Send + 'static,
+    {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(callback));
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    /// Run the callback for `meta` off the calling task
+    fn fire(&self, meta: FileMetadata) {
+        if !self.is_set() {
+            return;
+        }
+
+        let callback = self.0.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Some(f) = callback.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                f(meta);
+            }
+        });
+    }
+}
+
+impl std::fmt::Debug for FileReadyHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileReadyHook")
+            .field("set", &self.is_set())
+            .finish()
+    }
+}
+
+/// Outcome of [`Importer::register_file`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Registered {
+    /// The file was not indexed before
+    New(FileMetadata),
+    /// Indexed with the same size and mtime and still in the store, so
+    /// neither the store nor the index was written
+    Unchanged(FileMetadata),
+    /// Indexed before and registered again
+    Updated(FileMetadata),
+}
+
+impl Registered {
+    pub fn meta(&self) -> &FileMetadata {
+        match self {
+            Registered::New(meta) | Registered::Unchanged(meta) | Registered::Updated(meta) => meta,
+        }
+    }
+
+    pub fn into_meta(self) -> FileMetadata {
+        match self {
+            Registered::New(meta) | Registered::Unchanged(meta) | Registered::Updated(meta) => meta,
+        }
+    }
+}
+
+/// Registers files with both Iroh (Node) and Redb (Index)
+///
+/// Cheap to clone, so background tasks like the watcher bridge can own one.
+#[derive(Clone)]
+pub struct Importer {
+    pub(crate) index: Arc<dyn FileStore>,
+    pub(crate) node: Arc<StreamNode>,
+    pub(crate) strategy: ImportStrategy,
+    pub(crate) on_ready: FileReadyHook,
+}
+
+impl Importer {
+    pub fn new(index: Arc<dyn FileStore>, node: Arc<StreamNode>, strategy: ImportStrategy) -> Self {
+        Self {
+            index,
+            node,
+            strategy,
+            on_ready: FileReadyHook::default(),
+        }
+    }
+
+    /// Register a file, tracking its import state (Importing -> Ready/Failed) along the way
+    ///
+    /// Files that are already registered and unchanged are skipped without
+    /// touching the store. Store and index stay consistent: if the index write
+    /// fails, a blob imported only for this file is removed from the store again.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn register_file(&self, path: &PathBuf) -> StreamResult<Registered> {
+        if let Some(meta) = self.unchanged(path).await {
+            // Only written if it was interrupted (or queued) before getting there
+            if self.index.get_import_state(path)? != Some(ImportState::Ready) {
+                self.index.set_import_state(path, ImportState::Ready)?;
+                self.on_ready.fire(meta.clone());
+            }
+            debug!("Unchanged since last registered: {:?}", path);
+            return Ok(Registered::Unchanged(meta));
+        }
+
+        self.index.set_import_state(path, ImportState::Importing)?;
+
+        match self.import_file(path).await {
+            Ok((meta, existed)) => {
+                self.index.set_import_state(path, ImportState::Ready)?;
+                self.on_ready.fire(meta.clone());
+                Ok(if existed { Registered::Updated(meta) } else { Registered::New(meta) })
+            }
+            Err(e) => {
+                if let Err(state_err) = self.index.set_import_state(path, ImportState::Failed) {
+                    warn!("Failed to record import failure for {:?}: {}", path, state_err);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// The index entry of `path`, if its size and modification time still
+    /// match and its blob is in the store
+    async fn unchanged(&self, path: &PathBuf) -> Option<FileMetadata> {
+        let previous = self.index.get_by_path(path).ok().flatten()?;
+        let metadata = tokio::fs::metadata(path).await.ok()?;
+        if !previous.is_unchanged(metadata.len(), modified_ns(&metadata)) {
+            return None;
+        }
+        match self.node.has_blob(&previous.hash).await {
+            Ok(true) => Some(previous),
+            _ => None,
+        }
+    }
+
+    /// Import a file into the store and write its metadata to the index
+    ///
+    /// Also returns whether the file was indexed before.
+    async fn import_file(&self, path: &PathBuf) -> StreamResult<(FileMetadata, bool)> {
+        let started = Instant::now();
+        let previous = self.index.get_by_path(path).ok().flatten();
+
+        // Add to Iroh Node (computes/verifies hash)
+        // Using node to get the hash first, as it's the source of truth for network
+        let hash = self.node.add_file(path.clone(), self.strategy).await?;
+        let import_elapsed = started.elapsed();
+
+        // Gather metadata
+        let stage = Instant::now();
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                self.rollback_import(&hash).await;
+                return Err(StreamError::from(e));
+            }
+        };
+        let mime = mime_guess::from_path(path).first_or_octet_stream().to_string();
+        let mime_elapsed = stage.elapsed();
+        let now = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        // Re-indexing keeps the original creation time
+        let existed = previous.is_some();
+        let created_at = match previous {
+            Some(previous) => previous.created_at,
+            None => metadata.created()
+                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+                .unwrap_or(now),
+        };
+
+        let meta = FileMetadata {
+            path: path.clone(),
+            hash,
+            size: metadata.len(),
+            mime_type: mime,
+            created_at,
+            updated_at: now,
+            modified_ns: modified_ns(&metadata),
+        };
+
+        // Update index, undoing the import if that fails
+        let stage = Instant::now();
+        if let Err(e) = self.index.upsert_file(&meta) {
+            self.rollback_import(&meta.hash).await;
+            return Err(StreamError::Database(format!(
+                "Failed to index {:?}, store import rolled back: {}",
+                path, e
+            )));
+        }
+        let index_elapsed = stage.elapsed();
+
+        debug!(
+            path = ?path,
+            size = meta.size,
+            import_us = import_elapsed.as_micros() as u64,
+            mime_us = mime_elapsed.as_micros() as u64,
+            index_us = index_elapsed.as_micros() as u64,
+            total_us = started.elapsed().as_micros() as u64,
+            "Registered file"
+        );
+
+        Ok((meta, existed))
+    }
+
+    /// Remove a just-imported blob unless another indexed file still uses it
+    async fn rollback_import(&self, hash: &MediaHash) {
+        match self.index.get_by_hash(hash) {
+            Ok(Some(_)) => debug!("Keeping blob {:#}, still indexed for another file", hash),
+            Ok(None) => {
+                if let Err(e) = self.node.delete_blob(hash).await {
+                    warn!("Failed to roll back import of {:#}: {}", hash, e);
+                }
+            }
+            Err(e) => warn!("Failed to check references to {:#}, keeping blob: {}", hash, e),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_recursion::async_recursion;
+use ghostdrive_core::{StreamError, StreamResult};
+use ghostdrive_indexer::SymlinkPolicy;
+use tracing::{debug, warn};
+
+/// How scans treat directories that cannot be read (e.g. permission denied)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnreadableDirPolicy {
+    /// Log the directory, record it in the summary and continue with its siblings
+    #[default]
+    Skip,
+    /// Abort the scan of the whole root
+    Fail,
+}
+
+impl UnreadableDirPolicy {
+    /// Whether an error enumerating a directory should be skipped under this policy
+    pub(crate) fn skips(&self, err: &std::io::Error) -> bool {
+        *self == UnreadableDirPolicy::Skip
+            && matches!(
+                err.kind(),
+                std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::NotFound
+            )
+    }
+}
+
+/// Progress through the startup scan, reported after each entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestProgress {
+    /// Entries processed so far, whether or not they registered
+    pub scanned: u64,
+    /// Entries found across all watch paths
+    pub total: u64,
+    /// The entry just processed
+    pub current_path: PathBuf,
+}
+
+type IngestProgressCallback = dyn Fn(&IngestProgress) + Send + Sync;
+
+/// Callback receiving [`IngestProgress`] during the startup scan
+///
+/// Runs inline on the scan task, so it should return quickly (e.g. update a
+/// progress bar). Clones share the same callback.
+#[derive(Clone, Default)]
+pub struct IngestProgressHook(Option<Arc<IngestProgressCallback>>);
+
+impl IngestProgressHook {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&IngestProgress) + Send + Sync + 'static,
+    {
+        Self(Some(Arc::new(callback)))
+    }
+
+    pub(crate) fn report(&self, progress: &IngestProgress) {
+        if let Some(callback) = &self.0 {
+            callback(progress);
+        }
+    }
+}
+
+impl std::fmt::Debug for IngestProgressHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IngestProgressHook")
+            .field("set", &self.0.is_some())
+            .finish()
+    }
+}
+
+/// Aggregate timing for an ingestion scan
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IngestionSummary {
+    /// Files successfully registered
+    pub files: u64,
+    /// Total bytes of successfully registered files
+    pub bytes: u64,
+    /// Of `files`, those already registered and unchanged, which were skipped
+    pub unchanged: u64,
+    /// Files that failed to register
+    pub failed: u64,
+    /// Directories that could not be read and were skipped
+    pub inaccessible: Vec<PathBuf>,
+    /// Wall-clock duration of the scan
+    pub elapsed: Duration,
+}
+
+impl IngestionSummary {
+    /// Record a successfully registered file
+    pub fn record(&mut self, size: u64) {
+        self.files += 1;
+        self.bytes += size;
+    }
+
+    /// Files processed per second over the whole scan
+    pub fn files_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.files as f64 / secs } else { 0.0 }
+    }
+
+    /// Megabytes (10^6 bytes) processed per second over the whole scan
+    pub fn mb_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.bytes as f64 / 1_000_000.0 / secs } else { 0.0 }
+    }
+}
+
+/// Changes applied by an on-demand rescan of a subtree
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RescanSummary {
+    /// Files that were not indexed before
+    pub added: Vec<PathBuf>,
+    /// Indexed files whose content changed
+    pub updated: Vec<PathBuf>,
+    /// Indexed files that no longer exist on disk
+    pub removed: Vec<PathBuf>,
+    /// Indexed files whose content is unchanged
+    pub unchanged: u64,
+    /// Files that failed to register
+    pub failed: u64,
+    /// Directories that could not be read and were skipped
+    pub inaccessible: Vec<PathBuf>,
+}
+
+/// Something found while walking a directory tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WalkEntry {
+    /// A regular file (or a followed link to one)
+    File(PathBuf),
+    /// A symbolic link to be indexed without following it
+    Link(PathBuf),
+}
+
+impl WalkEntry {
+    pub(crate) fn path(&self) -> &Path {
+        match self {
+            WalkEntry::File(path) | WalkEntry::Link(path) => path,
+        }
+    }
+}
+
+/// Policies applied while walking a directory tree
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WalkOptions {
+    pub unreadable_dirs: UnreadableDirPolicy,
+    pub symlinks: SymlinkPolicy,
+}
+
+/// Result of walking a directory tree
+#[derive(Debug, Default)]
+pub(crate) struct WalkResult {
+    pub entries: Vec<WalkEntry>,
+    /// Directories that could not be read and were skipped
+    pub inaccessible: Vec<PathBuf>,
+}
+
+/// Recursively collect the files under `root`
+pub(crate) async fn walk(root: &Path, options: WalkOptions) -> StreamResult<WalkResult> {
+    let mut result = WalkResult::default();
+    let mut visited = HashSet::new();
+    if let Ok(canonical) = root.canonicalize() {
+        visited.insert(canonical);
+    }
+
+    walk_dir(root, options, &mut visited, &mut result).await?;
+    Ok(result)
+}
+
+#[async_recursion]
+async fn walk_dir(
+    dir: &Path,
+    options: WalkOptions,
+    visited: &mut HashSet<PathBuf>,
+    result: &mut WalkResult
+) -> StreamResult<()> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if options.unreadable_dirs.skips(&e) => {
+            warn!("Skipping unreadable directory {:?}: {}", dir, e);
+            result.inaccessible.push(dir.to_path_buf());
+            return Ok(());
+        }
+        Err(e) => return Err(StreamError::from(e)),
+    };
+
+    while let Some(entry) = entries.next_entry().await.map_err(StreamError::from)? {
+        let path = entry.path();
+        let file_type = entry.file_type().await.map_err(StreamError::from)?;
+
+        if file_type.is_symlink() {
+            match options.symlinks {
+                SymlinkPolicy::Follow => {}
+                SymlinkPolicy::Ignore => continue,
+                SymlinkPolicy::IndexAsLink => {
+                    result.entries.push(WalkEntry::Link(path));
+                    continue;
+                }
+            }
+        }
+
+        if path.is_dir() {
+            // Followed links can form cycles: never descend into the same directory twice
+            if options.symlinks == SymlinkPolicy::Follow {
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                if !visited.insert(canonical) {
+                    debug!("Skipping already visited directory {:?}", path);
+                    continue;
+                }
+            }
+            walk_dir(&path, options, visited, result).await?;
+        } else if path.is_file() {
+            result.entries.push(WalkEntry::File(path));
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,10 @@
+mod config;
 mod daemon;
+mod http;
+mod importer;
+mod ingest;
 
-pub use daemon::{HostDaemon, HostConfig};
\ No newline at end of file
+pub use daemon::{DownloadHandle, FolderShareResult, HostDaemon, HostConfig, IndexLimit, DEFAULT_INGEST_CONCURRENCY, MAX_INGEST_CONCURRENCY};
+pub use http::stream_router;
+pub use importer::{FileReadyHook, Importer, Registered};
+pub use ingest::{IngestProgress, IngestProgressHook, IngestionSummary, RescanSummary, UnreadableDirPolicy};
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use ghostdrive_core::StreamError;
+use ghostdrive_host::HostConfig;
+use ghostdrive_transcoder::{HwAccel, TranscodeOptions};
+
+#[test]
+fn test_from_toml_file() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_config_toml_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let config_path = temp_dir.join("ghostdrive.toml");
+    std::fs::write(&config_path, r#"
+data_dir = "state"
+watch_paths = ["media", "/srv/shows"]
+
+[transcode]
+video_codec = "libx265"
+video_bitrate = "4M"
+resolution = "1920x1080"
+hw_accel = "vaapi"
+extra_args = ["-tune", "film"]
+"#).unwrap();
+
+    let config = HostConfig::from_toml_file(&config_path).unwrap();
+    assert_eq!(config.data_dir, temp_dir.join("state"));
+    assert_eq!(config.watch_paths, vec![temp_dir.join("media"), PathBuf::from("/srv/shows")]);
+
+    let options = &config.transcode_options;
+    assert_eq!(options.video_codec, "libx265");
+    assert_eq!(options.video_bitrate, "4M");
+    assert_eq!(options.resolution.as_deref(), Some("1920x1080"));
+    assert_eq!(options.hw_accel, HwAccel::Vaapi);
+    assert_eq!(options.extra_args, vec!["-tune".to_string(), "film".to_string()]);
+
+    // Keys left out keep their defaults
+    let defaults = TranscodeOptions::default();
+    assert_eq!(options.audio_codec, defaults.audio_codec);
+    assert_eq!(options.format, defaults.format);
+    assert_eq!(options.frame_rate, defaults.frame_rate);
+    assert!(!options.copy);
+    assert!(config.ingest_concurrency.is_none());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_from_toml_file_defaults() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_config_toml_defaults_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let config_path = temp_dir.join("ghostdrive.toml");
+    std::fs::write(&config_path, "").unwrap();
+
+    let config = HostConfig::from_toml_file(&config_path).unwrap();
+    assert_eq!(config.data_dir, temp_dir);
+    assert!(config.watch_paths.is_empty());
+    assert_eq!(config.transcode_options.fingerprint(), TranscodeOptions::default().fingerprint());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_from_toml_file_names_bad_field() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_config_toml_error_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let config_path = temp_dir.join("ghostdrive.toml");
+    std::fs::write(&config_path, "[transcode]\nframe_rate = \"fast\"\n").unwrap();
+    match HostConfig::from_toml_file(&config_path) {
+        Err(StreamError::Config(msg)) => assert!(msg.contains("frame_rate"), "{}", msg),
+        other => panic!("Expected a config error, got {:?}", other.map(|_| ())),
+    }
+
+    // Values that parse but can't be used are rejected too
+    std::fs::write(&config_path, "[transcode]\nresolution = \"wide\"\n").unwrap();
+    assert!(matches!(HostConfig::from_toml_file(&config_path), Err(StreamError::Config(_))));
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -1,4 +1,4 @@
-use ghostdrive_host::{HostConfig, HostDaemon};
+use ghostdrive_host::{FileReadyHook, HostConfig, HostDaemon, IngestProgress, IngestProgressHook};
 use ghostdrive_transcoder::TranscodeOptions;
 
 #[tokio::test]
@@ -20,6 +20,7 @@ async fn test_daemon_init_and_share() {
         data_dir,
         watch_paths: vec![media_dir.clone()],
         transcode_options: TranscodeOptions::default(),
+        ..Default::default()
     };
 
     // Initialize Daemon
@@ -38,4 +39,659 @@ async fn test_daemon_init_and_share() {
 
     // Cleanup
     let _ = tokio::fs::remove_dir_all(test_root).await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_ingestion_summary() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_summary_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let data_dir = test_root.join("data");
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(media_dir.join("nested")).await.unwrap();
+
+    tokio::fs::write(media_dir.join("a.txt"), "first file").await.unwrap();
+    tokio::fs::write(media_dir.join("nested/b.txt"), "second file").await.unwrap();
+
+    let config = HostConfig {
+        data_dir,
+        watch_paths: vec![media_dir],
+        transcode_options: TranscodeOptions::default(),
+        ..Default::default()
+    };
+
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    let summary = daemon.ingestion_summary();
+    assert_eq!(summary.files, 2);
+    assert_eq!(summary.failed, 0);
+    assert_eq!(summary.bytes, ("first file".len() + "second file".len()) as u64);
+    println!("{:.1} files/s, {:.3} MB/s", summary.files_per_sec(), summary.mb_per_sec());
+
+    // Cleanup
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_rescan_subtree() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_rescan_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let data_dir = test_root.join("data");
+    let media_dir = test_root.join("media");
+    // Not watched, so only the rescan touches it
+    let library = test_root.join("library");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    tokio::fs::create_dir_all(library.join("sub")).await.unwrap();
+
+    tokio::fs::write(library.join("a.txt"), "alpha").await.unwrap();
+    tokio::fs::write(library.join("sub/b.txt"), "bravo").await.unwrap();
+
+    let config = HostConfig {
+        data_dir,
+        watch_paths: vec![media_dir],
+        transcode_options: TranscodeOptions::default(),
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    let first = daemon.rescan(library.clone()).await.unwrap();
+    assert_eq!(first.added.len(), 2);
+    assert!(first.updated.is_empty() && first.removed.is_empty());
+
+    // Change one, delete one, add one
+    tokio::fs::write(library.join("a.txt"), "alpha v2").await.unwrap();
+    tokio::fs::remove_file(library.join("sub/b.txt")).await.unwrap();
+    tokio::fs::write(library.join("sub/c.txt"), "charlie").await.unwrap();
+
+    let second = daemon.rescan(library.clone()).await.unwrap();
+    assert_eq!(second.added, vec![library.join("sub/c.txt")]);
+    assert_eq!(second.updated, vec![library.join("a.txt")]);
+    assert_eq!(second.removed, vec![library.join("sub/b.txt")]);
+    assert_eq!(second.unchanged, 0);
+
+    // Nothing changed since
+    let third = daemon.rescan(library).await.unwrap();
+    assert!(third.added.is_empty() && third.updated.is_empty() && third.removed.is_empty());
+    assert_eq!(third.unchanged, 2);
+
+    // Cleanup
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unreadable_directory_is_skipped() {
+    use std::os::unix::fs::PermissionsExt;
+    use ghostdrive_host::UnreadableDirPolicy;
+
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_unreadable_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let data_dir = test_root.join("data");
+    let media_dir = test_root.join("media");
+    let locked = media_dir.join("locked");
+    tokio::fs::create_dir_all(&locked).await.unwrap();
+    tokio::fs::write(media_dir.join("visible.txt"), "visible").await.unwrap();
+    tokio::fs::write(locked.join("hidden.txt"), "hidden").await.unwrap();
+
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+    // Privileged users bypass directory permissions, nothing to test then
+    if std::fs::read_dir(&locked).is_ok() {
+        println!("Directory still readable (running as root?), skipping");
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let _ = tokio::fs::remove_dir_all(test_root).await;
+        return;
+    }
+
+    let config = HostConfig {
+        data_dir: data_dir.clone(),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Scan should survive an unreadable directory");
+
+    let summary = daemon.ingestion_summary();
+    assert_eq!(summary.files, 1);
+    assert_eq!(summary.inaccessible, vec![locked.clone()]);
+    drop(daemon);
+
+    // Strict policy aborts instead
+    let strict = HostConfig {
+        data_dir: test_root.join("data_strict"),
+        watch_paths: vec![media_dir],
+        unreadable_dirs: UnreadableDirPolicy::Fail,
+        ..Default::default()
+    };
+    assert!(HostDaemon::new(strict).await.is_err());
+
+    // Cleanup
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_import_state_gates_sharing() {
+    use ghostdrive_core::{ImportState, StreamError};
+
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_import_state_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    let file_path = media_dir.join("clip.mp4");
+    tokio::fs::write(&file_path, "not really a video").await.unwrap();
+    tokio::fs::write(media_dir.join("other.mp4"), "another clip").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let index = daemon.index();
+
+    // Initial ingestion leaves the file servable
+    assert_eq!(index.get_import_state(&file_path).unwrap(), Some(ImportState::Ready));
+
+    // A file mid-import can't be shared
+    let canonical = file_path.canonicalize().unwrap();
+    index.set_import_state(&canonical, ImportState::Importing).unwrap();
+    assert!(matches!(daemon.share_file(file_path.clone()).await, Err(StreamError::NotReady(_))));
+
+    // Nor included in a folder share
+    let folder = daemon.share_folder_detailed(media_dir).await.expect("Failed to share folder");
+    assert_eq!(folder.entries.len(), 1);
+    assert_eq!(folder.skipped.len(), 1);
+    assert_eq!(folder.skipped[0].0, canonical);
+
+    // Once the import settles, sharing works again
+    index.set_import_state(&canonical, ImportState::Ready).unwrap();
+    assert!(daemon.share_file(file_path).await.is_ok());
+
+    // Cleanup
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_keep_alive_keeps_daemon_shareable() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_keep_alive_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    let file_path = media_dir.join("idle.txt");
+    tokio::fs::write(&file_path, "shared long ago").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir],
+        keep_alive_interval: Some(std::time::Duration::from_millis(50)),
+        ..Default::default()
+    };
+
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    // Let a few keep-alive ticks run
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let ticket = daemon.share_file(file_path).await.expect("Failed to share file");
+    assert!(ticket.len() > 10);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_file_ready_hook_fires_for_scan_and_watcher() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_ready_hook_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    tokio::fs::write(media_dir.join("scanned.txt"), "present at startup").await.unwrap();
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        on_file_ready: FileReadyHook::new(move |meta| {
+            let _ = tx.send(meta);
+        }),
+        ..Default::default()
+    };
+
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let wait = std::time::Duration::from_secs(10);
+
+    // Ingestion path
+    let scanned = tokio::time::timeout(wait, rx.recv()).await.unwrap().unwrap();
+    assert!(scanned.path.ends_with("scanned.txt"));
+
+    // Watcher path: the file is imported and reported once servable
+    let watched_path = media_dir.join("watched.txt");
+    tokio::fs::write(&watched_path, "appeared later").await.unwrap();
+
+    let watched = loop {
+        let meta = tokio::time::timeout(wait, rx.recv()).await
+            .expect("Hook never fired for watched file")
+            .unwrap();
+        if meta.path.ends_with("watched.txt") {
+            break meta;
+        }
+    };
+    assert_eq!(
+        daemon.index().get_import_state(&watched.path).unwrap(),
+        Some(ghostdrive_core::ImportState::Ready)
+    );
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_share_files_reports_per_file_results() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_share_files_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    tokio::fs::write(media_dir.join("one.txt"), "first").await.unwrap();
+    tokio::fs::write(media_dir.join("two.txt"), "second").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    let paths = vec![
+        media_dir.join("one.txt"),
+        media_dir.join("missing.txt"),
+        media_dir.join("two.txt"),
+    ];
+    let results = daemon.share_files(paths.clone()).await;
+
+    // Input order is preserved, and the missing file doesn't stop the others
+    assert_eq!(results.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>(), paths);
+    let one = results[0].1.as_ref().expect("Sharing one.txt failed");
+    assert!(results[1].1.is_err());
+    let two = results[2].1.as_ref().expect("Sharing two.txt failed");
+
+    // Each file gets its own ticket
+    assert_ne!(one, two);
+
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_withheld_file_is_not_served() {
+    use ghostdrive_core::{ShareTicket, StreamError};
+    use ghostdrive_network::StreamNode;
+
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_servable_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    let file_path = media_dir.join("private.txt");
+    tokio::fs::write(&file_path, "only sometimes shared").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let receiver = StreamNode::new(test_root.join("receiver")).await.expect("Failed to start receiver");
+
+    let ticket = ShareTicket::decode(&daemon.share_file(file_path.clone()).await.unwrap()).unwrap();
+    let timeout = std::time::Duration::from_secs(10);
+
+    // Withheld: the entry stays indexed, but no tickets and no content
+    daemon.set_servable(file_path.clone(), false).await.unwrap();
+    assert!(daemon.index().get_by_path(&file_path.canonicalize().unwrap()).unwrap().is_some());
+    assert!(matches!(daemon.share_file(file_path.clone()).await, Err(StreamError::NotServable(_))));
+    let denied = tokio::time::timeout(timeout, receiver.fetch_bytes(&ticket, 1024)).await.unwrap();
+    assert!(denied.is_err());
+
+    // Re-enabled: the same ticket works again
+    daemon.set_servable(file_path.clone(), true).await.unwrap();
+    let bytes = tokio::time::timeout(timeout, receiver.fetch_bytes(&ticket, 1024)).await
+        .unwrap()
+        .expect("Fetch failed after re-enabling");
+    assert_eq!(&bytes[..], b"only sometimes shared");
+    assert!(daemon.share_file(file_path).await.is_ok());
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_share_folder_detailed_lists_contents() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_folder_result_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    tokio::fs::write(media_dir.join("a.txt"), "alpha").await.unwrap();
+    tokio::fs::write(media_dir.join("b.txt"), "bravo!").await.unwrap();
+    tokio::fs::write(media_dir.join("c.txt"), "hidden").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    daemon.set_servable(media_dir.join("c.txt"), false).await.unwrap();
+
+    let result = daemon.share_folder_detailed(media_dir.clone()).await.expect("Failed to share folder");
+
+    let names: Vec<_> = result.entries.iter().map(|(name, _, size)| (name.as_str(), *size)).collect();
+    assert_eq!(names, vec![("a.txt", 5), ("b.txt", 6)]);
+    assert_eq!(result.skipped.len(), 1);
+    assert!(result.skipped[0].0.ends_with("c.txt"));
+
+    let ticket = ghostdrive_core::ShareTicket::decode(&result.ticket).unwrap();
+    assert_eq!(ticket.hash, result.collection_hash);
+
+    // The collection carries the file names
+    let manifest = daemon.node().read_named_collection(&result.collection_hash).await.unwrap();
+    let listed: Vec<_> = manifest.entries.iter().map(|e| (e.name.as_str(), e.size)).collect();
+    assert_eq!(listed, names);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_reindex_advances_updated_at() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_updated_at_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    let file_path = media_dir.join("draft.txt");
+    tokio::fs::write(&file_path, "first draft").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let before = daemon.index().get_by_path(&file_path).unwrap().expect("File not indexed");
+
+    // Timestamps have second resolution
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    tokio::fs::write(&file_path, "second draft, longer").await.unwrap();
+    daemon.rescan(media_dir).await.unwrap();
+
+    let after = daemon.index().get_by_path(&file_path).unwrap().unwrap();
+    assert_ne!(after.hash, before.hash);
+    assert_eq!(after.created_at, before.created_at);
+    assert!(after.updated_at > before.updated_at);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_audio_files_transcode_audio_only() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_audio_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    tokio::fs::write(media_dir.join("episode.mp3"), "not really audio").await.unwrap();
+    tokio::fs::write(media_dir.join("clip.mp4"), "not really video").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    assert!(daemon.transcode_options_for(&media_dir.join("episode.mp3")).unwrap().audio_only);
+    assert!(!daemon.transcode_options_for(&media_dir.join("clip.mp4")).unwrap().audio_only);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_rescan_reuses_hash_of_unchanged_files() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_hash_cache_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    let file_path = media_dir.join("movie.mp4");
+    tokio::fs::write(&file_path, "original content").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let indexed = daemon.index().get_by_path(&file_path).unwrap().expect("File not indexed");
+    assert_ne!(indexed.modified_ns, 0);
+
+    // Swap the content behind the index's back: same size, same mtime
+    let mtime = std::fs::metadata(&file_path).unwrap().modified().unwrap();
+    std::fs::write(&file_path, "replaced content").unwrap();
+    std::fs::File::options().write(true).open(&file_path).unwrap().set_modified(mtime).unwrap();
+
+    // The file isn't read again, so the stale hash survives
+    let summary = daemon.rescan(media_dir.clone()).await.unwrap();
+    assert_eq!(summary.unchanged, 1);
+    assert_eq!(daemon.index().get_by_path(&file_path).unwrap().unwrap().hash, indexed.hash);
+
+    // A new mtime is enough to hash it again
+    let later = mtime + std::time::Duration::from_secs(5);
+    std::fs::File::options().write(true).open(&file_path).unwrap().set_modified(later).unwrap();
+    let summary = daemon.rescan(media_dir).await.unwrap();
+    assert_eq!(summary.updated, vec![file_path.clone()]);
+    assert_ne!(daemon.index().get_by_path(&file_path).unwrap().unwrap().hash, indexed.hash);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_concurrent_ingestion_indexes_everything() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_concurrent_ingest_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    for dir in 0..4 {
+        tokio::fs::create_dir_all(media_dir.join(format!("season{}", dir))).await.unwrap();
+    }
+    let mut paths = Vec::new();
+    for i in 0..200 {
+        let path = media_dir.join(format!("season{}", i % 4)).join(format!("clip{:03}.txt", i));
+        tokio::fs::write(&path, format!("clip number {}", i)).await.unwrap();
+        paths.push(path);
+    }
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir],
+        ingest_concurrency: Some(16),
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    let summary = daemon.ingestion_summary();
+    assert_eq!(summary.files, 200);
+    assert_eq!(summary.failed, 0);
+    assert_eq!(daemon.index().count().unwrap(), 200);
+    for path in &paths {
+        let state = daemon.index().get_import_state(path).unwrap();
+        assert_eq!(state, Some(ghostdrive_core::ImportState::Ready), "{:?} not ready", path);
+    }
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_ingestion_progress() {
+    use std::sync::{Arc, Mutex};
+
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_progress_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let movies = test_root.join("movies");
+    let shows = test_root.join("shows");
+    tokio::fs::create_dir_all(&movies).await.unwrap();
+    tokio::fs::create_dir_all(&shows).await.unwrap();
+    for i in 0..12 {
+        let dir = if i % 3 == 0 { &shows } else { &movies };
+        tokio::fs::write(dir.join(format!("file{}.txt", i)), format!("file {}", i)).await.unwrap();
+    }
+
+    let reports: Arc<Mutex<Vec<IngestProgress>>> = Arc::default();
+    let sink = reports.clone();
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![movies, shows],
+        on_ingest_progress: IngestProgressHook::new(move |progress| {
+            sink.lock().unwrap().push(progress.clone());
+        }),
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 12);
+    assert!(reports.iter().all(|p| p.total == 12), "Total should cover every watch path");
+    let scanned: Vec<u64> = reports.iter().map(|p| p.scanned).collect();
+    assert_eq!(scanned, (1..=12).collect::<Vec<u64>>());
+    assert!(reports.iter().all(|p| p.current_path.exists()));
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_shares_are_recorded_and_revocable() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_shares_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+    let file_path = media_dir.join("movie.mp4");
+    tokio::fs::write(&file_path, "movie content").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    assert!(daemon.list_shares().unwrap().is_empty());
+
+    let encoded = daemon.share_file(file_path).await.expect("Failed to share file");
+    let ticket = ghostdrive_core::ShareTicket::decode(&encoded).unwrap();
+
+    let shares = daemon.list_shares().unwrap();
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].ticket, ticket);
+    assert!(shares[0].is_active());
+
+    // The id doesn't depend on how the node could be reached
+    let mut readdressed = ticket.clone();
+    readdressed.direct_addrs = vec!["192.0.2.1:4433".to_string()];
+    assert_eq!(daemon.index().record_share(&readdressed).unwrap().id, shares[0].id);
+    assert_eq!(daemon.list_shares().unwrap().len(), 1);
+
+    let revoked = daemon.revoke_share(&shares[0].id).unwrap().expect("Share not found");
+    assert!(!revoked.is_active());
+    assert!(daemon.revoke_share("unknown").unwrap().is_none());
+
+    // The record is kept, marked revoked
+    assert_eq!(daemon.list_shares().unwrap(), vec![revoked.clone()]);
+    assert_eq!(daemon.index().get_share(&revoked.id).unwrap(), Some(revoked));
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_share_folder_includes_subfolders() {
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_nested_folder_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(media_dir.join("Season 1")).await.unwrap();
+    tokio::fs::create_dir_all(media_dir.join("empty")).await.unwrap();
+    tokio::fs::write(media_dir.join("poster.jpg"), "poster").await.unwrap();
+    tokio::fs::write(media_dir.join("Season 1").join("e01.mkv"), "episode one").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+
+    let result = daemon.share_folder_detailed(media_dir.clone()).await.expect("Failed to share folder");
+    assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+
+    let manifest = daemon.node().read_named_collection(&result.collection_hash).await.unwrap();
+    let listed: Vec<_> = manifest.entries.iter().map(|e| (e.name.as_str(), e.size)).collect();
+    assert_eq!(listed, vec![("Season 1/e01.mkv", 11), ("poster.jpg", 6)]);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_index_limit_keeps_folder_share_members() {
+    use ghostdrive_host::IndexLimit;
+
+    let test_root = std::env::temp_dir().join("ghostdrive_daemon_limit_share_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+
+    let media_dir = test_root.join("media");
+    let album = media_dir.join("album");
+    tokio::fs::create_dir_all(&album).await.unwrap();
+    tokio::fs::write(album.join("one.flac"), "track one").await.unwrap();
+    tokio::fs::write(album.join("two.flac"), "track two").await.unwrap();
+
+    let config = HostConfig {
+        data_dir: test_root.join("data"),
+        watch_paths: vec![media_dir.clone()],
+        index_limit: Some(IndexLimit { max_files: 2, policy: Default::default(), unshare_evicted: true }),
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let shared = daemon.share_folder_detailed(album.clone()).await.expect("Failed to share folder");
+    assert_eq!(shared.entries.len(), 2);
+
+    // New files push the index over the limit
+    tokio::fs::write(media_dir.join("three.flac"), "track three").await.unwrap();
+    tokio::fs::write(media_dir.join("four.flac"), "track four").await.unwrap();
+    daemon.rescan(media_dir.clone()).await.unwrap();
+
+    // The shared members stay indexed and served, the others made room
+    let index = daemon.index();
+    assert_eq!(index.count().unwrap(), 2);
+    for (_, hash, _) in &shared.entries {
+        assert!(index.get_by_hash(hash).unwrap().is_some());
+        assert!(daemon.node().has_blob(hash).await.unwrap());
+    }
+
+    // Cleanup
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
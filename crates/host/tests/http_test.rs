@@ -0,0 +1,161 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_core::FileMetadata;
+use ghostdrive_host::stream_router;
+use ghostdrive_indexer::{hash_file, FileIndex, DEFAULT_HASH_BUFFER_SIZE};
+use ghostdrive_transcoder::TranscodeOptions;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// MPEG-TS packets start with this sync byte
+const TS_SYNC_BYTE: u8 = 0x47;
+
+/// Send a GET and read the whole response as (lowercased headers, body)
+async fn get(addr: SocketAddr, path: &str, range: Option<&str>) -> (String, Vec<u8>) {
+    let mut conn = TcpStream::connect(addr).await.unwrap();
+    let range = range.map(|r| format!("Range: {}\r\n", r)).unwrap_or_default();
+    let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n{}Connection: close\r\n\r\n", path, addr, range);
+    conn.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = Vec::new();
+    timeout(Duration::from_secs(5), conn.read_to_end(&mut response))
+        .await
+        .expect("Timed out waiting for response")
+        .unwrap();
+    let header_end = response.windows(4).position(|w| w == b"\r\n\r\n").expect("No header terminator") + 4;
+    let headers = String::from_utf8_lossy(&response[..header_end]).to_lowercase();
+    (headers, response[header_end..].to_vec())
+}
+
+#[tokio::test]
+async fn test_stream_serves_mpegts() {
+    let test_root = std::env::temp_dir().join("ghostdrive_http_stream_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+    tokio::fs::create_dir_all(&test_root).await.unwrap();
+
+    let video = test_root.join("clip.mp4");
+    let generated = Command::new("ffmpeg")
+        .args(["-f", "lavfi", "-i", "testsrc=duration=3:size=320x240:rate=25", "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+        .arg(&video)
+        .output()
+        .await
+        .expect("Failed to run ffmpeg generator");
+    assert!(generated.status.success(), "Failed to generate test video");
+
+    let index = Arc::new(FileIndex::open(test_root.join("index.db")).unwrap());
+    let hash = hash_file(&video, DEFAULT_HASH_BUFFER_SIZE).unwrap();
+    index.upsert_file(&FileMetadata {
+        path: video.clone(),
+        hash: hash.clone(),
+        size: std::fs::metadata(&video).unwrap().len(),
+        mime_type: "video/mp4".into(),
+        created_at: 0,
+        updated_at: 0,
+        modified_ns: 0,
+    }).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let options = TranscodeOptions { resolution: Some("320x240".into()), ..Default::default() };
+    tokio::spawn(async move {
+        axum::serve(listener, stream_router(index, options)).await.unwrap();
+    });
+
+    let mut conn = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET /stream/{} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", hash, addr);
+    conn.write_all(request.as_bytes()).await.unwrap();
+
+    // Read until the headers and a good part of the first chunk are in
+    let mut response = Vec::new();
+    let mut buf = [0u8; 8192];
+    timeout(Duration::from_secs(10), async {
+        while response.len() < 4096 {
+            let n = conn.read(&mut buf).await.unwrap();
+            assert!(n > 0, "Stream ended early");
+            response.extend_from_slice(&buf[..n]);
+        }
+    }).await.expect("Timed out waiting for stream data");
+
+    let header_end = response.windows(4).position(|w| w == b"\r\n\r\n").expect("No header terminator") + 4;
+    let headers = String::from_utf8_lossy(&response[..header_end]).to_lowercase();
+    assert!(headers.starts_with("http/1.1 200"), "{}", headers);
+    assert!(headers.contains("content-type: video/mp2t"), "{}", headers);
+
+    // Skip the chunk size line of the chunked body
+    let body = &response[header_end..];
+    let chunk_start = body.windows(2).position(|w| w == b"\r\n").unwrap() + 2;
+    assert_eq!(body[chunk_start], TS_SYNC_BYTE, "Body is not MPEG-TS");
+
+    // Hanging up mid-stream is fine for the server
+    drop(conn);
+
+    let (headers, _) = get(addr, &format!("/stream/{}", "0".repeat(64)), None).await;
+    assert!(headers.starts_with("http/1.1 404"), "{}", headers);
+
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
+
+#[tokio::test]
+async fn test_file_range_requests() {
+    let test_root = std::env::temp_dir().join("ghostdrive_http_range_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+    tokio::fs::create_dir_all(&test_root).await.unwrap();
+
+    let path = test_root.join("movie.mp4");
+    let content: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&path, &content).unwrap();
+
+    let index = Arc::new(FileIndex::open(test_root.join("index.db")).unwrap());
+    let hash = hash_file(&path, DEFAULT_HASH_BUFFER_SIZE).unwrap();
+    index.upsert_file(&FileMetadata {
+        path: path.clone(),
+        hash: hash.clone(),
+        size: content.len() as u64,
+        mime_type: "video/mp4".into(),
+        created_at: 0,
+        updated_at: 0,
+        modified_ns: 0,
+    }).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, stream_router(index, TranscodeOptions::default())).await.unwrap();
+    });
+    let file_path = format!("/file/{}", hash);
+
+    // Whole file
+    let (headers, body) = get(addr, &file_path, None).await;
+    assert!(headers.starts_with("http/1.1 200"), "{}", headers);
+    assert!(headers.contains("accept-ranges: bytes"), "{}", headers);
+    assert!(headers.contains("content-type: video/mp4"), "{}", headers);
+    assert_eq!(body, content);
+
+    // A slice
+    let (headers, body) = get(addr, &file_path, Some("bytes=100-199")).await;
+    assert!(headers.starts_with("http/1.1 206"), "{}", headers);
+    assert!(headers.contains("content-range: bytes 100-199/1000"), "{}", headers);
+    assert!(headers.contains("content-length: 100"), "{}", headers);
+    assert_eq!(body, &content[100..200]);
+
+    // Open-ended, suffix and multi-range requests (only the first range is served)
+    let (headers, body) = get(addr, &file_path, Some("bytes=900-")).await;
+    assert!(headers.contains("content-range: bytes 900-999/1000"), "{}", headers);
+    assert_eq!(body, &content[900..]);
+    let (_, body) = get(addr, &file_path, Some("bytes=-10")).await;
+    assert_eq!(body, &content[990..]);
+    let (headers, body) = get(addr, &file_path, Some("bytes=0-9, 50-59")).await;
+    assert!(headers.contains("content-range: bytes 0-9/1000"), "{}", headers);
+    assert_eq!(body, &content[..10]);
+
+    // Past the end
+    let (headers, body) = get(addr, &file_path, Some("bytes=5000-6000")).await;
+    assert!(headers.starts_with("http/1.1 416"), "{}", headers);
+    assert!(headers.contains("content-range: bytes */1000"), "{}", headers);
+    assert!(body.is_empty());
+
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
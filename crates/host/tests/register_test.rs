@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_core::ImportState;
+use ghostdrive_host::{Importer, Registered};
+use ghostdrive_indexer::FileIndex;
+use ghostdrive_network::{ImportStrategy, StreamNode};
+
+#[tokio::test]
+async fn test_second_pass_skips_unchanged_files() {
+    let test_root = std::env::temp_dir().join("ghostdrive_register_unchanged_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+    let media_dir = test_root.join("media");
+    tokio::fs::create_dir_all(&media_dir).await.unwrap();
+
+    let mut files = Vec::new();
+    for i in 0..5 {
+        let path = media_dir.join(format!("episode{}.mkv", i));
+        tokio::fs::write(&path, format!("episode {} content", i)).await.unwrap();
+        files.push(path);
+    }
+
+    let index = Arc::new(FileIndex::open(test_root.join("index.db")).unwrap());
+    let node = Arc::new(StreamNode::new(test_root.join("node")).await.unwrap());
+    let importer = Importer::new(index.clone(), node.clone(), ImportStrategy::Reference);
+
+    for path in &files {
+        let registered = importer.register_file(path).await.unwrap();
+        assert!(matches!(registered, Registered::New(_)), "{:?}", registered);
+    }
+    let first_pass = index.list_all().unwrap();
+
+    // Nothing changed: nothing is imported or written again
+    for path in &files {
+        let registered = importer.register_file(path).await.unwrap();
+        assert!(matches!(registered, Registered::Unchanged(_)), "{:?}", registered);
+        assert_eq!(index.get_import_state(path).unwrap(), Some(ImportState::Ready));
+    }
+    assert_eq!(index.list_all().unwrap(), first_pass);
+
+    // A touched file is registered again
+    let touched = std::fs::metadata(&files[0]).unwrap().modified().unwrap() + Duration::from_secs(5);
+    std::fs::File::options().write(true).open(&files[0]).unwrap().set_modified(touched).unwrap();
+    let registered = importer.register_file(&files[0]).await.unwrap();
+    assert!(matches!(registered, Registered::Updated(_)), "{:?}", registered);
+
+    // As is one whose blob went missing from the store
+    node.delete_blob(&first_pass[1].hash).await.unwrap();
+    let registered = importer.register_file(&first_pass[1].path).await.unwrap();
+    assert!(matches!(registered, Registered::Updated(_)), "{:?}", registered);
+    assert!(node.has_blob(&first_pass[1].hash).await.unwrap());
+
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
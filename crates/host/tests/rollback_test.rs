@@ -0,0 +1,61 @@
+use std::path::Path;
+use std::sync::Arc;
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash, StreamError, StreamResult};
+use ghostdrive_host::Importer;
+use ghostdrive_indexer::{FileIndex, FileStore};
+use ghostdrive_network::{ImportStrategy, StreamNode};
+
+/// Index whose metadata writes always fail
+struct FailingIndex(FileIndex);
+
+impl FileStore for FailingIndex {
+    fn upsert_file(&self, _metadata: &FileMetadata) -> StreamResult<()> {
+        Err(StreamError::Database("injected write failure".to_string()))
+    }
+
+    fn get_by_path(&self, path: &Path) -> StreamResult<Option<FileMetadata>> {
+        self.0.get_by_path(path)
+    }
+
+    fn get_by_hash(&self, hash: &MediaHash) -> StreamResult<Option<FileMetadata>> {
+        self.0.get_by_hash(hash)
+    }
+
+    fn remove_file(&self, path: &Path) -> StreamResult<()> {
+        self.0.remove_file(path)
+    }
+
+    fn set_import_state(&self, path: &Path, state: ImportState) -> StreamResult<()> {
+        self.0.set_import_state(path, state)
+    }
+
+    fn get_import_state(&self, path: &Path) -> StreamResult<Option<ImportState>> {
+        self.0.get_import_state(path)
+    }
+}
+
+#[tokio::test]
+async fn test_failed_index_write_rolls_back_import() {
+    let test_root = std::env::temp_dir().join("ghostdrive_rollback_test");
+    let _ = tokio::fs::remove_dir_all(&test_root).await;
+    tokio::fs::create_dir_all(&test_root).await.unwrap();
+
+    let file_path = test_root.join("orphan.txt");
+    tokio::fs::write(&file_path, "would be orphaned").await.unwrap();
+
+    let index = Arc::new(FailingIndex(FileIndex::open(test_root.join("index.db")).unwrap()));
+    let node = Arc::new(StreamNode::new(test_root.join("node")).await.unwrap());
+
+    for strategy in [ImportStrategy::Reference, ImportStrategy::Copy] {
+        let importer = Importer::new(index.clone(), node.clone(), strategy);
+
+        let result = importer.register_file(&file_path).await;
+        assert!(matches!(result, Err(StreamError::Database(_))), "Expected index failure, got {:?}", result);
+
+        // The store holds no blob without an index entry, and the failure is recorded
+        assert!(node.list_blobs().await.unwrap().is_empty(), "Orphaned blob left with {:?}", strategy);
+        assert_eq!(index.get_import_state(&file_path).unwrap(), Some(ImportState::Failed));
+    }
+
+    let _ = tokio::fs::remove_dir_all(test_root).await;
+}
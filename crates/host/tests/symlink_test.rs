@@ -0,0 +1,131 @@
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use ghostdrive_host::{HostConfig, HostDaemon};
+use ghostdrive_indexer::SymlinkPolicy;
+
+/// Layout: media/{real.txt, file_link -> real.txt, dir_link -> ../outside}, outside/inner.txt
+async fn setup(root: &Path) -> PathBuf {
+    let _ = tokio::fs::remove_dir_all(root).await;
+    let media = root.join("media");
+    let outside = root.join("outside");
+    tokio::fs::create_dir_all(&media).await.unwrap();
+    tokio::fs::create_dir_all(&outside).await.unwrap();
+
+    tokio::fs::write(media.join("real.txt"), "real content").await.unwrap();
+    tokio::fs::write(outside.join("inner.txt"), "outside content").await.unwrap();
+    std::os::unix::fs::symlink(media.join("real.txt"), media.join("file_link")).unwrap();
+    std::os::unix::fs::symlink(&outside, media.join("dir_link")).unwrap();
+
+    media
+}
+
+async fn start(root: &Path, media: &Path, policy: SymlinkPolicy) -> HostDaemon {
+    let config = HostConfig {
+        data_dir: root.join("data"),
+        watch_paths: vec![media.to_path_buf()],
+        symlink_policy: policy,
+        ..Default::default()
+    };
+    HostDaemon::new(config).await.expect("Failed to start daemon")
+}
+
+#[tokio::test]
+async fn test_symlink_follow() {
+    let root = std::env::temp_dir().join("ghostdrive_symlink_follow_test");
+    let media = setup(&root).await;
+    let daemon = start(&root, &media, SymlinkPolicy::Follow).await;
+    let index = daemon.index();
+
+    let real = index.get_by_path(&media.join("real.txt")).unwrap().unwrap();
+    let linked = index.get_by_path(&media.join("file_link")).unwrap().unwrap();
+    assert_eq!(real.hash, linked.hash);
+    assert!(index.get_by_path(&media.join("dir_link/inner.txt")).unwrap().is_some());
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(root).await;
+}
+
+#[tokio::test]
+async fn test_symlink_ignore() {
+    let root = std::env::temp_dir().join("ghostdrive_symlink_ignore_test");
+    let media = setup(&root).await;
+    let daemon = start(&root, &media, SymlinkPolicy::Ignore).await;
+    let index = daemon.index();
+
+    assert!(index.get_by_path(&media.join("real.txt")).unwrap().is_some());
+    assert!(index.get_by_path(&media.join("file_link")).unwrap().is_none());
+    assert!(index.get_by_path(&media.join("dir_link")).unwrap().is_none());
+    assert!(index.get_by_path(&media.join("dir_link/inner.txt")).unwrap().is_none());
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(root).await;
+}
+
+#[tokio::test]
+async fn test_symlink_index_as_link() {
+    let root = std::env::temp_dir().join("ghostdrive_symlink_link_test");
+    let media = setup(&root).await;
+    let daemon = start(&root, &media, SymlinkPolicy::IndexAsLink).await;
+    let index = daemon.index();
+
+    assert_eq!(index.get_link(&media.join("file_link")).unwrap(), Some(media.join("real.txt")));
+    assert_eq!(index.get_link(&media.join("dir_link")).unwrap(), Some(root.join("outside")));
+    assert!(index.get_by_path(&media.join("dir_link/inner.txt")).unwrap().is_none());
+
+    // Links have no content, so they stay out of the file entries
+    assert!(index.get_by_path(&media.join("file_link")).unwrap().is_none());
+    assert!(index.get_by_path(&media.join("dir_link")).unwrap().is_none());
+    assert_eq!(index.count().unwrap(), 1);
+    let real = index.get_by_path(&media.join("real.txt")).unwrap().unwrap();
+    assert_eq!(index.get_all_paths_by_hash(&real.hash).unwrap(), vec![media.join("real.txt")]);
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(root).await;
+}
+
+#[tokio::test]
+async fn test_symlink_loop_scan_terminates() {
+    let root = std::env::temp_dir().join("ghostdrive_symlink_loop_test");
+    let _ = tokio::fs::remove_dir_all(&root).await;
+    let media = root.join("media");
+    tokio::fs::create_dir_all(media.join("sub")).await.unwrap();
+    tokio::fs::write(media.join("sub/clip.txt"), "clip").await.unwrap();
+    // media/sub/back -> media: following it naively never ends
+    std::os::unix::fs::symlink(&media, media.join("sub/back")).unwrap();
+
+    for policy in [SymlinkPolicy::default(), SymlinkPolicy::Follow, SymlinkPolicy::Ignore] {
+        let _ = tokio::fs::remove_dir_all(root.join("data")).await;
+        let daemon = tokio::time::timeout(std::time::Duration::from_secs(30), start(&root, &media, policy))
+            .await
+            .expect("Scan of a symlink loop did not terminate");
+
+        assert!(daemon.index().get_by_path(&media.join("sub/clip.txt")).unwrap().is_some());
+        drop(daemon);
+    }
+
+    let _ = tokio::fs::remove_dir_all(root).await;
+}
+
+#[tokio::test]
+async fn test_default_policy_stays_in_tree() {
+    let root = std::env::temp_dir().join("ghostdrive_symlink_default_test");
+    let media = setup(&root).await;
+    std::fs::create_dir_all(media.join("sub")).unwrap();
+    std::os::unix::fs::symlink(&media, media.join("sub/back")).unwrap();
+
+    let config = HostConfig {
+        data_dir: root.join("data"),
+        watch_paths: vec![media.clone()],
+        ..Default::default()
+    };
+    let daemon = HostDaemon::new(config).await.expect("Failed to start daemon");
+    let index = daemon.index();
+
+    assert!(index.get_by_path(&media.join("real.txt")).unwrap().is_some());
+    assert!(index.get_by_path(&media.join("dir_link/inner.txt")).unwrap().is_none());
+    assert!(index.get_by_path(&media.join("sub/back/real.txt")).unwrap().is_none());
+
+    drop(daemon);
+    let _ = tokio::fs::remove_dir_all(root).await;
+}
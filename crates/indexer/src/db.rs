@@ -1,16 +1,87 @@
 use std::path::PathBuf;
-use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
-use ghostdrive_core::{FileMetadata, MediaHash, StreamError, StreamResult};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use redb::{Database, ReadableDatabase, ReadableTable, ReadableTableMetadata, Table, TableDefinition, TableHandle};
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash, ShareRecord, ShareTicket, StreamError, StreamResult};
+use serde::Deserialize;
 use tracing::{debug, info};
 
 /// Table: File Path (String) -> Serialized FileMetadata (Bytes)
 const FILES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("files");
 
-/// Table: Content Hash (String) -> File Path (String)
-const HASH_INDEX: TableDefinition<&str, &str> = TableDefinition::new("hash_index");
+/// Table: Content Hash (String) -> Serialized Vec<String> of File Paths (Bytes)
+const HASH_INDEX: TableDefinition<&str, &[u8]> = TableDefinition::new("hash_paths");
+
+/// Table: Content Hash (String) -> File Path (String), replaced by `HASH_INDEX`
+const LEGACY_HASH_INDEX: &str = "hash_index";
+
+/// Table: File Path (String) -> Serialized ImportState (Bytes)
+const IMPORT_STATE: TableDefinition<&str, &[u8]> = TableDefinition::new("import_state");
+
+/// Table: File Path (String) of files that are indexed but not served
+const WITHHELD: TableDefinition<&str, ()> = TableDefinition::new("withheld");
+
+/// Table: Share Id (String) -> JSON ShareRecord (Bytes)
+///
+/// JSON rather than bincode: tickets skip unset fields when serialized.
+const SHARES: TableDefinition<&str, &[u8]> = TableDefinition::new("shares");
+
+/// Table: Link Path (String) -> Link Target (String), for links indexed without following them
+///
+/// Kept apart from `FILES_TABLE` so links never show up as content.
+const LINKS: TableDefinition<&str, &str> = TableDefinition::new("links");
+
+/// Hex characters of the hash kept as a share id
+const SHARE_ID_LEN: usize = 16;
+
+/// Table: Key (String) -> Value (u64), index-wide settings such as the schema version
+const META_TABLE: TableDefinition<&str, u64> = TableDefinition::new("meta");
+
+/// Layout version of the rows in `FILES_TABLE`
+///
+/// 1: no `updated_at` or `modified_ns`, 2: both added
+const SCHEMA_VERSION: u64 = 2;
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// `FileMetadata` as written by schema version 1
+#[derive(Deserialize)]
+struct FileMetadataV1 {
+    path: PathBuf,
+    hash: MediaHash,
+    size: u64,
+    mime_type: String,
+    created_at: u64,
+}
+
+impl From<FileMetadataV1> for FileMetadata {
+    fn from(old: FileMetadataV1) -> Self {
+        Self {
+            path: old.path,
+            hash: old.hash,
+            size: old.size,
+            mime_type: old.mime_type,
+            created_at: old.created_at,
+            // Best guess: last indexed when it was created
+            updated_at: old.created_at,
+            modified_ns: 0,
+        }
+    }
+}
+
+/// Which entries go first when the index is over its size cap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Evict the files with the oldest `created_at`
+    #[default]
+    OldestCreated,
+}
 
 pub struct FileIndex {
-    db: Database
+    /// Write-locked only while compacting, which redb does through `&mut`
+    db: RwLock<Database>,
+    /// Rows written since the last compaction (not persisted)
+    writes_since_compact: AtomicU64,
 }
 
 impl FileIndex {
@@ -19,7 +90,7 @@ impl FileIndex {
         info!("Opening database at: {:?}", path);
 
         if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(StreamError::Io)?;
+            std::fs::create_dir_all(parent).map_err(StreamError::from)?;
         }
 
         let db = Database::create(&path).map_err(|e| StreamError::Database(e.to_string()))?;
@@ -30,23 +101,128 @@ impl FileIndex {
             // Just opening the table initializes them
             let _ = txn.open_table(FILES_TABLE).map_err(|e| StreamError::Database(e.to_string()))?;
             let _ = txn.open_table(HASH_INDEX).map_err(|e| StreamError::Database(e.to_string()))?;
+            let _ = txn.open_table(IMPORT_STATE).map_err(|e| StreamError::Database(e.to_string()))?;
+            let _ = txn.open_table(WITHHELD).map_err(|e| StreamError::Database(e.to_string()))?;
+            let _ = txn.open_table(META_TABLE).map_err(|e| StreamError::Database(e.to_string()))?;
+            let _ = txn.open_table(SHARES).map_err(|e| StreamError::Database(e.to_string()))?;
+            let _ = txn.open_table(LINKS).map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let index = Self { db: RwLock::new(db), writes_since_compact: AtomicU64::new(0) };
+        index.migrate_schema()?;
+        index.migrate_hash_index()?;
+        Ok(index)
+    }
+
+    /// Upgrade rows written in an older layout to the current `FileMetadata`
+    ///
+    /// Databases without a recorded version are new if empty and version 1
+    /// otherwise. Databases from a newer version are refused rather than
+    /// misread.
+    fn migrate_schema(&self) -> StreamResult<()> {
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let mut meta_table = txn.open_table(META_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut files_table = txn.open_table(FILES_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let recorded = meta_table.get(SCHEMA_VERSION_KEY)
+                .map_err(|e| StreamError::Database(e.to_string()))?
+                .map(|access| access.value());
+            let version = match recorded {
+                Some(version) => version,
+                None if files_table.is_empty().map_err(|e| StreamError::Database(e.to_string()))? => SCHEMA_VERSION,
+                None => 1,
+            };
+
+            if version > SCHEMA_VERSION {
+                return Err(StreamError::Database(format!(
+                    "Index schema version {} is newer than supported version {}",
+                    version, SCHEMA_VERSION
+                )));
+            }
+
+            if version < SCHEMA_VERSION {
+                info!("Upgrading index schema from version {} to {}", version, SCHEMA_VERSION);
+                let config = bincode::config::standard();
+
+                let mut upgraded = Vec::new();
+                for entry in files_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+                    let (key, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+                    let (old, _) = bincode::serde::decode_from_slice::<FileMetadataV1, _>(value.value(), config)
+                        .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                    upgraded.push((key.value().to_string(), FileMetadata::from(old)));
+                }
+
+                for (key, metadata) in upgraded {
+                    let encoded = bincode::serde::encode_to_vec(&metadata, config)
+                        .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+                    files_table.insert(key.as_str(), encoded.as_slice())
+                        .map_err(|e| StreamError::Database(e.to_string()))?;
+                }
+            }
+
+            meta_table.insert(SCHEMA_VERSION_KEY, SCHEMA_VERSION)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Replace the single-path hash index of older versions
+    ///
+    /// The old table kept one path per hash, so it is rebuilt from the files
+    /// table rather than copied.
+    fn migrate_hash_index(&self) -> StreamResult<()> {
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let has_legacy = txn.list_tables()
+            .map_err(|e| StreamError::Database(e.to_string()))?
+            .any(|table| table.name() == LEGACY_HASH_INDEX);
+        if !has_legacy {
+            txn.abort().map_err(|e| StreamError::Database(e.to_string()))?;
+            return Ok(());
+        }
+
+        info!("Rebuilding hash index to track every path per hash");
+        {
+            let files_table = txn.open_table(FILES_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut hash_table = txn.open_table(HASH_INDEX)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let config = bincode::config::standard();
+            for entry in files_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+                let (_, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+                let (metadata, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(value.value(), config)
+                    .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                add_hash_path(&mut hash_table, &metadata.hash, &metadata.path.to_string_lossy())?;
+            }
         }
+        txn.delete_table(TableDefinition::<&str, &str>::new(LEGACY_HASH_INDEX))
+            .map_err(|e| StreamError::Database(e.to_string()))?;
         txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
 
-        Ok(Self { db })
+        Ok(())
     }
 
     /// Insert or update a file's metadata
     pub fn upsert_file(&self, metadata: &FileMetadata) -> StreamResult<()> {
-        let path_str = metadata.path.to_string_lossy();
-        let hash_str = metadata.hash.0.as_str();
+        self.upsert_files(std::slice::from_ref(metadata))?;
+        debug!("Inserted file: {:?}", metadata.path);
+        Ok(())
+    }
 
-        // Serialize FileMetadata
+    /// Insert or update many files in one transaction
+    pub fn upsert_files(&self, files: &[FileMetadata]) -> StreamResult<()> {
         let config = bincode::config::standard();
-        let encoded = bincode::serde::encode_to_vec(metadata, config)
-            .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
 
-        let txn = self.db.begin_write()
+        let txn = self.db().begin_write()
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
         {
@@ -55,24 +231,45 @@ impl FileIndex {
             let mut hash_table = txn.open_table(HASH_INDEX)
                 .map_err(|e| StreamError::Database(e.to_string()))?;
 
-            // Insert into FILES_TABLE (Path -> Metadata)
-            files_table.insert(path_str.as_ref(), encoded.as_slice())
-                .map_err(|e| StreamError::Database(e.to_string()))?;
+            for metadata in files {
+                let path_str = metadata.path.to_string_lossy();
 
-            // Insert into HASH_INDEX (Hash -> Path)
-            hash_table.insert(hash_str, path_str.as_ref())
-                .map_err(|e| StreamError::Database(e.to_string()))?;
+                // Drop the reverse mapping of content this path no longer has
+                let previous_hash = match files_table.get(path_str.as_ref())
+                    .map_err(|e| StreamError::Database(e.to_string()))?
+                {
+                    Some(access) => {
+                        let (previous, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                            .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                        Some(previous.hash)
+                    }
+                    None => None,
+                };
+                if let Some(previous_hash) = previous_hash.filter(|h| *h != metadata.hash) {
+                    remove_hash_path(&mut hash_table, &previous_hash, &path_str)?;
+                }
+
+                // Serialize FileMetadata
+                let encoded = bincode::serde::encode_to_vec(metadata, config)
+                    .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+
+                // Insert into FILES_TABLE (Path -> Metadata)
+                files_table.insert(path_str.as_ref(), encoded.as_slice())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+
+                // Add to HASH_INDEX (Hash -> Paths)
+                add_hash_path(&mut hash_table, &metadata.hash, &path_str)?;
+            }
         }
 
         txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
-
-        debug!("Inserted file: {:?}", metadata.path);
+        self.record_writes(files.len());
         Ok(())
     }
 
     /// Get file metadata by path
     pub fn get_by_path(&self, path: &std::path::Path) -> StreamResult<Option<FileMetadata>> {
-        let txn = self.db.begin_read()
+        let txn = self.db().begin_read()
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
         let files_table = txn.open_table(FILES_TABLE)
@@ -95,8 +292,10 @@ impl FileIndex {
     }
 
     /// Get file metadata by hash (reverse lookup)
+    ///
+    /// If several paths share the content, the first one still indexed is returned.
     pub fn get_by_hash(&self, hash: &MediaHash) -> StreamResult<Option<FileMetadata>> {
-        let txn = self.db.begin_read()
+        let txn = self.db().begin_read()
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
         let hash_table = txn.open_table(HASH_INDEX)
@@ -105,14 +304,9 @@ impl FileIndex {
         let files_table = txn.open_table(FILES_TABLE)
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
-        // Lookup path in HASH_INDEX
-        if let Some(path_access) = hash_table.get(hash.0.as_str())
-            .map_err(|e| StreamError::Database(e.to_string()))?
-        {
-            let path_str = path_access.value();
-
-            // Query FILES_TABLE
-            if let Some(file_access) = files_table.get(path_str)
+        // Lookup paths in HASH_INDEX, then query FILES_TABLE
+        for path_str in hash_paths(&hash_table, hash)? {
+            if let Some(file_access) = files_table.get(path_str.as_str())
                 .map_err(|e| StreamError::Database(e.to_string()))?
             {
                 let config = bincode::config::standard();
@@ -126,9 +320,74 @@ impl FileIndex {
         Ok(None)
     }
 
-    /// Remove a file from index
+    /// Every indexed path whose content has `hash`
+    pub fn get_all_paths_by_hash(&self, hash: &MediaHash) -> StreamResult<Vec<PathBuf>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let hash_table = txn.open_table(HASH_INDEX)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        Ok(hash_paths(&hash_table, hash)?.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Record a link indexed without following it, returning its previous target
+    ///
+    /// Links are kept out of the file entries: they have no content of their
+    /// own, so they never get a hash or show up in hash lookups.
+    pub fn upsert_link(&self, path: &std::path::Path, target: &std::path::Path) -> StreamResult<Option<PathBuf>> {
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let previous = {
+            let mut links_table = txn.open_table(LINKS)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            links_table.insert(path.to_string_lossy().as_ref(), target.to_string_lossy().as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?
+                .map(|access| PathBuf::from(access.value()))
+        };
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(1);
+        debug!("Indexed link: {:?} -> {:?}", path, target);
+
+        Ok(previous)
+    }
+
+    /// Target of a link indexed without following it
+    pub fn get_link(&self, path: &std::path::Path) -> StreamResult<Option<PathBuf>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let links_table = txn.open_table(LINKS)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let target = links_table.get(path.to_string_lossy().as_ref())
+            .map_err(|e| StreamError::Database(e.to_string()))?
+            .map(|access| PathBuf::from(access.value()));
+
+        Ok(target)
+    }
+
+    /// All indexed links with their targets, in path order
+    pub fn list_links(&self) -> StreamResult<Vec<(PathBuf, PathBuf)>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let links_table = txn.open_table(LINKS)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let mut links = Vec::new();
+        for entry in links_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (key, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            links.push((PathBuf::from(key.value()), PathBuf::from(value.value())));
+        }
+
+        Ok(links)
+    }
+
+    /// Remove a file or link from index
     pub fn remove_file(&self, path: &std::path::Path) -> StreamResult<()> {
-        let txn = self.db.begin_write()
+        let txn = self.db().begin_write()
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
         let path_str = path.to_string_lossy();
@@ -156,39 +415,445 @@ impl FileIndex {
                 .map_err(|e| StreamError::Database(e.to_string()))?;
             let mut hash_table = txn.open_table(HASH_INDEX)
                 .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut state_table = txn.open_table(IMPORT_STATE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut withheld_table = txn.open_table(WITHHELD)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut links_table = txn.open_table(LINKS)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            // Remove import state
+            state_table.remove(path_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            // Remove serving flag
+            withheld_table.remove(path_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
 
             // Remove from files table
             files_table.remove(path_str.as_ref())
                 .map_err(|e| StreamError::Database(e.to_string()))?;
 
-            // Remove from hash index
+            // Or from the links, if it was indexed as one
+            links_table.remove(path_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            // Remove from hash index, dropping the hash once no path has it
             if let Some(hash) = hash_to_remove {
-                hash_table.remove(hash.0.as_str())
-                    .map_err(|e| StreamError::Database(e.to_string()))?;
+                remove_hash_path(&mut hash_table, &hash, &path_str)?;
             }
         }
 
         txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(1);
         debug!("Removed file: {:?}", path);
         Ok(())
     }
 
-    /// List all indexed files
+    /// Remove every file with content `hash` from the index
+    ///
+    /// Each path recorded for the hash loses its entry, import state and
+    /// serving flag, all in one transaction; other hashes are untouched.
+    /// Returns whether anything was removed.
+    pub fn remove_by_hash(&self, hash: &MediaHash) -> StreamResult<bool> {
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let paths = {
+            let mut files_table = txn.open_table(FILES_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut hash_table = txn.open_table(HASH_INDEX)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut state_table = txn.open_table(IMPORT_STATE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut withheld_table = txn.open_table(WITHHELD)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let paths = hash_paths(&hash_table, hash)?;
+            for path in &paths {
+                files_table.remove(path.as_str())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+                state_table.remove(path.as_str())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+                withheld_table.remove(path.as_str())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            }
+            hash_table.remove(hash.0.as_str())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            paths
+        };
+
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        if paths.is_empty() {
+            return Ok(false);
+        }
+        self.record_writes(paths.len());
+        debug!("Removed {} file(s) with hash {:#}", paths.len(), hash);
+        Ok(true)
+    }
+
+    /// Move an indexed file to a new path, keeping its metadata and hash
+    ///
+    /// The import state and serving flag move along with it, and any entry
+    /// already at `to` is replaced. Returns the moved metadata, or `None` if
+    /// `from` isn't indexed.
+    pub fn rename_file(&self, from: &std::path::Path, to: &std::path::Path) -> StreamResult<Option<FileMetadata>> {
+        let config = bincode::config::standard();
+        let from_str = from.to_string_lossy();
+        let to_str = to.to_string_lossy();
+
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let moved = {
+            let mut files_table = txn.open_table(FILES_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut hash_table = txn.open_table(HASH_INDEX)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut state_table = txn.open_table(IMPORT_STATE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            let mut withheld_table = txn.open_table(WITHHELD)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let mut meta = match files_table.remove(from_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?
+            {
+                Some(access) => {
+                    let (meta, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                        .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                    meta
+                }
+                None => return Ok(None),
+            };
+
+            // Whatever was at the destination has been overwritten
+            let replaced = match files_table.get(to_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?
+            {
+                Some(access) => {
+                    let (replaced, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                        .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                    Some(replaced.hash)
+                }
+                None => None,
+            };
+            if let Some(hash) = replaced {
+                remove_hash_path(&mut hash_table, &hash, &to_str)?;
+            }
+
+            meta.path = to.to_path_buf();
+            let encoded = bincode::serde::encode_to_vec(&meta, config)
+                .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+            files_table.insert(to_str.as_ref(), encoded.as_slice())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            remove_hash_path(&mut hash_table, &meta.hash, &from_str)?;
+            add_hash_path(&mut hash_table, &meta.hash, &to_str)?;
+
+            // Carry over the import state
+            let state = state_table.remove(from_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?
+                .map(|access| access.value().to_vec());
+            match state {
+                Some(state) => {
+                    state_table.insert(to_str.as_ref(), state.as_slice())
+                        .map_err(|e| StreamError::Database(e.to_string()))?;
+                }
+                None => {
+                    state_table.remove(to_str.as_ref())
+                        .map_err(|e| StreamError::Database(e.to_string()))?;
+                }
+            }
+
+            // And the serving flag
+            let withheld = withheld_table.remove(from_str.as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?
+                .is_some();
+            if withheld {
+                withheld_table.insert(to_str.as_ref(), ())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            } else {
+                withheld_table.remove(to_str.as_ref())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            }
+
+            meta
+        };
+
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(1);
+        debug!("Renamed file: {:?} -> {:?}", from, to);
+        Ok(Some(moved))
+    }
+
+    /// Record the import state of a file
+    pub fn set_import_state(&self, path: &std::path::Path, state: ImportState) -> StreamResult<()> {
+        self.set_import_states(&[path.to_path_buf()], state)
+    }
+
+    /// Record the same import state for many files in one transaction
+    pub fn set_import_states(&self, paths: &[PathBuf], state: ImportState) -> StreamResult<()> {
+        let config = bincode::config::standard();
+        let encoded = bincode::serde::encode_to_vec(state, config)
+            .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let mut state_table = txn.open_table(IMPORT_STATE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            for path in paths {
+                state_table.insert(path.to_string_lossy().as_ref(), encoded.as_slice())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            }
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(paths.len());
+
+        Ok(())
+    }
+
+    /// Move every file left `Importing` back to `Pending`, returning their paths
+    ///
+    /// Meant for startup: nothing can be importing yet, so such states were
+    /// left by a process that stopped mid-import and would otherwise block
+    /// sharing the file for good.
+    pub fn reset_interrupted_imports(&self) -> StreamResult<Vec<PathBuf>> {
+        let config = bincode::config::standard();
+        let pending = bincode::serde::encode_to_vec(ImportState::Pending, config)
+            .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let interrupted = {
+            let mut state_table = txn.open_table(IMPORT_STATE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let mut interrupted = Vec::new();
+            for entry in state_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+                let (key, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+                let (state, _): (ImportState, usize) = bincode::serde::decode_from_slice(value.value(), config)
+                    .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                if state == ImportState::Importing {
+                    interrupted.push(key.value().to_string());
+                }
+            }
+
+            for path in &interrupted {
+                state_table.insert(path.as_str(), pending.as_slice())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            }
+            interrupted
+        };
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(interrupted.len());
+
+        Ok(interrupted.into_iter().map(PathBuf::from).collect())
+    }
+
+    /// Get the import state of a file, if one was recorded
+    pub fn get_import_state(&self, path: &std::path::Path) -> StreamResult<Option<ImportState>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let state_table = txn.open_table(IMPORT_STATE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let path_str = path.to_string_lossy();
+
+        if let Some(access) = state_table.get(path_str.as_ref())
+            .map_err(|e| StreamError::Database(e.to_string()))?
+        {
+            let config = bincode::config::standard();
+            let (state, _): (ImportState, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+
+            Ok(Some(state))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Mark whether a file may be served to peers
+    ///
+    /// Files are servable by default. Withholding a file keeps its index
+    /// entry; the flag is cleared when the file is removed from the index.
+    pub fn set_servable(&self, path: &std::path::Path, servable: bool) -> StreamResult<()> {
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let mut withheld_table = txn.open_table(WITHHELD)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let path_str = path.to_string_lossy();
+            if servable {
+                withheld_table.remove(path_str.as_ref())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            } else {
+                withheld_table.insert(path_str.as_ref(), ())
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+            }
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(1);
+
+        Ok(())
+    }
+
+    /// Whether a file may be served to peers
+    pub fn is_servable(&self, path: &std::path::Path) -> StreamResult<bool> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let withheld_table = txn.open_table(WITHHELD)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let withheld = withheld_table.get(path.to_string_lossy().as_ref())
+            .map_err(|e| StreamError::Database(e.to_string()))?
+            .is_some();
+
+        Ok(!withheld)
+    }
+
+    /// Paths of all files currently withheld from peers
+    pub fn list_withheld(&self) -> StreamResult<Vec<PathBuf>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let withheld_table = txn.open_table(WITHHELD)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let mut paths = Vec::new();
+        for entry in withheld_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (key, _) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            paths.push(PathBuf::from(key.value()));
+        }
+
+        Ok(paths)
+    }
+
+    /// Record a ticket handed out by this node
+    ///
+    /// The id is derived from the sharing node, content hash and ticket
+    /// creation time, so recording the same ticket twice keeps the first
+    /// record, whatever else the ticket carries.
+    pub fn record_share(&self, ticket: &ShareTicket) -> StreamResult<ShareRecord> {
+        let key = format!("{}:{}:{}", ticket.node_id, ticket.hash.0, ticket.created_at);
+        let id = blake3::hash(key.as_bytes()).to_hex()[..SHARE_ID_LEN].to_string();
+
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let record = {
+            let mut shares_table = txn.open_table(SHARES)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            match read_share(&shares_table, &id)? {
+                Some(existing) => existing,
+                None => {
+                    let record = ShareRecord {
+                        id: id.clone(),
+                        ticket: ticket.clone(),
+                        created_at: unix_now(),
+                        revoked_at: None,
+                    };
+                    write_share(&mut shares_table, &record)?;
+                    record
+                }
+            }
+        };
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(1);
+
+        Ok(record)
+    }
+
+    /// Look up a recorded share by id
+    pub fn get_share(&self, id: &str) -> StreamResult<Option<ShareRecord>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let shares_table = txn.open_table(SHARES)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        read_share(&shares_table, id)
+    }
+
+    /// All recorded shares, revoked ones included, oldest first
+    pub fn list_shares(&self) -> StreamResult<Vec<ShareRecord>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let shares_table = txn.open_table(SHARES)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let mut shares = Vec::new();
+        for entry in shares_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (_, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            shares.push(decode_share(value.value())?);
+        }
+        shares.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        Ok(shares)
+    }
+
+    /// Mark a share as revoked, returning the updated record
+    ///
+    /// This only updates the record: content is still served to whoever
+    /// holds the ticket. Records are kept so the share can still be listed.
+    /// Revoking twice keeps the first revocation time; unknown ids return `None`.
+    pub fn revoke_share(&self, id: &str) -> StreamResult<Option<ShareRecord>> {
+        let txn = self.db().begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let record = {
+            let mut shares_table = txn.open_table(SHARES)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            match read_share(&shares_table, id)? {
+                Some(mut record) => {
+                    if record.revoked_at.is_none() {
+                        record.revoked_at = Some(unix_now());
+                        write_share(&mut shares_table, &record)?;
+                    }
+                    Some(record)
+                }
+                None => None,
+            }
+        };
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        self.record_writes(1);
+
+        Ok(record)
+    }
+
+    /// List all indexed files in path order
+    ///
+    /// Collects the whole index; prefer [`FileIndex::list_paged`] or
+    /// [`FileIndex::for_each_file`] for large libraries.
     pub fn list_all(&self) -> StreamResult<Vec<FileMetadata>> {
-        let txn = self.db.begin_read()
+        self.list_paged(0, usize::MAX)
+    }
+
+    /// List up to `limit` indexed files in path order, starting at `offset`
+    ///
+    /// Skipped entries are never deserialized. Use [`FileIndex::count`] to
+    /// work out the number of pages.
+    pub fn list_paged(&self, offset: usize, limit: usize) -> StreamResult<Vec<FileMetadata>> {
+        let txn = self.db().begin_read()
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
         let files_table = txn.open_table(FILES_TABLE)
             .map_err(|e| StreamError::Database(e.to_string()))?;
 
         let mut results = Vec::new();
-        let limit = 10_000;
         let config = bincode::config::standard();
 
-        for (i, entry) in files_table.iter().map_err(|e| StreamError::Database(e.to_string()))?.enumerate() {
-            if i >= limit {
-                break;
-            }
+        let entries = files_table.iter()
+            .map_err(|e| StreamError::Database(e.to_string()))?
+            .skip(offset)
+            .take(limit);
+
+        for entry in entries {
             let (_, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
             let (metadata, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(value.value(), config)
                 .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
@@ -198,11 +863,324 @@ impl FileIndex {
         Ok(results)
     }
 
+    /// Iterate over every indexed file in path order without collecting them
+    ///
+    /// Entries are decoded one at a time. The iterator keeps its own read
+    /// transaction alive, so it sees a consistent snapshot of the index even
+    /// while files are written concurrently.
+    pub fn iter_files(&self) -> StreamResult<impl Iterator<Item = StreamResult<FileMetadata>> + use<>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let files_table = txn.open_table(FILES_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        // The range of a read-only table owns a handle to the transaction
+        let entries = files_table.range::<&str>(..)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let config = bincode::config::standard();
+
+        Ok(entries.map(move |entry| {
+            let (_, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            let (metadata, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(value.value(), config)
+                .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+            Ok(metadata)
+        }))
+    }
+
+    /// Visit every indexed file in path order without collecting them
+    ///
+    /// Unlike [`FileIndex::list_all`] nothing is collected, so it suits large
+    /// libraries. Stops at the first error returned by `f`.
+    pub fn for_each_file<F>(&self, mut f: F) -> StreamResult<()>
+    where
+        F: FnMut(FileMetadata) -> StreamResult<()>,
+    {
+        for metadata in self.iter_files()? {
+            f(metadata?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Find files whose name contains `query`, ignoring case
+    ///
+    /// Only the file name is matched, not the directories above it. An empty
+    /// query matches nothing. Entries are decoded one at a time and the scan
+    /// stops once `limit` matches are found.
+    pub fn search_by_name(&self, query: &str, limit: Option<usize>) -> StreamResult<Vec<FileMetadata>> {
+        let query = query.to_lowercase();
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut results = Vec::new();
+        if query.is_empty() || limit == 0 {
+            return Ok(results);
+        }
+
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let files_table = txn.open_table(FILES_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let config = bincode::config::standard();
+
+        for entry in files_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (key, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+
+            // Match on the key so non-matching entries are never deserialized
+            let matches = std::path::Path::new(key.value())
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().to_lowercase().contains(&query));
+            if !matches {
+                continue;
+            }
+
+            let (metadata, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(value.value(), config)
+                .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+            results.push(metadata);
+            if results.len() >= limit {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// List every file whose MIME type starts with `prefix`, e.g. `"video/"`
+    ///
+    /// MIME types aren't indexed, so this scans the whole files table.
+    pub fn list_by_mime_prefix(&self, prefix: &str) -> StreamResult<Vec<FileMetadata>> {
+        let mut results = Vec::new();
+        self.for_each_file(|metadata| {
+            if metadata.mime_type.starts_with(prefix) {
+                results.push(metadata);
+            }
+            Ok(())
+        })?;
+
+        Ok(results)
+    }
+
+    /// List files with `created_at` in `[start, end)`, oldest first
+    ///
+    /// `created_at` isn't a key, so this decodes the whole files table and
+    /// sorts the matches: O(n) reads plus O(m log m) for m matches. Fine for
+    /// occasional feeds, too slow to call per request on huge libraries.
+    pub fn list_by_time_range(&self, start: u64, end: u64) -> StreamResult<Vec<FileMetadata>> {
+        let mut results = Vec::new();
+        self.for_each_file(|metadata| {
+            if (start..end).contains(&metadata.created_at) {
+                results.push(metadata);
+            }
+            Ok(())
+        })?;
+
+        // Stable sort keeps path order among equal timestamps
+        results.sort_by_key(|metadata| metadata.created_at);
+        Ok(results)
+    }
+
+    /// Groups of paths sharing the same content, largest waste first
+    ///
+    /// Only hashes indexed under more than one path are returned, ordered by
+    /// the bytes taken up by the extra copies (size times copies beyond the
+    /// first). Paths within a group are sorted.
+    pub fn find_duplicates(&self) -> StreamResult<Vec<(MediaHash, Vec<PathBuf>)>> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let files_table = txn.open_table(FILES_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let hash_table = txn.open_table(HASH_INDEX)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let config = bincode::config::standard();
+        let mut groups = Vec::new();
+        for entry in hash_table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (key, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            let (mut paths, _): (Vec<String>, usize) = bincode::serde::decode_from_slice(value.value(), config)
+                .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+            if paths.len() < 2 {
+                continue;
+            }
+            paths.sort();
+
+            let size = match files_table.get(paths[0].as_str()).map_err(|e| StreamError::Database(e.to_string()))? {
+                Some(access) => {
+                    let (meta, _): (FileMetadata, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                        .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                    meta.size
+                }
+                None => 0,
+            };
+            let wasted = size.saturating_mul(paths.len() as u64 - 1);
+            groups.push((wasted, MediaHash(key.value().to_string()), paths));
+        }
+
+        groups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.0.cmp(&b.1.0)));
+        Ok(groups.into_iter()
+            .map(|(_, hash, paths)| (hash, paths.into_iter().map(PathBuf::from).collect()))
+            .collect())
+    }
+
+    /// Number of indexed files
+    pub fn count(&self) -> StreamResult<u64> {
+        let txn = self.db().begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let files_table = txn.open_table(FILES_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        files_table.len().map_err(|e| StreamError::Database(e.to_string()))
+    }
+
+    /// Remove entries chosen by `policy` until at most `max_files` remain
+    ///
+    /// Returns the evicted entries so the caller can release their blobs.
+    pub fn evict(&self, max_files: usize, policy: EvictionPolicy) -> StreamResult<Vec<FileMetadata>> {
+        self.evict_except(max_files, policy, |_| false)
+    }
+
+    /// Like [`FileIndex::evict`], but never evicts entries `keep` returns true for
+    ///
+    /// Kept entries still count towards `max_files`, so fewer than
+    /// `max_files` others may remain.
+    pub fn evict_except(
+        &self,
+        max_files: usize,
+        policy: EvictionPolicy,
+        keep: impl Fn(&FileMetadata) -> bool
+    ) -> StreamResult<Vec<FileMetadata>> {
+        let count = self.count()? as usize;
+        if count <= max_files {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates = Vec::with_capacity(count);
+        self.for_each_file(|meta| {
+            if !keep(&meta) {
+                candidates.push(meta);
+            }
+            Ok(())
+        })?;
+
+        match policy {
+            EvictionPolicy::OldestCreated => {
+                candidates.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.path.cmp(&b.path)));
+            }
+        }
+        candidates.truncate(count - max_files);
+
+        for meta in &candidates {
+            self.remove_file(&meta.path)?;
+        }
+
+        info!("Evicted {} files to stay within {} indexed files", candidates.len(), max_files);
+        Ok(candidates)
+    }
+
+    /// Compact once more than `write_threshold` rows were written since the last compaction
+    ///
+    /// Returns what [`compact`](Self::compact) returned, or false if the
+    /// threshold wasn't reached. The count is kept in memory only and starts
+    /// at zero whenever the index is opened.
+    pub fn maybe_compact(&self, write_threshold: u64) -> StreamResult<bool> {
+        if self.writes_since_compact.load(Ordering::Relaxed) <= write_threshold {
+            return Ok(false);
+        }
+
+        self.compact()
+    }
+
+    fn record_writes(&self, rows: usize) {
+        self.writes_since_compact.fetch_add(rows as u64, Ordering::Relaxed);
+    }
+
+    fn db(&self) -> RwLockReadGuard<'_, Database> {
+        self.db.read().expect("index lock poisoned")
+    }
+
     /// Compact the database to reclaim free space
     /// Returns true if compaction was performed
-    pub fn compact(&mut self) -> StreamResult<bool> {
-        self.db
+    pub fn compact(&self) -> StreamResult<bool> {
+        let mut db = self.db.write().expect("index lock poisoned");
+        let compacted = db
             .compact()
-            .map_err(|e| StreamError::Database(e.to_string()))
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        self.writes_since_compact.store(0, Ordering::Relaxed);
+        Ok(compacted)
     }
-}
\ No newline at end of file
+}
+
+/// Paths recorded for `hash` in the hash index
+fn hash_paths(table: &impl ReadableTable<&'static str, &'static [u8]>, hash: &MediaHash) -> StreamResult<Vec<String>> {
+    match table.get(hash.0.as_str()).map_err(|e| StreamError::Database(e.to_string()))? {
+        Some(access) => {
+            let config = bincode::config::standard();
+            let (paths, _): (Vec<String>, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+            Ok(paths)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Record that `path` has content `hash`
+fn add_hash_path(table: &mut Table<'_, &'static str, &'static [u8]>, hash: &MediaHash, path: &str) -> StreamResult<()> {
+    let mut paths = hash_paths(&*table, hash)?;
+    if paths.iter().any(|p| p == path) {
+        return Ok(());
+    }
+    paths.push(path.to_string());
+    write_hash_paths(table, hash, &paths)
+}
+
+/// Forget that `path` has content `hash`, removing the hash with its last path
+fn remove_hash_path(table: &mut Table<'_, &'static str, &'static [u8]>, hash: &MediaHash, path: &str) -> StreamResult<()> {
+    let mut paths = hash_paths(&*table, hash)?;
+    paths.retain(|p| p != path);
+    if paths.is_empty() {
+        table.remove(hash.0.as_str())
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        Ok(())
+    } else {
+        write_hash_paths(table, hash, &paths)
+    }
+}
+
+fn write_hash_paths(table: &mut Table<'_, &'static str, &'static [u8]>, hash: &MediaHash, paths: &[String]) -> StreamResult<()> {
+    let config = bincode::config::standard();
+    let encoded = bincode::serde::encode_to_vec(paths, config)
+        .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+    table.insert(hash.0.as_str(), encoded.as_slice())
+        .map_err(|e| StreamError::Database(e.to_string()))?;
+    Ok(())
+}
+
+fn read_share(table: &impl ReadableTable<&'static str, &'static [u8]>, id: &str) -> StreamResult<Option<ShareRecord>> {
+    match table.get(id).map_err(|e| StreamError::Database(e.to_string()))? {
+        Some(access) => decode_share(access.value()).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn decode_share(bytes: &[u8]) -> StreamResult<ShareRecord> {
+    serde_json::from_slice(bytes)
+        .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))
+}
+
+fn write_share(table: &mut Table<'_, &'static str, &'static [u8]>, record: &ShareRecord) -> StreamResult<()> {
+    let encoded = serde_json::to_vec(record)
+        .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+    table.insert(record.id.as_str(), encoded.as_slice())
+        .map_err(|e| StreamError::Database(e.to_string()))?;
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
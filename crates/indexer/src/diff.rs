@@ -0,0 +1,66 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use ghostdrive_core::{FileMetadata, Manifest, ManifestEntry, MediaHash, StreamResult};
+
+use crate::FileIndex;
+
+/// Result of comparing the local library against a remote manifest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibraryDiff {
+    /// Entries in the manifest whose content we don't have
+    pub missing: Vec<ManifestEntry>,
+    /// Local files whose content isn't in the manifest
+    pub extra: Vec<FileMetadata>,
+    /// Same content on both sides, but no local file carries the manifest's name
+    pub renamed: Vec<(ManifestEntry, FileMetadata)>,
+}
+
+impl LibraryDiff {
+    /// True when both sides hold exactly the same content under the same names
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.renamed.is_empty()
+    }
+}
+
+impl FileIndex {
+    /// Compare indexed content against `manifest` by hash
+    pub fn diff_against(&self, manifest: &Manifest) -> StreamResult<LibraryDiff> {
+        // Group local files by content hash
+        let mut local: HashMap<MediaHash, Vec<FileMetadata>> = HashMap::new();
+        for meta in self.list_all()? {
+            local.entry(meta.hash.clone()).or_default().push(meta);
+        }
+
+        let mut diff = LibraryDiff::default();
+        let mut remote_hashes: HashSet<&MediaHash> = HashSet::new();
+
+        for entry in &manifest.entries {
+            remote_hashes.insert(&entry.hash);
+
+            match local.get(&entry.hash) {
+                None => diff.missing.push(entry.clone()),
+                Some(files) => {
+                    let remote_name = file_name(Path::new(&entry.name));
+                    if !files.iter().any(|f| file_name(&f.path) == remote_name) {
+                        diff.renamed.push((entry.clone(), files[0].clone()));
+                    }
+                }
+            }
+        }
+
+        for (hash, files) in local {
+            if !remote_hashes.contains(&hash) {
+                diff.extra.extend(files);
+            }
+        }
+        diff.extra.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(diff)
+    }
+}
+
+/// Final path component, used to compare names across machines
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name().map(|s| s.to_string_lossy().to_string())
+}
@@ -0,0 +1,157 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use ghostdrive_core::{FileMetadata, MediaHash, StreamError, StreamResult};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::FileIndex;
+
+/// Number of rows upserted per transaction during a CSV [`FileIndex::import`]
+const IMPORT_BATCH_SIZE: usize = 1_000;
+
+/// Interchange formats for exporting and importing the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// JSON array of `FileMetadata` objects
+    Json,
+    /// CSV with a header row: path, hash, size, mime, created_at, updated_at
+    Csv,
+}
+
+/// Flat CSV row, column order matches the header
+#[derive(Serialize, Deserialize)]
+struct CsvRow {
+    path: String,
+    hash: String,
+    size: u64,
+    mime: String,
+    created_at: u64,
+    /// Missing from exports made before the column existed
+    #[serde(default)]
+    updated_at: u64,
+}
+
+impl From<FileMetadata> for CsvRow {
+    fn from(meta: FileMetadata) -> Self {
+        Self {
+            path: meta.path.to_string_lossy().to_string(),
+            hash: meta.hash.0,
+            size: meta.size,
+            mime: meta.mime_type,
+            created_at: meta.created_at,
+            updated_at: meta.updated_at,
+        }
+    }
+}
+
+impl From<CsvRow> for FileMetadata {
+    fn from(row: CsvRow) -> Self {
+        Self {
+            path: PathBuf::from(row.path),
+            hash: MediaHash(row.hash),
+            size: row.size,
+            mime_type: row.mime,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            // Not exported, so imported files are hashed again on the next scan
+            modified_ns: 0,
+        }
+    }
+}
+
+impl FileIndex {
+    /// Write every indexed file to `writer`, one row at a time
+    ///
+    /// Rows are streamed straight from the database, so memory use stays
+    /// flat regardless of library size. Returns the number of rows written.
+    pub fn export(&self, format: ExportFormat, writer: impl Write) -> StreamResult<usize> {
+        let count = match format {
+            ExportFormat::Json => write_json(self, writer)?,
+            ExportFormat::Csv => write_csv(self, writer)?,
+        };
+
+        info!("Exported {} files as {:?}", count, format);
+        Ok(count)
+    }
+
+    /// Bulk-upsert files from data produced by [`FileIndex::export`]
+    ///
+    /// Existing entries with the same path are overwritten. JSON is restored
+    /// in a single transaction, so a failed restore leaves the index
+    /// untouched; CSV is streamed in batches. Returns the number of rows imported.
+    pub fn import(&self, format: ExportFormat, reader: impl Read) -> StreamResult<usize> {
+        let count = match format {
+            ExportFormat::Json => {
+                let files: Vec<FileMetadata> = serde_json::from_reader(reader)
+                    .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+
+                self.upsert_files(&files)?;
+                files.len()
+            }
+            ExportFormat::Csv => {
+                let mut csv_reader = csv::Reader::from_reader(reader);
+                let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+                let mut count = 0;
+
+                for row in csv_reader.deserialize::<CsvRow>() {
+                    let row = row.map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                    batch.push(FileMetadata::from(row));
+
+                    if batch.len() == IMPORT_BATCH_SIZE {
+                        self.upsert_files(&batch)?;
+                        count += batch.len();
+                        batch.clear();
+                    }
+                }
+
+                self.upsert_files(&batch)?;
+                count + batch.len()
+            }
+        };
+
+        info!("Imported {} files from {:?}", count, format);
+        Ok(count)
+    }
+}
+
+fn write_json(index: &FileIndex, mut writer: impl Write) -> StreamResult<usize> {
+    let mut count = 0;
+
+    writer.write_all(b"[").map_err(StreamError::from)?;
+    index.for_each_file(|meta| {
+        if count > 0 {
+            writer.write_all(b",").map_err(StreamError::from)?;
+        }
+        writer.write_all(b"\n  ").map_err(StreamError::from)?;
+        serde_json::to_writer(&mut writer, &meta)
+            .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+        count += 1;
+        Ok(())
+    })?;
+    writer.write_all(b"\n]\n").map_err(StreamError::from)?;
+    writer.flush().map_err(StreamError::from)?;
+
+    Ok(count)
+}
+
+fn write_csv(index: &FileIndex, writer: impl Write) -> StreamResult<usize> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    let mut count = 0;
+
+    index.for_each_file(|meta| {
+        csv_writer.serialize(CsvRow::from(meta))
+            .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+        count += 1;
+        Ok(())
+    })?;
+
+    // An empty index still gets a header row
+    if count == 0 {
+        csv_writer.write_record(["path", "hash", "size", "mime", "created_at", "updated_at"])
+            .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+    }
+    csv_writer.flush().map_err(StreamError::from)?;
+
+    Ok(count)
+}
@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use ghostdrive_core::{MediaHash, StreamError, StreamResult};
+use tracing::debug;
+
+/// Default read buffer for hashing
+///
+/// 64KB was the original size; on NVMe drives a 1MB buffer roughly halves the
+/// number of read syscalls and measurably improves throughput on large media,
+/// while small files are unaffected since the buffer is only as full as the file.
+pub const DEFAULT_HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Files at least this large are hashed memory-mapped across all cores
+///
+/// Below it, spinning up the thread pool and mapping the file costs more than
+/// a plain buffered read.
+pub const MMAP_HASH_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// Compute the BLAKE3 hash of a file
+///
+/// Large regular files are memory-mapped and hashed in parallel; smaller
+/// ones, and any file that can't be mapped, are read through a buffer of
+/// `buffer_size` bytes.
+pub fn hash_file(path: &Path, buffer_size: usize) -> StreamResult<MediaHash> {
+    let metadata = fs::metadata(path).map_err(StreamError::from)?;
+    if metadata.is_file() && metadata.len() >= MMAP_HASH_THRESHOLD {
+        let mut hasher = blake3::Hasher::new();
+        match hasher.update_mmap_rayon(path) {
+            Ok(_) => return Ok(MediaHash(hasher.finalize().to_hex().to_string())),
+            Err(e) => debug!("Memory-mapped hashing failed for {:?}, reading instead: {}", path, e),
+        }
+    }
+
+    hash_file_streaming(path, buffer_size)
+}
+
+/// Compute the BLAKE3 hash of a file, reading it through a buffer of `buffer_size` bytes
+pub fn hash_file_streaming(path: &Path, buffer_size: usize) -> StreamResult<MediaHash> {
+    let file = fs::File::open(path).map_err(StreamError::from)?;
+    let mut reader = std::io::BufReader::with_capacity(buffer_size.max(1), file);
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut reader, &mut hasher).map_err(StreamError::from)?;
+    let hash_bytes = hasher.finalize();
+
+    Ok(MediaHash(hash_bytes.to_hex().to_string()))
+}
+
+/// Modification time of a file in nanoseconds since the Unix epoch, 0 if the
+/// platform doesn't report one
+pub fn modified_ns(metadata: &fs::Metadata) -> u64 {
+    metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
@@ -1,5 +1,17 @@
 pub mod db;
+pub mod diff;
+pub mod export;
+pub mod hasher;
+pub mod mounts;
+pub mod stats;
+pub mod store;
 pub mod watcher;
 
-pub use db::FileIndex;
-pub use watcher::FileWatcher;
\ No newline at end of file
+pub use db::{EvictionPolicy, FileIndex};
+pub use diff::LibraryDiff;
+pub use export::ExportFormat;
+pub use hasher::{hash_file, hash_file_streaming, modified_ns, DEFAULT_HASH_BUFFER_SIZE, MMAP_HASH_THRESHOLD};
+pub use mounts::is_network_mount;
+pub use stats::IndexStats;
+pub use store::FileStore;
+pub use watcher::{FileWatcher, IndexEvent, SymlinkPolicy, WatchHandle, WatchStrategy, WatcherConfig, DEFAULT_DEBOUNCE, DEFAULT_POLL_INTERVAL, DEFAULT_STABILITY_INTERVAL};
\ No newline at end of file
@@ -0,0 +1,61 @@
+use std::path::{Path, PathBuf};
+
+/// Filesystem types whose change notifications can't be trusted
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb", "smbfs", "smb3", "afs", "9p", "ceph",
+    "glusterfs", "davfs", "fuse.sshfs", "fuse.rclone", "fuse.glusterfs",
+];
+
+/// Best-effort check whether `path` lives on a network filesystem (NFS, SMB, ...)
+///
+/// Native watchers don't see changes made by other machines on such mounts.
+/// Detection reads `/proc/mounts`, so it only works on Linux; elsewhere this
+/// returns false and polling has to be requested explicitly.
+pub fn is_network_mount(path: &Path) -> bool {
+    mount_fs_type(path)
+        .map(|fs_type| NETWORK_FS_TYPES.contains(&fs_type.as_str()))
+        .unwrap_or(false)
+}
+
+/// Filesystem type of the mount containing `path`, if it can be determined
+fn mount_fs_type(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+
+    // The longest mount point that prefixes the path is the one it lives on
+    mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_path(fields.next()?);
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point).then(|| (mount_point, fs_type.to_string()))
+        })
+        .max_by_key(|(mount_point, _)| mount_point.as_os_str().len())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// `/proc/mounts` escapes spaces, tabs, newlines and backslashes as octal (`\040`)
+fn unescape_mount_path(raw: &str) -> PathBuf {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        let code = rest.get(pos + 1..pos + 4)
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[pos + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[pos + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    PathBuf::from(out)
+}
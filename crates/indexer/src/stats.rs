@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use ghostdrive_core::StreamResult;
+use serde::Serialize;
+
+use crate::FileIndex;
+
+/// Totals over the whole index
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IndexStats {
+    /// Number of indexed files
+    pub files: u64,
+    /// Sum of all file sizes in bytes
+    pub bytes: u64,
+    /// Number of files per MIME type
+    pub by_mime: HashMap<String, u64>,
+}
+
+impl FileIndex {
+    /// Compute aggregate statistics in a single pass over one read transaction
+    pub fn stats(&self) -> StreamResult<IndexStats> {
+        let mut stats = IndexStats::default();
+
+        for metadata in self.iter_files()? {
+            let metadata = metadata?;
+            stats.files += 1;
+            stats.bytes += metadata.size;
+            *stats.by_mime.entry(metadata.mime_type).or_default() += 1;
+        }
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash, StreamResult};
+
+use crate::FileIndex;
+
+/// The index operations needed to register files
+///
+/// [`FileIndex`] is the real implementation; the trait lets callers wrap or
+/// replace it, e.g. to inject failures in tests.
+pub trait FileStore: Send + Sync {
+    fn upsert_file(&self, metadata: &FileMetadata) -> StreamResult<()>;
+    fn get_by_path(&self, path: &Path) -> StreamResult<Option<FileMetadata>>;
+    fn get_by_hash(&self, hash: &MediaHash) -> StreamResult<Option<FileMetadata>>;
+    fn remove_file(&self, path: &Path) -> StreamResult<()>;
+    fn set_import_state(&self, path: &Path, state: ImportState) -> StreamResult<()>;
+    fn get_import_state(&self, path: &Path) -> StreamResult<Option<ImportState>>;
+}
+
+impl FileStore for FileIndex {
+    fn upsert_file(&self, metadata: &FileMetadata) -> StreamResult<()> {
+        FileIndex::upsert_file(self, metadata)
+    }
+
+    fn get_by_path(&self, path: &Path) -> StreamResult<Option<FileMetadata>> {
+        FileIndex::get_by_path(self, path)
+    }
+
+    fn get_by_hash(&self, hash: &MediaHash) -> StreamResult<Option<FileMetadata>> {
+        FileIndex::get_by_hash(self, hash)
+    }
+
+    fn remove_file(&self, path: &Path) -> StreamResult<()> {
+        FileIndex::remove_file(self, path)
+    }
+
+    fn set_import_state(&self, path: &Path, state: ImportState) -> StreamResult<()> {
+        FileIndex::set_import_state(self, path, state)
+    }
+
+    fn get_import_state(&self, path: &Path) -> StreamResult<Option<ImportState>> {
+        FileIndex::get_import_state(self, path)
+    }
+}
@@ -4,13 +4,17 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use mime_guess::from_path;
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use ghostdrive_core::{FileMetadata, MediaHash, StreamError, StreamResult};
-use tokio::sync::mpsc;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use ghostdrive_core::{FileMetadata, ImportState, StreamError, StreamResult};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio::time::{interval, Instant};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, instrument, warn};
 
+use crate::hasher::{hash_file, modified_ns, DEFAULT_HASH_BUFFER_SIZE};
+use crate::mounts::is_network_mount;
 use crate::FileIndex;
 
 /// Events user internally by the watcher loop
@@ -18,61 +22,388 @@ use crate::FileIndex;
 enum WatcherEvent {
     FileSystem(Event),
     ScanTick,
+    /// A file was still growing when checked, with the size seen then
+    Unsettled(PathBuf, u64),
+    /// An existing file found when a watch path was added
+    Discovered(PathBuf),
+    /// Watch path changes requested through a [`WatchHandle`]
+    Control(WatchCommand, oneshot::Sender<StreamResult<()>>),
+}
+
+#[derive(Debug)]
+enum WatchCommand {
+    Add(PathBuf),
+    Remove { path: PathBuf, purge: bool },
+}
+
+/// Change to the index made by the watcher
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexEvent {
+    /// A path was indexed for the first time
+    Added(FileMetadata),
+    /// An indexed path was re-indexed after changing
+    Updated(FileMetadata),
+    /// A link was indexed without following it, or now points elsewhere
+    Linked { path: PathBuf, target: PathBuf },
+    /// A path was removed from the index
+    Removed(PathBuf),
+}
+
+/// Events buffered per subscriber before the oldest are dropped
+const INDEX_EVENT_CAPACITY: usize = 256;
+
+/// Adds and removes watch paths of a running [`FileWatcher`]
+#[derive(Clone)]
+pub struct WatchHandle {
+    event_tx: mpsc::UnboundedSender<WatcherEvent>,
+    index_events: broadcast::Sender<IndexEvent>,
+}
+
+impl WatchHandle {
+    /// See [`FileWatcher::subscribe`]
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexEvent> {
+        self.index_events.subscribe()
+    }
+
+    /// See [`FileWatcher::add_watch`]
+    pub async fn add_watch(&self, path: PathBuf) -> StreamResult<()> {
+        self.send(WatchCommand::Add(path)).await
+    }
+
+    /// See [`FileWatcher::remove_watch`]
+    pub async fn remove_watch(&self, path: PathBuf, purge: bool) -> StreamResult<()> {
+        self.send(WatchCommand::Remove { path, purge }).await
+    }
+
+    async fn send(&self, command: WatchCommand) -> StreamResult<()> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.event_tx.send(WatcherEvent::Control(command, reply_tx))
+            .map_err(|_| StreamError::Cancelled("File watcher stopped".to_string()))?;
+        reply_rx.await
+            .map_err(|_| StreamError::Cancelled("File watcher stopped".to_string()))?
+    }
+}
+
+/// Outcome of [`process_file_blocking`]
+enum Processed {
+    /// A regular file was indexed, `existed` if it replaced an entry
+    Indexed { meta: FileMetadata, existed: bool },
+    /// A link was indexed without following it, `changed` if it is new or retargeted
+    Linked { path: PathBuf, target: PathBuf, changed: bool },
+    /// Nothing to report (gone, ignored or filtered)
+    Skipped,
+    /// The file changed size while being checked, with the latest size
+    Unsettled(u64),
+}
+
+/// How symbolic links under a watch path are treated
+///
+/// Links are skipped by default, so nothing outside the watched trees is
+/// indexed unless following them is asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Follow links and index their targets (directory loops are skipped)
+    Follow,
+    /// Skip links entirely
+    #[default]
+    Ignore,
+    /// Index the link itself without following it
+    IndexAsLink,
+}
+
+/// How watch roots receive change notifications
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatchStrategy {
+    /// Poll roots detected as network mounts, use native events elsewhere
+    #[default]
+    Auto,
+    /// Always use the platform's native events (unreliable on NFS/SMB)
+    Native,
+    /// Always poll, e.g. for network mounts that aren't detected
+    Poll,
+}
+
+/// Default interval between polls of [`WatchStrategy::Poll`] roots
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default quiet period after the last change before a file is indexed
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Default time a file's size must stay unchanged before it is hashed
+pub const DEFAULT_STABILITY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest stability wait done in place on the blocking pool; longer waits
+/// put the file back in the queue instead of holding the thread
+const MAX_INLINE_STABILITY_WAIT: Duration = Duration::from_millis(250);
+
+/// Tunables for [`FileWatcher`]
+#[derive(Debug, Clone)]
+pub struct WatcherConfig {
+    /// Read buffer size (bytes) used when hashing file contents
+    pub hash_buffer_size: usize,
+    /// Treatment of symbolic links
+    pub symlink_policy: SymlinkPolicy,
+    /// Native events, polling, or a per-root choice
+    pub watch_strategy: WatchStrategy,
+    /// How often polled roots are rescanned; shorter notices changes sooner
+    /// at the cost of walking the whole tree more often
+    pub poll_interval: Duration,
+    /// How long a file must go without changes before it is indexed
+    pub debounce: Duration,
+    /// Glob patterns for paths to skip, matched against both the file name
+    /// and the full path (defaults to dotfiles)
+    pub ignore_globs: Vec<String>,
+    /// Only index files whose MIME type starts with one of these prefixes
+    /// (e.g. `video/`), `None` indexes everything
+    pub include_mime_prefixes: Option<Vec<String>>,
+    /// How long a file's size must stay the same before it is hashed, so
+    /// files still being copied in aren't indexed half-written
+    pub stability_interval: Duration,
+}
+
+impl WatcherConfig {
+    /// Whether a file of type `mime` passes the include filter
+    pub fn includes_mime(&self, mime: &str) -> bool {
+        match &self.include_mime_prefixes {
+            Some(prefixes) => prefixes.iter().any(|prefix| mime.starts_with(prefix.as_str())),
+            None => true,
+        }
+    }
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            hash_buffer_size: DEFAULT_HASH_BUFFER_SIZE,
+            symlink_policy: SymlinkPolicy::default(),
+            watch_strategy: WatchStrategy::default(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            debounce: DEFAULT_DEBOUNCE,
+            ignore_globs: vec![".*".to_string()],
+            include_mime_prefixes: None,
+            stability_interval: DEFAULT_STABILITY_INTERVAL,
+        }
+    }
 }
 
 pub struct FileWatcher {
     index: Arc<FileIndex>,
-    // Keep watcher alive by holding it, even if we don't access it directly after init
-    _watcher: RecommendedWatcher,
+    config: WatcherConfig,
+    ignore: GlobSet,
+    /// Created on first use, then shared by every root using it
+    native: Option<RecommendedWatcher>,
+    poll: Option<PollWatcher>,
+    /// Watched roots, and whether each one is polled
+    roots: HashMap<PathBuf, bool>,
     event_rx: mpsc::UnboundedReceiver<WatcherEvent>,
+    /// Lets processing tasks and handles feed the event loop
+    event_tx: mpsc::UnboundedSender<WatcherEvent>,
+    /// Dropping this ends the ticker task
+    _ticker_stop: oneshot::Sender<()>,
+    indexed_tx: Option<mpsc::UnboundedSender<FileMetadata>>,
+    index_events: broadcast::Sender<IndexEvent>,
 }
 
 impl FileWatcher {
     pub fn new(index: Arc<FileIndex>, watch_paths: Vec<PathBuf>) -> StreamResult<Self> {
+        Self::with_config(index, watch_paths, WatcherConfig::default())
+    }
+
+    /// Create a watcher with custom tunables
+    pub fn with_config(
+        index: Arc<FileIndex>,
+        watch_paths: Vec<PathBuf>,
+        config: WatcherConfig
+    ) -> StreamResult<Self> {
+        let ignore = build_ignore_set(&config.ignore_globs)?;
         let (tx, rx) = mpsc::unbounded_channel();
 
-        // Proxy notify events to tokio channel
-        let tx_clone = tx.clone();
-        let watcher_handler = move |res: Result<Event, notify::Error>| {
-            match res {
-                Ok(event) => {
-                    let _ = tx_clone.send(WatcherEvent::FileSystem(event));
-                }
-                Err(e) => {
-                    error!("File watcher error: {}", e);
+        // Set up a ticker for debouncing check, stopped when the watcher is dropped
+        let tx_tick = tx.clone();
+        let (ticker_stop, mut stopped) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let mut ticker = interval(Duration::from_millis(200));
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = ticker.tick() => {
+                        if tx_tick.send(WatcherEvent::ScanTick).is_err() {
+                            break;
+                        }
+                    }
                 }
             }
-        };
+        });
 
-        let mut watcher = RecommendedWatcher::new(watcher_handler, Config::default())
-            .map_err(|e| StreamError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+        let mut watcher = Self {
+            index,
+            config,
+            ignore,
+            native: None,
+            poll: None,
+            roots: HashMap::new(),
+            event_rx: rx,
+            event_tx: tx,
+            _ticker_stop: ticker_stop,
+            indexed_tx: None,
+            index_events: broadcast::channel(INDEX_EVENT_CAPACITY).0,
+        };
 
         for path in &watch_paths {
-            if !path.exists() {
-                fs::create_dir_all(path).map_err(StreamError::Io)?;
+            watcher.attach(path)?;
+        }
+
+        Ok(watcher)
+    }
+
+    /// Start watching another directory tree
+    ///
+    /// Files already in it are queued for indexing, as if they had just been
+    /// created. The tree is walked on a blocking task, so this returns once
+    /// the path is watched without waiting for the walk. Watching a path
+    /// twice is a no-op.
+    pub fn add_watch(&mut self, path: PathBuf) -> StreamResult<()> {
+        if self.roots.contains_key(&path) {
+            return Ok(());
+        }
+        self.attach(&path)?;
+
+        let event_tx = self.event_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            collect_files(&path, &mut files);
+            let queued = files.len();
+            for file in files {
+                let _ = event_tx.send(WatcherEvent::Discovered(file));
             }
-            watcher.watch(path, RecursiveMode::Recursive)
-                .map_err(|e| StreamError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-            info!("Watching path: {:?}", path);
+            info!("Queued {} existing files under {:?}", queued, path);
+        });
+
+        Ok(())
+    }
+
+    /// Stop watching a directory tree added with [`FileWatcher::new`] or
+    /// [`FileWatcher::add_watch`]
+    ///
+    /// With `purge`, index entries under `path` are removed as well;
+    /// otherwise they stay but are no longer kept up to date.
+    pub fn remove_watch(&mut self, path: &Path, purge: bool) -> StreamResult<()> {
+        let Some(polled) = self.roots.remove(path) else {
+            return Err(StreamError::FileNotFound(path.to_path_buf()));
+        };
+
+        let result = if polled {
+            self.poll.as_mut().map(|w| w.unwatch(path))
+        } else {
+            self.native.as_mut().map(|w| w.unwatch(path))
+        };
+        if let Some(Err(e)) = result {
+            // Already gone from disk, for example
+            warn!("Failed to unwatch {:?}: {}", path, e);
         }
+        info!("Stopped watching path: {:?}", path);
 
-        // Set up a ticker for debouncing check
-        let tx_tick = tx;
-        tokio::spawn(async move {
-            let mut ticker = interval(Duration::from_millis(200));
-            loop {
-                ticker.tick().await;
-                if tx_tick.send(WatcherEvent::ScanTick).is_err() {
-                    break;
+        if purge {
+            let mut stale = Vec::new();
+            self.index.for_each_file(|meta| {
+                if meta.path.starts_with(path) {
+                    stale.push(meta.path);
                 }
+                Ok(())
+            })?;
+            stale.extend(self.index.list_links()?
+                .into_iter()
+                .map(|(link, _)| link)
+                .filter(|link| link.starts_with(path)));
+            for file in &stale {
+                self.index.remove_file(file)?;
+                let _ = self.index_events.send(IndexEvent::Removed(file.clone()));
             }
-        });
+            info!("Purged {} files under {:?} from the index", stale.len(), path);
+        }
 
-        Ok(Self {
-            index,
-            _watcher: watcher,
-            event_rx: rx,
-        })
+        Ok(())
+    }
+
+    /// A handle for adding and removing watch paths while [`FileWatcher::run`]
+    /// owns the watcher
+    pub fn handle(&self) -> WatchHandle {
+        WatchHandle {
+            event_tx: self.event_tx.clone(),
+            index_events: self.index_events.clone(),
+        }
+    }
+
+    /// Register `path` with the native or poll watcher, per the strategy
+    ///
+    /// A missing directory is created; a path that is a file is refused.
+    fn attach(&mut self, path: &Path) -> StreamResult<()> {
+        if !path.exists() {
+            fs::create_dir_all(path).map_err(StreamError::from)?;
+        } else if !path.is_dir() {
+            return Err(StreamError::from(std::io::Error::new(
+                std::io::ErrorKind::NotADirectory,
+                format!("Watch path {:?} is not a directory", path)
+            )));
+        }
+
+        let notify_config = Config::default()
+            .with_follow_symlinks(self.config.symlink_policy == SymlinkPolicy::Follow);
+
+        let network = is_network_mount(path);
+        let use_poll = match self.config.watch_strategy {
+            WatchStrategy::Poll => true,
+            WatchStrategy::Auto => network,
+            WatchStrategy::Native => {
+                if network {
+                    warn!("{:?} looks like a network mount; native events may miss changes, consider WatchStrategy::Poll", path);
+                }
+                false
+            }
+        };
+
+        if use_poll {
+            if self.poll.is_none() {
+                let poll_config = notify_config.with_poll_interval(self.config.poll_interval);
+                self.poll = Some(PollWatcher::new(forward_events(self.event_tx.clone()), poll_config)
+                    .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::Other, e)))?);
+            }
+            let watcher = self.poll.as_mut().expect("poll watcher initialized above");
+            watcher.watch(path, RecursiveMode::Recursive)
+                .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+            info!("Polling path every {:?}: {:?}", self.config.poll_interval, path);
+        } else {
+            if self.native.is_none() {
+                self.native = Some(RecommendedWatcher::new(forward_events(self.event_tx.clone()), notify_config)
+                    .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::Other, e)))?);
+            }
+            let watcher = self.native.as_mut().expect("native watcher initialized above");
+            watcher.watch(path, RecursiveMode::Recursive)
+                .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+            info!("Watching path: {:?}", path);
+        }
+
+        self.roots.insert(path.to_path_buf(), use_poll);
+        Ok(())
+    }
+
+    /// Receive the metadata of every regular file this watcher indexes
+    ///
+    /// Links and removals are not reported. Calling this again replaces the
+    /// previous receiver.
+    pub fn subscribe_indexed(&mut self) -> mpsc::UnboundedReceiver<FileMetadata> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.indexed_tx = Some(tx);
+        rx
+    }
+
+    /// Receive every change this watcher makes to the index, links included
+    ///
+    /// Any number of receivers can subscribe. Indexing never waits for them:
+    /// a receiver that falls more than a few hundred events behind misses the
+    /// oldest ones and sees [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexEvent> {
+        self.index_events.subscribe()
     }
 
     /// Main loop processing events with debouncing
@@ -81,7 +412,9 @@ impl FileWatcher {
 
         // Map path -> Instant (when to process)
         let mut pending_updates: HashMap<PathBuf, Instant> = HashMap::new();
-        let debounce_duration = Duration::from_millis(500);
+        // Map path -> size last seen for files that were still growing
+        let mut settling: HashMap<PathBuf, u64> = HashMap::new();
+        let debounce_duration = self.config.debounce;
 
         while let Some(event) = self.event_rx.recv().await {
             match event {
@@ -89,7 +422,32 @@ impl FileWatcher {
                     self.handle_fs_event(fs_event, &mut pending_updates, debounce_duration);
                 }
                 WatcherEvent::ScanTick => {
-                    self.process_pending(&mut pending_updates).await;
+                    self.process_pending(&mut pending_updates, &mut settling).await;
+                }
+                WatcherEvent::Discovered(path) => {
+                    // The walk runs in the background, its root may be gone by now
+                    let watched = self.roots.keys().any(|root| path.starts_with(root));
+                    if watched && !self.should_ignore(&path) {
+                        pending_updates.insert(path, Instant::now() + debounce_duration);
+                    }
+                }
+                WatcherEvent::Control(command, reply) => {
+                    let result = match command {
+                        WatchCommand::Add(path) => self.add_watch(path),
+                        WatchCommand::Remove { path, purge } => {
+                            pending_updates.retain(|pending, _| !pending.starts_with(&path));
+                            self.remove_watch(&path, purge)
+                        }
+                    };
+                    let _ = reply.send(result);
+                }
+                WatcherEvent::Unsettled(path, size) => {
+                    debug!("Still being written, retrying later: {:?} ({} bytes)", path, size);
+                    // A newer change event already rescheduled it
+                    if !pending_updates.contains_key(&path) {
+                        pending_updates.insert(path.clone(), Instant::now() + self.config.stability_interval);
+                    }
+                    settling.insert(path, size);
                 }
             }
         }
@@ -103,12 +461,23 @@ impl FileWatcher {
         pending: &mut HashMap<PathBuf, Instant>,
         debounce: Duration
     ) {
+        // Backends that pair renames report both paths in one event
+        if let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind {
+            if let [from, to] = event.paths.as_slice() {
+                self.handle_rename(from, to, pending, debounce);
+                return;
+            }
+        }
+
         for path in event.paths {
             if self.should_ignore(&path) {
                 continue;
             }
 
             match event.kind {
+                // The old name of a rename no longer exists; the paired
+                // `Both` event (if any) carries the entry over
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {}
                 EventKind::Create(_) | EventKind::Modify(_) => {
                     // Schedule for update
                     pending.insert(path, Instant::now() + debounce);
@@ -116,18 +485,85 @@ impl FileWatcher {
                 EventKind::Remove(_) => {
                     // Remove immediately
                     pending.remove(&path);
-                    if let Err(e) = self.index.remove_file(&path) {
-                        error!("Failed to remove file from index: {}", e);
-                    } else {
-                        info!("File removed: {:?}", path);
-                    }
+                    self.remove_indexed(path);
                 }
                 _ => {}
             }
         }
     }
 
-    async fn process_pending(&self, pending: &mut HashMap<PathBuf, Instant>) {
+    /// Move the index entry of a renamed file instead of re-hashing it
+    ///
+    /// Falls back to indexing `to` from scratch when `from` isn't indexed
+    /// (or was still settling), and drops the entry when `to` is ignored.
+    fn handle_rename(
+        &self,
+        from: &Path,
+        to: &Path,
+        pending: &mut HashMap<PathBuf, Instant>,
+        debounce: Duration
+    ) {
+        let was_pending = pending.remove(from).is_some();
+
+        if self.should_ignore(to) {
+            pending.remove(to);
+            self.remove_indexed(from.to_path_buf());
+            return;
+        }
+
+        let moved = if self.should_ignore(from) {
+            None
+        } else {
+            match self.index.rename_file(from, to) {
+                Ok(moved) => moved,
+                Err(e) => {
+                    error!("Failed to rename file in index: {}", e);
+                    None
+                }
+            }
+        };
+
+        match moved {
+            Some(meta) if !was_pending => {
+                // Same content, nothing to re-hash
+                pending.remove(to);
+                info!("File renamed: {:?} -> {:?}", from, to);
+                let _ = self.index_events.send(IndexEvent::Removed(from.to_path_buf()));
+                let _ = self.index_events.send(IndexEvent::Added(meta));
+            }
+            Some(_) => {
+                let _ = self.index_events.send(IndexEvent::Removed(from.to_path_buf()));
+                pending.insert(to.to_path_buf(), Instant::now() + debounce);
+            }
+            None => {
+                // A renamed link is indexed again under its new path
+                if matches!(self.index.get_link(from), Ok(Some(_))) {
+                    self.remove_indexed(from.to_path_buf());
+                }
+                pending.insert(to.to_path_buf(), Instant::now() + debounce);
+            }
+        }
+    }
+
+    /// Drop `path` from the index, announcing it if it was indexed
+    fn remove_indexed(&self, path: PathBuf) {
+        let indexed = matches!(self.index.get_by_path(&path), Ok(Some(_)))
+            || matches!(self.index.get_link(&path), Ok(Some(_)));
+        if let Err(e) = self.index.remove_file(&path) {
+            error!("Failed to remove file from index: {}", e);
+        } else {
+            info!("File removed: {:?}", path);
+            if indexed {
+                let _ = self.index_events.send(IndexEvent::Removed(path));
+            }
+        }
+    }
+
+    async fn process_pending(
+        &self,
+        pending: &mut HashMap<PathBuf, Instant>,
+        settling: &mut HashMap<PathBuf, u64>
+    ) {
         let now = Instant::now();
         let mut to_process = Vec::new();
 
@@ -146,53 +582,192 @@ impl FileWatcher {
         // Process ready files
         for path in to_process {
             let index = self.index.clone();
+            let config = self.config.clone();
+            let indexed_tx = self.indexed_tx.clone();
+            let event_tx = self.event_tx.clone();
+            let index_events = self.index_events.clone();
+            let last_size = settling.remove(&path);
 
             // Spawn blocking task for heavy IO/Hashing
             tokio::task::spawn_blocking(move || {
-                if let Err(e) = process_file_blocking(&index, path) {
-                    warn!("Failed to process file: {}", e);
+                match process_file_blocking(&index, path.clone(), &config, last_size) {
+                    Ok(Processed::Indexed { meta, existed }) => {
+                        let _ = index_events.send(index_event(meta.clone(), existed));
+                        if let Some(tx) = indexed_tx {
+                            let _ = tx.send(meta);
+                        }
+                    }
+                    Ok(Processed::Linked { path, target, changed }) => {
+                        if changed {
+                            let _ = index_events.send(IndexEvent::Linked { path, target });
+                        }
+                    }
+                    Ok(Processed::Skipped) => {}
+                    Ok(Processed::Unsettled(size)) => {
+                        let _ = event_tx.send(WatcherEvent::Unsettled(path, size));
+                    }
+                    Err(e) => warn!("Failed to process file: {}", e),
                 }
             });
         }
     }
 
     fn should_ignore(&self, path: &Path) -> bool {
-        // Ignore hidden files (Unix style)
-        if let Some(name) = path.file_name() {
-            if let Some(s) = name.to_str() {
-                if s.starts_with('.') {
-                    return true;
-                }
+        if self.ignore.is_match(path) {
+            return true;
+        }
+        path.file_name().is_some_and(|name| self.ignore.is_match(name))
+    }
+}
+
+/// Every file under `dir`, without following directory links
+///
+/// Unreadable directories are skipped; the watcher still picks up later
+/// changes in them if it can.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to scan {:?}: {}", dir, e);
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => collect_files(&path, files),
+            Ok(_) => files.push(path),
+            Err(e) => warn!("Failed to stat {:?}: {}", path, e),
+        }
+    }
+}
+
+/// Compile the configured ignore patterns
+fn build_ignore_set(patterns: &[String]) -> StreamResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)))?;
+        builder.add(glob);
+    }
+    builder.build()
+        .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)))
+}
+
+/// Proxy notify events to the tokio channel
+fn forward_events(
+    tx: mpsc::UnboundedSender<WatcherEvent>
+) -> impl Fn(Result<Event, notify::Error>) + Send + 'static {
+    move |res| {
+        match res {
+            Ok(event) => {
+                let _ = tx.send(WatcherEvent::FileSystem(event));
+            }
+            Err(e) => {
+                error!("File watcher error: {}", e);
             }
         }
-        false
     }
 }
 
 /// Helper function to hash and metadata a file (Blocking IO)
-fn process_file_blocking(index: &FileIndex, path: PathBuf) -> StreamResult<()> {
+///
+/// `last_size` is the size seen on a previous attempt that found the file
+/// still growing; matching it counts as settled without waiting again.
+#[instrument(skip(index), level = "debug")]
+fn process_file_blocking(
+    index: &FileIndex,
+    path: PathBuf,
+    config: &WatcherConfig,
+    last_size: Option<u64>
+) -> StreamResult<Processed> {
+    let is_link = fs::symlink_metadata(&path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if is_link {
+        match config.symlink_policy {
+            SymlinkPolicy::Follow => {}
+            SymlinkPolicy::Ignore => return Ok(Processed::Skipped),
+            SymlinkPolicy::IndexAsLink => {
+                let target = fs::read_link(&path).map_err(StreamError::from)?;
+                let previous = index.upsert_link(&path, &target)?;
+                info!("Indexed link: {:?} -> {:?}", path, target);
+                let changed = previous.as_ref() != Some(&target);
+                return Ok(Processed::Linked { path, target, changed });
+            }
+        }
+    }
+
     // Re-check existence as it might have been deleted during debounce
     if !path.exists() || !path.is_file() {
-        return Ok(());
+        return Ok(Processed::Skipped);
     }
 
-    let metadata = fs::metadata(&path).map_err(StreamError::Io)?;
+    let metadata = fs::metadata(&path).map_err(StreamError::from)?;
     let size = metadata.len();
 
-    // Hash content
-    let file = fs::File::open(&path).map_err(StreamError::Io)?;
-    let mut reader = std::io::BufReader::with_capacity(64 * 1024, file);
-    let mut hasher = blake3::Hasher::new();
-    std::io::copy(&mut reader, &mut hasher).map_err(StreamError::Io)?;
-    let hash_bytes = hasher.finalize();
-    let hash = MediaHash(hash_bytes.to_hex().to_string());
+    let started = std::time::Instant::now();
 
-    // Detect Mime
+    // Detect Mime, before hashing so filtered files are skipped cheaply
     let mime_type = from_path(&path).first_or_octet_stream().to_string();
+    let mime_elapsed = started.elapsed();
+    if !config.includes_mime(&mime_type) {
+        debug!("Skipping {:?}: {} not in include filter", path, mime_type);
+        return Ok(Processed::Skipped);
+    }
+
+    let previous = index.get_by_path(&path)?;
+    let modified_ns = modified_ns(&metadata);
+
+    // Unchanged since it was last indexed: reuse the stored hash
+    let cached = previous.as_ref()
+        .filter(|previous| previous.is_unchanged(size, modified_ns))
+        .map(|previous| previous.hash.clone());
+
+    let stage = std::time::Instant::now();
+    let hash = match cached {
+        Some(hash) => {
+            debug!("Reusing stored hash for unchanged {:?}", path);
+            hash
+        }
+        None => {
+            // Make sure the file isn't still being written
+            if last_size != Some(size) {
+                if last_size.is_some() || config.stability_interval > MAX_INLINE_STABILITY_WAIT {
+                    return Ok(Processed::Unsettled(size));
+                }
+                std::thread::sleep(config.stability_interval);
+                let current = fs::metadata(&path).map_err(StreamError::from)?.len();
+                if current != size {
+                    return Ok(Processed::Unsettled(current));
+                }
+            }
 
-    // Get creation time
-    let created_at = metadata.created()
-        .unwrap_or(SystemTime::now())
+            // Hash content
+            let hash = hash_file(&path, config.hash_buffer_size)?;
+
+            // Grew while hashing: the hash is of a partial file
+            let current = fs::metadata(&path).map_err(StreamError::from)?.len();
+            if current != size {
+                return Ok(Processed::Unsettled(current));
+            }
+            hash
+        }
+    };
+    let hash_elapsed = stage.elapsed();
+
+    // Get creation time, keeping the first one seen across re-indexing
+    let existed = previous.is_some();
+    let created_at = match previous {
+        Some(previous) => previous.created_at,
+        None => metadata.created()
+            .unwrap_or(SystemTime::now())
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let updated_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
@@ -203,10 +778,34 @@ fn process_file_blocking(index: &FileIndex, path: PathBuf) -> StreamResult<()> {
         size,
         mime_type,
         created_at,
+        updated_at,
+        modified_ns,
     };
 
+    let stage = std::time::Instant::now();
     index.upsert_file(&meta)?;
+    // Indexed only: the blob still has to be imported before it can be served
+    index.set_import_state(&path, ImportState::Pending)?;
+    let index_elapsed = stage.elapsed();
+
+    debug!(
+        path = ?path,
+        size,
+        hash_us = hash_elapsed.as_micros() as u64,
+        mime_us = mime_elapsed.as_micros() as u64,
+        index_us = index_elapsed.as_micros() as u64,
+        total_us = started.elapsed().as_micros() as u64,
+        "Indexed file timing"
+    );
     info!("Indexed file: {:?} (Size: {} bytes)", path, size);
 
-    Ok(())
+    Ok(Processed::Indexed { meta, existed })
+}
+
+fn index_event(meta: FileMetadata, existed: bool) -> IndexEvent {
+    if existed {
+        IndexEvent::Updated(meta)
+    } else {
+        IndexEvent::Added(meta)
+    }
 }
@@ -1,6 +1,7 @@
 use ghostdrive_indexer::FileIndex;
-use ghostdrive_core::{FileMetadata, MediaHash};
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[test]
 fn test_crud_operations() {
@@ -8,7 +9,7 @@ fn test_crud_operations() {
     let _ = std::fs::remove_dir_all(&temp_dir);
     let db_path = temp_dir.join("test_crud.db");
 
-    let mut db = FileIndex::open(db_path.clone()).unwrap();
+    let db = FileIndex::open(db_path.clone()).unwrap();
 
     let meta = FileMetadata {
         path: PathBuf::from("/test/video.mp4"),
@@ -16,6 +17,8 @@ fn test_crud_operations() {
         size: 1024,
         mime_type: "video/mp4".into(),
         created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
     };
 
     // Upsert
@@ -43,4 +46,178 @@ fn test_crud_operations() {
 
     // Cleanup
     let _ = std::fs::remove_dir_all(temp_dir);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_content_change_drops_old_hash() {
+    let temp_dir = std::env::temp_dir().join("db_crud_rehash_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("rehash.db")).unwrap();
+
+    let original = FileMetadata {
+        path: PathBuf::from("/test/edited.mp4"),
+        hash: MediaHash("hash_a".into()),
+        size: 1024,
+        mime_type: "video/mp4".into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    };
+    let edited = FileMetadata {
+        hash: MediaHash("hash_b".into()),
+        size: 2048,
+        ..original.clone()
+    };
+
+    db.upsert_file(&original).unwrap();
+    db.upsert_file(&edited).unwrap();
+
+    // The old content is gone from this path
+    assert!(db.get_by_hash(&original.hash).unwrap().is_none());
+    assert_eq!(db.get_by_hash(&edited.hash).unwrap(), Some(edited));
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_identical_files_share_hash() {
+    let temp_dir = std::env::temp_dir().join("db_crud_shared_hash_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("shared.db")).unwrap();
+
+    let first = FileMetadata {
+        path: PathBuf::from("/movies/original.mp4"),
+        hash: MediaHash("same_content".into()),
+        size: 4096,
+        mime_type: "video/mp4".into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    };
+    let second = FileMetadata {
+        path: PathBuf::from("/backup/copy.mp4"),
+        ..first.clone()
+    };
+
+    db.upsert_file(&first).unwrap();
+    db.upsert_file(&second).unwrap();
+
+    let mut paths = db.get_all_paths_by_hash(&first.hash).unwrap();
+    paths.sort();
+    assert_eq!(paths, vec![second.path.clone(), first.path.clone()]);
+
+    // Removing one copy keeps the other discoverable
+    db.remove_file(&first.path).unwrap();
+    assert_eq!(db.get_by_hash(&first.hash).unwrap(), Some(second.clone()));
+    assert_eq!(db.get_all_paths_by_hash(&first.hash).unwrap(), vec![second.path.clone()]);
+
+    // The mapping goes away with the last path
+    db.remove_file(&second.path).unwrap();
+    assert!(db.get_by_hash(&first.hash).unwrap().is_none());
+    assert!(db.get_all_paths_by_hash(&first.hash).unwrap().is_empty());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_maybe_compact_threshold() {
+    let temp_dir = std::env::temp_dir().join("db_crud_compact_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    // Shared like the daemon shares it
+    let db = Arc::new(FileIndex::open(temp_dir.join("compact.db")).unwrap());
+
+    let file = |i: u32| FileMetadata {
+        path: PathBuf::from(format!("/test/{}.mp4", i)),
+        hash: MediaHash(format!("hash{}", i)),
+        size: 1024,
+        mime_type: "video/mp4".into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    };
+
+    // Nothing written yet
+    assert!(!db.maybe_compact(3).unwrap());
+
+    for i in 0..3 {
+        db.upsert_file(&file(i)).unwrap();
+    }
+    assert!(!db.maybe_compact(3).unwrap());
+
+    // Crossing the threshold reports what compaction did and resets the count
+    for i in 0..3 {
+        db.remove_file(&file(i).path).unwrap();
+    }
+    let compacted = db.maybe_compact(3).unwrap();
+    assert!(compacted, "freed pages should have been reclaimed");
+    assert!(!db.maybe_compact(3).unwrap());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_remove_by_hash() {
+    let temp_dir = std::env::temp_dir().join("db_crud_remove_hash_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("remove_hash.db")).unwrap();
+
+    let file = |path: &str, hash: &str| FileMetadata {
+        path: PathBuf::from(path),
+        hash: MediaHash(hash.into()),
+        size: 1024,
+        mime_type: "video/mp4".into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    };
+    let original = file("/test/movie.mp4", "hash_a");
+    let copy = file("/backup/movie.mp4", "hash_a");
+    let other = file("/test/other.mp4", "hash_b");
+    db.upsert_files(&[original.clone(), copy.clone(), other.clone()]).unwrap();
+    db.set_servable(&copy.path, false).unwrap();
+
+    assert!(db.remove_by_hash(&original.hash).unwrap());
+    assert!(db.get_by_path(&original.path).unwrap().is_none());
+    assert!(db.get_by_path(&copy.path).unwrap().is_none());
+    assert!(db.get_by_hash(&original.hash).unwrap().is_none());
+    assert!(db.is_servable(&copy.path).unwrap());
+
+    // Files with other content are kept
+    assert_eq!(db.get_by_hash(&other.hash).unwrap(), Some(other.clone()));
+    assert_eq!(db.list_all().unwrap(), vec![other]);
+
+    // Nothing left to remove
+    assert!(!db.remove_by_hash(&original.hash).unwrap());
+    assert!(!db.remove_by_hash(&MediaHash("unknown".into())).unwrap());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_interrupted_imports_are_reset() {
+    let temp_dir = std::env::temp_dir().join("db_crud_interrupted_import_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db_path = temp_dir.join("imports.db");
+
+    let stuck = PathBuf::from("/test/stuck.mp4");
+    let done = PathBuf::from("/test/done.mp4");
+    {
+        let db = FileIndex::open(db_path.clone()).unwrap();
+        db.set_import_state(&stuck, ImportState::Importing).unwrap();
+        db.set_import_state(&done, ImportState::Ready).unwrap();
+    }
+
+    // The process stopped mid-import; the next start must not block on it
+    let db = FileIndex::open(db_path).unwrap();
+    assert_eq!(db.reset_interrupted_imports().unwrap(), vec![stuck.clone()]);
+    assert_eq!(db.get_import_state(&stuck).unwrap(), Some(ImportState::Pending));
+    assert_eq!(db.get_import_state(&done).unwrap(), Some(ImportState::Ready));
+    assert!(db.reset_interrupted_imports().unwrap().is_empty());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use ghostdrive_core::{FileMetadata, Manifest, ManifestEntry, MediaHash};
+use ghostdrive_indexer::FileIndex;
+
+fn meta(path: &str, hash: &str, size: u64) -> FileMetadata {
+    FileMetadata {
+        path: PathBuf::from(path),
+        hash: MediaHash(hash.into()),
+        size,
+        mime_type: "video/mp4".into(),
+        created_at: 1_700_000_000,
+        updated_at: 1_700_000_000,
+        modified_ns: 0,
+    }
+}
+
+fn entry(name: &str, hash: &str, size: u64) -> ManifestEntry {
+    ManifestEntry { name: name.into(), hash: MediaHash(hash.into()), size }
+}
+
+#[test]
+fn test_diff_against_manifest() {
+    let temp_dir = std::env::temp_dir().join("db_diff_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("diff.db")).unwrap();
+
+    db.upsert_file(&meta("/lib/shared.mp4", "aaaa", 10)).unwrap();
+    db.upsert_file(&meta("/lib/local_name.mp4", "bbbb", 20)).unwrap();
+    db.upsert_file(&meta("/lib/only_local.mp4", "cccc", 30)).unwrap();
+
+    let manifest = Manifest::new(vec![
+        entry("shared.mp4", "aaaa", 10),
+        entry("remote_name.mp4", "bbbb", 20),
+        entry("only_remote.mp4", "dddd", 40),
+    ]);
+
+    let diff = db.diff_against(&manifest).unwrap();
+
+    assert_eq!(diff.missing, vec![entry("only_remote.mp4", "dddd", 40)]);
+    assert_eq!(diff.extra, vec![meta("/lib/only_local.mp4", "cccc", 30)]);
+    assert_eq!(diff.renamed.len(), 1);
+    assert_eq!(diff.renamed[0].0.name, "remote_name.mp4");
+    assert_eq!(diff.renamed[0].1.path, PathBuf::from("/lib/local_name.mp4"));
+
+    // Fully disjoint manifest: everything is missing or extra
+    let disjoint = Manifest::new(vec![entry("x.mp4", "eeee", 1)]);
+    let diff = db.diff_against(&disjoint).unwrap();
+    assert_eq!(diff.missing.len(), 1);
+    assert_eq!(diff.extra.len(), 3);
+    assert!(diff.renamed.is_empty());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -0,0 +1,54 @@
+use ghostdrive_indexer::{hash_file, FileIndex, DEFAULT_HASH_BUFFER_SIZE};
+use ghostdrive_core::{FileMetadata, MediaHash};
+use std::path::{Path, PathBuf};
+
+fn metadata(path: &Path) -> FileMetadata {
+    FileMetadata {
+        path: path.to_path_buf(),
+        hash: hash_file(path, DEFAULT_HASH_BUFFER_SIZE).unwrap(),
+        size: std::fs::metadata(path).unwrap().len(),
+        mime_type: "video/mp4".into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    }
+}
+
+#[test]
+fn test_find_duplicates() {
+    let temp_dir = std::env::temp_dir().join("db_duplicates_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let media_dir = temp_dir.join("media");
+    std::fs::create_dir_all(&media_dir).unwrap();
+    let db = FileIndex::open(temp_dir.join("duplicates.db")).unwrap();
+
+    // No files, no duplicates
+    assert!(db.find_duplicates().unwrap().is_empty());
+
+    let original = media_dir.join("movie.mp4");
+    let copy = media_dir.join("movie (copy).mp4");
+    let unique = media_dir.join("other.mp4");
+    std::fs::write(&original, "the same movie").unwrap();
+    std::fs::write(&copy, "the same movie").unwrap();
+    std::fs::write(&unique, "something else").unwrap();
+    for path in [&original, &copy, &unique] {
+        db.upsert_file(&metadata(path)).unwrap();
+    }
+
+    let hash = metadata(&original).hash;
+    assert_eq!(db.find_duplicates().unwrap(), vec![(hash, vec![copy.clone(), original.clone()])]);
+
+    // Groups wasting the most space come first
+    let small = |name: &str| FileMetadata {
+        path: PathBuf::from(format!("/small/{}", name)),
+        hash: MediaHash("small_hash".into()),
+        size: 1,
+        ..metadata(&unique)
+    };
+    db.upsert_files(&[small("a"), small("b"), small("c")]).unwrap();
+    let groups: Vec<_> = db.find_duplicates().unwrap().into_iter().map(|(hash, paths)| (hash, paths.len())).collect();
+    assert_eq!(groups, vec![(metadata(&original).hash, 2), (MediaHash("small_hash".into()), 3)]);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
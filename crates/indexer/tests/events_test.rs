@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_indexer::{FileIndex, FileWatcher, IndexEvent};
+use tokio::time::{sleep, timeout};
+
+#[tokio::test]
+async fn test_index_events() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_index_events_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let watcher = FileWatcher::new(index.clone(), vec![watch_path.clone()]).unwrap();
+    let mut events = watcher.subscribe();
+    tokio::spawn(watcher.run());
+    sleep(Duration::from_millis(200)).await;
+
+    let file_path = watch_path.join("trailer.mp4");
+    std::fs::write(&file_path, "trailer content").unwrap();
+
+    let event = timeout(Duration::from_secs(3), events.recv())
+        .await
+        .expect("No index event")
+        .unwrap();
+    let IndexEvent::Added(meta) = event else {
+        panic!("Expected Added, got {:?}", event);
+    };
+    assert_eq!(meta.path, file_path);
+    assert_eq!(meta.size, "trailer content".len() as u64);
+    assert_eq!(meta.mime_type, "video/mp4");
+    assert_eq!(index.get_by_path(&file_path).unwrap(), Some(meta));
+
+    std::fs::write(&file_path, "director's cut").unwrap();
+    let event = timeout(Duration::from_secs(3), events.recv()).await.expect("No update event").unwrap();
+    assert!(matches!(&event, IndexEvent::Updated(meta) if meta.size == "director's cut".len() as u64), "{:?}", event);
+
+    std::fs::remove_file(&file_path).unwrap();
+    let event = timeout(Duration::from_secs(3), events.recv()).await.expect("No removal event").unwrap();
+    assert_eq!(event, IndexEvent::Removed(file_path));
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
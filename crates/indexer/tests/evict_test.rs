@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+use ghostdrive_core::{FileMetadata, MediaHash};
+use ghostdrive_indexer::{EvictionPolicy, FileIndex};
+
+fn meta(path: &str, hash: &str, created_at: u64) -> FileMetadata {
+    FileMetadata {
+        path: PathBuf::from(path),
+        hash: MediaHash(hash.into()),
+        size: 10,
+        mime_type: "video/mp4".into(),
+        created_at,
+        updated_at: created_at,
+        modified_ns: 0,
+    }
+}
+
+#[test]
+fn test_evict_oldest_beyond_cap() {
+    let temp_dir = std::env::temp_dir().join("db_evict_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("evict.db")).unwrap();
+
+    db.upsert_file(&meta("/lib/new.mp4", "aaaa", 300)).unwrap();
+    db.upsert_file(&meta("/lib/oldest.mp4", "bbbb", 100)).unwrap();
+    db.upsert_file(&meta("/lib/middle.mp4", "cccc", 200)).unwrap();
+
+    // Within the cap nothing happens
+    assert!(db.evict(3, EvictionPolicy::OldestCreated).unwrap().is_empty());
+    assert_eq!(db.count().unwrap(), 3);
+
+    // Adding beyond the cap evicts the oldest entries
+    db.upsert_file(&meta("/lib/newest.mp4", "dddd", 400)).unwrap();
+    let evicted = db.evict(2, EvictionPolicy::OldestCreated).unwrap();
+
+    assert_eq!(evicted, vec![meta("/lib/oldest.mp4", "bbbb", 100), meta("/lib/middle.mp4", "cccc", 200)]);
+    assert_eq!(db.count().unwrap(), 2);
+    assert!(db.get_by_path(&PathBuf::from("/lib/oldest.mp4")).unwrap().is_none());
+    assert!(db.get_by_hash(&MediaHash("cccc".into())).unwrap().is_none());
+    assert!(db.get_by_path(&PathBuf::from("/lib/newest.mp4")).unwrap().is_some());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_evict_except_keeps_matching_entries() {
+    let temp_dir = std::env::temp_dir().join("db_evict_except_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("evict.db")).unwrap();
+
+    db.upsert_file(&meta("/lib/shared.mp4", "aaaa", 100)).unwrap();
+    db.upsert_file(&meta("/lib/older.mp4", "bbbb", 150)).unwrap();
+    db.upsert_file(&meta("/lib/newer.mp4", "cccc", 200)).unwrap();
+
+    // The oldest entry is being shared, so the next oldest goes instead
+    let shared = PathBuf::from("/lib/shared.mp4");
+    let evicted = db.evict_except(2, EvictionPolicy::OldestCreated, |meta| meta.path == shared).unwrap();
+
+    assert_eq!(evicted, vec![meta("/lib/older.mp4", "bbbb", 150)]);
+    assert!(db.get_by_path(&shared).unwrap().is_some());
+    assert_eq!(db.count().unwrap(), 2);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+use ghostdrive_core::{FileMetadata, MediaHash};
+use ghostdrive_indexer::{ExportFormat, FileIndex};
+
+fn meta(path: &str, hash: &str, size: u64) -> FileMetadata {
+    FileMetadata {
+        path: PathBuf::from(path),
+        hash: MediaHash(hash.into()),
+        size,
+        mime_type: "video/mp4".into(),
+        created_at: 1_700_000_000,
+        updated_at: 1_700_000_000,
+        modified_ns: 0,
+    }
+}
+
+fn round_trip(format: ExportFormat, name: &str) {
+    let temp_dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&temp_dir);
+
+    let source = FileIndex::open(temp_dir.join("source.db")).unwrap();
+    let files = vec![
+        meta("/lib/a.mp4", "aaaa", 10),
+        meta("/lib/b, \"quoted\".mkv", "bbbb", 20),
+        meta("/lib/c.mp4", "cccc", 30),
+    ];
+    for f in &files {
+        source.upsert_file(f).unwrap();
+    }
+
+    let mut buffer = Vec::new();
+    assert_eq!(source.export(format, &mut buffer).unwrap(), 3);
+
+    let restored = FileIndex::open(temp_dir.join("restored.db")).unwrap();
+    assert_eq!(restored.import(format, buffer.as_slice()).unwrap(), 3);
+
+    let mut all = restored.list_all().unwrap();
+    all.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(all, files);
+    assert_eq!(restored.get_by_hash(&MediaHash("bbbb".into())).unwrap(), Some(files[1].clone()));
+
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_json_round_trip() {
+    round_trip(ExportFormat::Json, "db_export_json_test");
+}
+
+#[test]
+fn test_csv_round_trip() {
+    round_trip(ExportFormat::Csv, "db_export_csv_test");
+}
+
+#[test]
+fn test_export_empty_index() {
+    let temp_dir = std::env::temp_dir().join("db_export_empty_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("empty.db")).unwrap();
+
+    let mut json = Vec::new();
+    assert_eq!(db.export(ExportFormat::Json, &mut json).unwrap(), 0);
+    let parsed: Vec<FileMetadata> = serde_json::from_slice(&json).unwrap();
+    assert!(parsed.is_empty());
+
+    let mut csv = Vec::new();
+    db.export(ExportFormat::Csv, &mut csv).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "path,hash,size,mime,created_at,updated_at\n");
+
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_json_backup_restore() {
+    let temp_dir = std::env::temp_dir().join("db_export_backup_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+
+    let source = FileIndex::open(temp_dir.join("source.db")).unwrap();
+    let files = vec![
+        meta("/lib/a.mp4", "aaaa", 10),
+        meta("/lib/copy_of_a.mp4", "aaaa", 10),
+        meta("/lib/b.mkv", "bbbb", 20),
+    ];
+    source.upsert_files(&files).unwrap();
+
+    let mut backup = Vec::new();
+    source.export(ExportFormat::Json, &mut backup).unwrap();
+
+    let restored = FileIndex::open(temp_dir.join("restored.db")).unwrap();
+    assert_eq!(restored.import(ExportFormat::Json, backup.as_slice()).unwrap(), 3);
+    assert_eq!(restored.list_all().unwrap(), source.list_all().unwrap());
+
+    // Reverse lookups work on the restored index
+    let hash = MediaHash("aaaa".into());
+    assert_eq!(restored.get_all_paths_by_hash(&hash).unwrap().len(), 2);
+    assert_eq!(restored.get_by_hash(&MediaHash("bbbb".into())).unwrap(), Some(files[2].clone()));
+
+    // A malformed backup imports nothing
+    let empty = FileIndex::open(temp_dir.join("empty.db")).unwrap();
+    assert!(empty.import(ExportFormat::Json, &b"[{\"path\": 1}]"[..]).is_err());
+    assert_eq!(empty.count().unwrap(), 0);
+
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
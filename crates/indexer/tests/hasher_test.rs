@@ -0,0 +1,62 @@
+use std::time::Instant;
+use ghostdrive_indexer::{hash_file, hash_file_streaming, DEFAULT_HASH_BUFFER_SIZE, MMAP_HASH_THRESHOLD};
+
+#[test]
+fn test_hash_identical_across_buffer_sizes() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_hasher_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    // 8MB of non-repeating-ish content
+    let content: Vec<u8> = (0..8 * 1024 * 1024u32).map(|i| (i.wrapping_mul(31) >> 3) as u8).collect();
+    let file_path = temp_dir.join("large.bin");
+    std::fs::write(&file_path, &content).unwrap();
+
+    let expected = blake3::hash(&content).to_hex().to_string();
+
+    for buffer_size in [4 * 1024, 64 * 1024, DEFAULT_HASH_BUFFER_SIZE, 4 * 1024 * 1024] {
+        let start = Instant::now();
+        let hash = hash_file(&file_path, buffer_size).unwrap();
+        println!("Buffer {:>8} bytes: {:?}", buffer_size, start.elapsed());
+
+        assert_eq!(hash.0, expected, "Hash differs with buffer size {}", buffer_size);
+    }
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_mmap_hash_matches_streaming() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_hasher_mmap_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    // Well above the threshold, so the memory-mapped path is taken
+    let len = (MMAP_HASH_THRESHOLD * 4) as u32;
+    let content: Vec<u8> = (0..len).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+    let file_path = temp_dir.join("huge.bin");
+    std::fs::write(&file_path, &content).unwrap();
+
+    let start = Instant::now();
+    let streamed = hash_file_streaming(&file_path, DEFAULT_HASH_BUFFER_SIZE).unwrap();
+    let streaming_time = start.elapsed();
+
+    let start = Instant::now();
+    let mapped = hash_file(&file_path, DEFAULT_HASH_BUFFER_SIZE).unwrap();
+    println!("Streaming: {:?}, memory-mapped: {:?}", streaming_time, start.elapsed());
+
+    assert_eq!(mapped, streamed);
+    assert_eq!(mapped.0, blake3::hash(&content).to_hex().to_string());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hash_special_file_falls_back_to_streaming() {
+    // Not a regular file, and can't be mapped
+    let hash = hash_file(std::path::Path::new("/dev/null"), DEFAULT_HASH_BUFFER_SIZE).unwrap();
+    assert_eq!(hash.0, blake3::hash(b"").to_hex().to_string());
+}
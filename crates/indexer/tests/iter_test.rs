@@ -0,0 +1,43 @@
+use ghostdrive_indexer::FileIndex;
+use ghostdrive_core::{FileMetadata, MediaHash};
+use std::path::PathBuf;
+
+#[test]
+fn test_iter_files_streams_large_index() {
+    let temp_dir = std::env::temp_dir().join("db_iter_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("iter.db")).unwrap();
+
+    let files: Vec<FileMetadata> = (0..20_000u64)
+        .map(|i| FileMetadata {
+            path: PathBuf::from(format!("/library/{:05}.mp4", i)),
+            hash: MediaHash(format!("hash{:05}", i)),
+            size: i,
+            mime_type: "video/mp4".into(),
+            created_at: 1_700_000_000,
+            updated_at: 1_700_000_000,
+            modified_ns: 0,
+        })
+        .collect();
+    db.upsert_files(&files).unwrap();
+
+    // Every entry comes through, in path order, past the old 10k cap
+    let mut count = 0u64;
+    let mut previous: Option<PathBuf> = None;
+    for entry in db.iter_files().unwrap() {
+        let meta = entry.unwrap();
+        assert!(previous.as_ref().is_none_or(|p| *p < meta.path));
+        previous = Some(meta.path);
+        count += 1;
+    }
+    assert_eq!(count, 20_000);
+
+    // The iterator's snapshot isn't disturbed by writes made while it runs
+    let mut iter = db.iter_files().unwrap();
+    db.remove_file(&files[19_999].path).unwrap();
+    assert_eq!(iter.by_ref().count(), 20_000);
+    assert_eq!(db.count().unwrap(), 19_999);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -0,0 +1,43 @@
+use ghostdrive_indexer::FileIndex;
+use ghostdrive_core::{FileMetadata, MediaHash};
+use std::path::PathBuf;
+
+#[test]
+fn test_list_paged_windows() {
+    let temp_dir = std::env::temp_dir().join("db_paging_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("paging.db")).unwrap();
+
+    let files: Vec<FileMetadata> = (0..25)
+        .map(|i| FileMetadata {
+            path: PathBuf::from(format!("/library/{:02}.mp4", i)),
+            hash: MediaHash(format!("hash{:02}", i)),
+            size: i,
+            mime_type: "video/mp4".into(),
+            created_at: 1_700_000_000,
+            updated_at: 1_700_000_000,
+            modified_ns: 0,
+        })
+        .collect();
+    db.upsert_files(&files).unwrap();
+    assert_eq!(db.count().unwrap(), 25);
+
+    // Pages come back in path order and cover the whole index
+    let first = db.list_paged(0, 10).unwrap();
+    let second = db.list_paged(10, 10).unwrap();
+    let last = db.list_paged(20, 10).unwrap();
+    assert_eq!(first, files[0..10]);
+    assert_eq!(second, files[10..20]);
+    assert_eq!(last, files[20..25]);
+
+    // Past the end and zero-sized pages are empty, not errors
+    assert!(db.list_paged(25, 10).unwrap().is_empty());
+    assert!(db.list_paged(1000, 10).unwrap().is_empty());
+    assert!(db.list_paged(0, 0).unwrap().is_empty());
+
+    // list_all is the unbounded page
+    assert_eq!(db.list_all().unwrap(), files);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
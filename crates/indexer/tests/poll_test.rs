@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_indexer::{is_network_mount, FileIndex, FileWatcher, WatchStrategy, WatcherConfig};
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn test_poll_strategy_indexes_new_files() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_poll_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+
+    // Local temp dirs are never network mounts
+    assert!(!is_network_mount(&watch_path));
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let config = WatcherConfig {
+        watch_strategy: WatchStrategy::Poll,
+        poll_interval: Duration::from_millis(100),
+        ..Default::default()
+    };
+    let watcher = FileWatcher::with_config(index.clone(), vec![watch_path.clone()], config).unwrap();
+    tokio::spawn(watcher.run());
+
+    // Give the poller its initial scan
+    sleep(Duration::from_millis(300)).await;
+
+    let file_path = watch_path.join("polled.mp4");
+    std::fs::write(&file_path, "seen by polling").unwrap();
+
+    // Poll interval + debounce (500ms) + processing
+    sleep(Duration::from_millis(1500)).await;
+
+    let found = index.get_by_path(&file_path).unwrap();
+    assert!(found.is_some(), "Polled file was not indexed");
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
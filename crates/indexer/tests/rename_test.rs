@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_core::{FileMetadata, ImportState, MediaHash};
+use ghostdrive_indexer::{FileIndex, FileWatcher};
+use tokio::time::{sleep, timeout};
+
+#[test]
+fn test_rename_file_moves_entry() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_rename_db_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+
+    let index = FileIndex::open(temp_dir.join("index.db")).unwrap();
+    let meta = FileMetadata {
+        path: PathBuf::from("/media/draft.mp4"),
+        hash: MediaHash("abc123456".into()),
+        size: 1024,
+        mime_type: "video/mp4".into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    };
+    index.upsert_file(&meta).unwrap();
+    index.set_import_state(&meta.path, ImportState::Ready).unwrap();
+    index.set_servable(&meta.path, false).unwrap();
+
+    let to = PathBuf::from("/media/final.mp4");
+    let moved = index.rename_file(&meta.path, &to).unwrap().expect("Indexed file not moved");
+    assert_eq!(moved, FileMetadata { path: to.clone(), ..meta.clone() });
+
+    assert!(index.get_by_path(&meta.path).unwrap().is_none());
+    assert_eq!(index.get_by_path(&to).unwrap(), Some(moved));
+    assert_eq!(index.get_all_paths_by_hash(&meta.hash).unwrap(), vec![to.clone()]);
+    assert_eq!(index.get_import_state(&to).unwrap(), Some(ImportState::Ready));
+    assert_eq!(index.get_import_state(&meta.path).unwrap(), None);
+    assert!(!index.is_servable(&to).unwrap());
+    assert!(index.is_servable(&meta.path).unwrap());
+
+    // Unknown sources are left to the caller
+    assert!(index.rename_file(&meta.path, &to).unwrap().is_none());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[tokio::test]
+async fn test_watcher_rename_keeps_hash() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_rename_watch_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let mut watcher = FileWatcher::new(index.clone(), vec![watch_path.clone()]).unwrap();
+    let mut indexed = watcher.subscribe_indexed();
+    tokio::spawn(watcher.run());
+    sleep(Duration::from_millis(200)).await;
+
+    let original = watch_path.join("episode.mkv");
+    std::fs::write(&original, "episode content").unwrap();
+    let first = timeout(Duration::from_secs(3), indexed.recv())
+        .await
+        .expect("File was not indexed")
+        .unwrap();
+
+    let renamed = watch_path.join("S01E01.mkv");
+    std::fs::rename(&original, &renamed).unwrap();
+
+    // The entry moves without the file being hashed (and reported) again
+    assert!(
+        timeout(Duration::from_millis(1500), indexed.recv()).await.is_err(),
+        "Renamed file was re-indexed"
+    );
+
+    let moved = index.get_by_path(&renamed).unwrap().expect("Entry not moved to new path");
+    assert_eq!(moved.hash, first.hash);
+    assert_eq!(moved.updated_at, first.updated_at);
+    assert!(index.get_by_path(&original).unwrap().is_none());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+use ghostdrive_core::MediaHash;
+use ghostdrive_indexer::FileIndex;
+use redb::{Database, TableDefinition};
+use serde::Serialize;
+
+/// Row layout written before `updated_at` existed
+#[derive(Serialize)]
+struct FileMetadataV1 {
+    path: PathBuf,
+    hash: MediaHash,
+    size: u64,
+    mime_type: String,
+    created_at: u64,
+}
+
+#[test]
+fn test_upgrades_rows_without_updated_at() {
+    let temp_dir = std::env::temp_dir().join("db_schema_upgrade_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+    let db_path = temp_dir.join("old.db");
+
+    // Write a database the way older versions did
+    {
+        let files: TableDefinition<&str, &[u8]> = TableDefinition::new("files");
+        let hashes: TableDefinition<&str, &str> = TableDefinition::new("hash_index");
+        let old = FileMetadataV1 {
+            path: PathBuf::from("/library/old.mp4"),
+            hash: MediaHash("oldhash".into()),
+            size: 42,
+            mime_type: "video/mp4".into(),
+            created_at: 1_600_000_000,
+        };
+        let encoded = bincode::serde::encode_to_vec(&old, bincode::config::standard()).unwrap();
+
+        let db = Database::create(&db_path).unwrap();
+        let txn = db.begin_write().unwrap();
+        {
+            txn.open_table(files).unwrap().insert("/library/old.mp4", encoded.as_slice()).unwrap();
+            txn.open_table(hashes).unwrap().insert("oldhash", "/library/old.mp4").unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let index = FileIndex::open(db_path.clone()).unwrap();
+    let meta = index.get_by_path(&PathBuf::from("/library/old.mp4")).unwrap().unwrap();
+    assert_eq!(meta.size, 42);
+    assert_eq!(meta.created_at, 1_600_000_000);
+    assert_eq!(meta.updated_at, meta.created_at);
+    assert_eq!(meta.modified_ns, 0);
+    assert_eq!(index.get_by_hash(&MediaHash("oldhash".into())).unwrap(), Some(meta.clone()));
+
+    // Upgrading is a one-off, reopening reads the new layout as is
+    drop(index);
+    let reopened = FileIndex::open(db_path).unwrap();
+    assert_eq!(reopened.list_all().unwrap(), vec![meta]);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
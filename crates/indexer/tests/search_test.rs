@@ -0,0 +1,87 @@
+use ghostdrive_indexer::FileIndex;
+use ghostdrive_core::{FileMetadata, MediaHash};
+use std::path::PathBuf;
+
+fn file(path: &str, mime: &str) -> FileMetadata {
+    FileMetadata {
+        path: PathBuf::from(path),
+        hash: MediaHash(format!("hash_of_{}", path)),
+        size: 1024,
+        mime_type: mime.into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    }
+}
+
+#[test]
+fn test_search_by_name() {
+    let temp_dir = std::env::temp_dir().join("db_search_name_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("search.db")).unwrap();
+
+    db.upsert_files(&[
+        file("/home/me/movies/Holiday Trip.mp4", "video/mp4"),
+        file("/home/me/movies/notes.txt", "text/plain"),
+        file("/home/me/music/trip-hop mix.mp3", "audio/mpeg"),
+    ]).unwrap();
+
+    // Case-insensitive substring of the file name
+    let mut found: Vec<PathBuf> = db.search_by_name("TRIP", None).unwrap().into_iter().map(|m| m.path).collect();
+    found.sort();
+    assert_eq!(found, vec![
+        PathBuf::from("/home/me/movies/Holiday Trip.mp4"),
+        PathBuf::from("/home/me/music/trip-hop mix.mp3"),
+    ]);
+
+    // Directory names don't match
+    assert!(db.search_by_name("movies", None).unwrap().is_empty());
+
+    // Empty query and limits
+    assert!(db.search_by_name("", None).unwrap().is_empty());
+    assert_eq!(db.search_by_name("trip", Some(1)).unwrap().len(), 1);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_list_by_mime_prefix() {
+    let temp_dir = std::env::temp_dir().join("db_search_mime_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("mime.db")).unwrap();
+
+    let video = file("/library/clip.mp4", "video/mp4");
+    let audio = file("/library/song.mp3", "audio/mpeg");
+    db.upsert_files(&[video.clone(), audio.clone(), file("/library/readme.txt", "text/plain")]).unwrap();
+
+    assert_eq!(db.list_by_mime_prefix("video/").unwrap(), vec![video]);
+    assert_eq!(db.list_by_mime_prefix("audio/").unwrap(), vec![audio]);
+    assert!(db.list_by_mime_prefix("image/").unwrap().is_empty());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn test_list_by_time_range() {
+    let temp_dir = std::env::temp_dir().join("db_search_time_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("time.db")).unwrap();
+
+    let at = |path: &str, created_at: u64| FileMetadata { created_at, ..file(path, "video/mp4") };
+    // Path order differs from time order
+    let newest = at("/library/a.mp4", 3000);
+    let oldest = at("/library/b.mp4", 1000);
+    let middle = at("/library/c.mp4", 2000);
+    db.upsert_files(&[newest.clone(), oldest.clone(), middle.clone()]).unwrap();
+
+    assert_eq!(db.list_by_time_range(0, u64::MAX).unwrap(), vec![oldest.clone(), middle.clone(), newest.clone()]);
+
+    // Start is inclusive, end exclusive
+    assert_eq!(db.list_by_time_range(1000, 3000).unwrap(), vec![oldest, middle]);
+    assert!(db.list_by_time_range(4000, 5000).unwrap().is_empty());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -0,0 +1,42 @@
+use ghostdrive_indexer::{FileIndex, IndexStats};
+use ghostdrive_core::{FileMetadata, MediaHash};
+use std::path::PathBuf;
+
+#[test]
+fn test_index_stats() {
+    let temp_dir = std::env::temp_dir().join("db_stats_test");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    let db = FileIndex::open(temp_dir.join("stats.db")).unwrap();
+
+    // Empty index: all zeros
+    assert_eq!(db.stats().unwrap(), IndexStats::default());
+
+    let file = |name: &str, size: u64, mime: &str| FileMetadata {
+        path: PathBuf::from(format!("/library/{}", name)),
+        hash: MediaHash(format!("hash_{}", name)),
+        size,
+        mime_type: mime.into(),
+        created_at: 1234567890,
+        updated_at: 1234567890,
+        modified_ns: 0,
+    };
+    db.upsert_files(&[
+        file("a.mp4", 1000, "video/mp4"),
+        file("b.mp4", 2000, "video/mp4"),
+        file("c.mp3", 500, "audio/mpeg"),
+    ]).unwrap();
+
+    let stats = db.stats().unwrap();
+    assert_eq!(stats.files, 3);
+    assert_eq!(stats.bytes, 3500);
+    assert_eq!(stats.by_mime.len(), 2);
+    assert_eq!(stats.by_mime["video/mp4"], 2);
+    assert_eq!(stats.by_mime["audio/mpeg"], 1);
+
+    // Serializable for APIs
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["bytes"], 3500);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_indexer::{FileIndex, FileWatcher};
+use tokio::runtime::Handle;
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn test_dropped_watchers_stop_ticking() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_ticker_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+
+    let metrics = Handle::current().metrics();
+    let baseline = metrics.num_alive_tasks();
+
+    let watchers: Vec<FileWatcher> = (0..20)
+        .map(|_| FileWatcher::new(index.clone(), vec![watch_path.clone()]).unwrap())
+        .collect();
+    assert_eq!(metrics.num_alive_tasks(), baseline + 20, "Each watcher should run one ticker");
+
+    drop(watchers);
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(metrics.num_alive_tasks(), baseline, "Ticker tasks outlived their watchers");
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_indexer::{FileIndex, FileWatcher};
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn test_add_and_remove_watch_at_runtime() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_watch_paths_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let movies = temp_root.join("movies");
+    let music = temp_root.join("music");
+    std::fs::create_dir_all(&movies).unwrap();
+    std::fs::create_dir_all(music.join("album")).unwrap();
+
+    // Already there before the directory is watched
+    let existing = music.join("album").join("track01.flac");
+    std::fs::write(&existing, "track one").unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let watcher = FileWatcher::new(index.clone(), vec![movies.clone()]).unwrap();
+    let handle = watcher.handle();
+    tokio::spawn(watcher.run());
+    sleep(Duration::from_millis(200)).await;
+
+    handle.add_watch(music.clone()).await.expect("Failed to add watch path");
+    let created = music.join("track02.flac");
+    std::fs::write(&created, "track two").unwrap();
+
+    // Debounce (500ms) + processing
+    sleep(Duration::from_millis(1500)).await;
+    assert!(index.get_by_path(&existing).unwrap().is_some(), "Existing file not scanned");
+    assert!(index.get_by_path(&created).unwrap().is_some(), "New file not indexed");
+
+    // Stop watching and forget the directory
+    handle.remove_watch(music.clone(), true).await.expect("Failed to remove watch path");
+    assert!(index.get_by_path(&existing).unwrap().is_none());
+    assert!(index.get_by_path(&created).unwrap().is_none());
+
+    let ignored = music.join("track03.flac");
+    std::fs::write(&ignored, "track three").unwrap();
+    sleep(Duration::from_millis(1500)).await;
+    assert!(index.get_by_path(&ignored).unwrap().is_none(), "Unwatched directory still indexed");
+
+    // Unknown paths are reported
+    assert!(handle.remove_watch(music, false).await.is_err());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+use std::time::Duration;
+use ghostdrive_indexer::{FileIndex, FileWatcher, WatcherConfig};
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn test_ignore_globs_and_mime_filter() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_watch_filter_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let config = WatcherConfig {
+        debounce: Duration::from_millis(100),
+        ignore_globs: vec!["*.part".into(), ".*".into()],
+        include_mime_prefixes: Some(vec!["video/".into(), "audio/".into()]),
+        ..Default::default()
+    };
+    let watcher = FileWatcher::with_config(index.clone(), vec![watch_path.clone()], config).unwrap();
+    tokio::spawn(watcher.run());
+    sleep(Duration::from_millis(200)).await;
+
+    let movie = watch_path.join("movie.mp4");
+    let partial = watch_path.join("movie.mp4.part");
+    let hidden = watch_path.join(".cache.mp4");
+    let notes = watch_path.join("notes.txt");
+    for path in [&movie, &partial, &hidden, &notes] {
+        std::fs::write(path, "content").unwrap();
+    }
+
+    sleep(Duration::from_millis(1000)).await;
+
+    assert!(index.get_by_path(&movie).unwrap().is_some(), "Video was not indexed");
+    assert!(index.get_by_path(&partial).unwrap().is_none(), "Ignored glob was indexed");
+    assert!(index.get_by_path(&hidden).unwrap().is_none(), "Dotfile was indexed");
+    assert!(index.get_by_path(&notes).unwrap().is_none(), "Filtered MIME type was indexed");
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
+
+#[tokio::test]
+async fn test_custom_debounce() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_watch_debounce_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let config = WatcherConfig {
+        debounce: Duration::from_millis(1500),
+        ..Default::default()
+    };
+    let watcher = FileWatcher::with_config(index.clone(), vec![watch_path.clone()], config).unwrap();
+    tokio::spawn(watcher.run());
+    sleep(Duration::from_millis(200)).await;
+
+    let file_path = watch_path.join("slow.mp4");
+    std::fs::write(&file_path, "still settling").unwrap();
+
+    // Well past the default debounce, but inside the configured one
+    sleep(Duration::from_millis(900)).await;
+    assert!(index.get_by_path(&file_path).unwrap().is_none(), "Indexed before the debounce elapsed");
+
+    sleep(Duration::from_millis(1500)).await;
+    assert!(index.get_by_path(&file_path).unwrap().is_some(), "Not indexed after the debounce");
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
+
+#[test]
+fn test_invalid_ignore_glob_rejected() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_watch_bad_glob_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+    std::fs::create_dir_all(&temp_root).unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let config = WatcherConfig {
+        ignore_globs: vec!["[unclosed".into()],
+        ..Default::default()
+    };
+
+    // Rejected before any watcher (or tick task) is started
+    assert!(FileWatcher::with_config(index, vec![temp_root.join("media")], config).is_err());
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
+
+#[tokio::test]
+async fn test_growing_file_indexed_once_settled() {
+    use std::io::Write;
+
+    let temp_root = std::env::temp_dir().join("ghostdrive_watch_stability_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+
+    let watch_path = temp_root.join("media");
+    std::fs::create_dir_all(&watch_path).unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let config = WatcherConfig {
+        debounce: Duration::from_millis(50),
+        stability_interval: Duration::from_millis(400),
+        ..Default::default()
+    };
+    let mut watcher = FileWatcher::with_config(index.clone(), vec![watch_path.clone()], config).unwrap();
+    let mut indexed = watcher.subscribe_indexed();
+    tokio::spawn(watcher.run());
+    sleep(Duration::from_millis(200)).await;
+
+    // A download arriving in two chunks, the debounce expiring in between
+    let file_path = watch_path.join("download.mp4");
+    let mut file = std::fs::File::create(&file_path).unwrap();
+    file.write_all(&[1u8; 4096]).unwrap();
+    file.flush().unwrap();
+    sleep(Duration::from_millis(300)).await;
+    file.write_all(&[2u8; 4096]).unwrap();
+    file.flush().unwrap();
+    drop(file);
+
+    sleep(Duration::from_millis(2000)).await;
+
+    let mut sizes = Vec::new();
+    while let Ok(meta) = indexed.try_recv() {
+        sizes.push(meta.size);
+    }
+    assert_eq!(sizes, vec![8192], "Partial file was indexed");
+    assert_eq!(index.get_by_path(&file_path).unwrap().unwrap().size, 8192);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
+
+#[test]
+fn test_file_watch_path_rejected() {
+    let temp_root = std::env::temp_dir().join("ghostdrive_watch_file_path_test");
+    let _ = std::fs::remove_dir_all(&temp_root);
+    std::fs::create_dir_all(&temp_root).unwrap();
+    let file = temp_root.join("not_a_dir.txt");
+    std::fs::write(&file, "content").unwrap();
+
+    let index = Arc::new(FileIndex::open(temp_root.join("index.db")).unwrap());
+    let err = FileWatcher::new(index, vec![file.clone()]).err().expect("File accepted as watch path");
+    assert!(err.to_string().contains("not a directory"), "unexpected error: {}", err);
+
+    // Cleanup
+    let _ = std::fs::remove_dir_all(temp_root);
+}
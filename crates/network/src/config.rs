@@ -0,0 +1,70 @@
+use iroh::RelayUrl;
+
+/// How peers find each other's current addresses from a node id
+///
+/// With discovery, a ticket's node id alone can be dialed even if the relay
+/// it names has changed; without it, only the ticket's relay URL is tried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiscoveryMode {
+    /// Publish to and resolve via n0's DNS/pkarr servers (works across the internet)
+    #[default]
+    Dns,
+    /// Announce and resolve on the local network only via mDNS
+    ///
+    /// Nothing leaves the LAN, but peers elsewhere can only reach this node
+    /// through the relay named in the ticket.
+    LocalNetwork,
+    /// Both DNS/pkarr and mDNS
+    DnsAndLocalNetwork,
+    /// No discovery: the node id is never published, tickets must carry a
+    /// reachable relay URL
+    Disabled,
+}
+
+/// Which relay servers the node uses to reach peers behind NATs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RelayMode {
+    /// n0's public relays
+    #[default]
+    Default,
+    /// A single self-hosted relay
+    Custom(RelayUrl),
+    /// No relays: only direct connections (e.g. on a LAN)
+    Disabled,
+}
+
+/// Options for starting a [`StreamNode`](crate::StreamNode)
+#[derive(Debug, Clone, Default)]
+pub struct StreamNodeConfig {
+    /// Maximum number of concurrent blob requests served to a single peer
+    ///
+    /// Requests beyond the cap are rejected so one aggressive peer can't
+    /// starve the others. `None` means unlimited.
+    pub max_requests_per_peer: Option<usize>,
+    /// Label of the identity to run as
+    ///
+    /// Each label has its own key (and so its own node id) and peer history,
+    /// while the blob store is shared. Only one identity can run from a data
+    /// dir at a time. `None` uses the default `secret.key` identity.
+    pub identity: Option<String>,
+    /// Mechanisms used to publish and resolve node addresses
+    pub discovery: DiscoveryMode,
+    /// Relay servers to use, if any
+    pub relay: RelayMode,
+    /// Fail reference imports that can't reference the file in place
+    ///
+    /// By default such imports fall back to copying the content into the
+    /// store with a warning, so ingestion still succeeds (e.g. on network
+    /// mounts). Strict mode returns the error instead.
+    pub strict_reference: bool,
+    /// Cap in bytes per second on content served to peers, `None` for no cap
+    ///
+    /// The cap is shared by all peers and requests together, so a node
+    /// serving many peers stays within it.
+    pub max_upload_bps: Option<u64>,
+    /// Cap in bytes per second on content downloaded from tickets, `None`
+    /// for no cap
+    ///
+    /// Shared by all downloads running on the node.
+    pub max_download_bps: Option<u64>,
+}
@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+
+use ghostdrive_core::{ShareTicket, StreamError, StreamResult};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Table: Destination path (String) -> Versioned DownloadIntent row (Bytes)
+const DOWNLOADS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("downloads");
+
+/// Leading byte of rows holding a JSON `DownloadIntent`
+///
+/// JSON rather than bincode: tickets skip unset fields (expiry, direct
+/// addresses) when serialized, so their bincode layout varies per ticket.
+const INTENT_ROW_VERSION: u8 = 1;
+
+/// A download that was started but hasn't completed yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadIntent {
+    /// Ticket the content is fetched from
+    pub ticket: ShareTicket,
+    /// Where the content lands once verified
+    pub dest: PathBuf,
+}
+
+fn encode_intent(intent: &DownloadIntent) -> StreamResult<Vec<u8>> {
+    let mut row = vec![INTENT_ROW_VERSION];
+    serde_json::to_writer(&mut row, intent)
+        .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+    Ok(row)
+}
+
+fn decode_intent(row: &[u8]) -> StreamResult<DownloadIntent> {
+    match row.split_first() {
+        Some((&INTENT_ROW_VERSION, json)) => serde_json::from_slice(json)
+            .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e))),
+        _ => Err(StreamError::Database("Unsupported download intent row".to_string())),
+    }
+}
+
+/// Persistent record of unfinished downloads, so they survive a restart
+pub(crate) struct DownloadLog {
+    db: Database,
+}
+
+impl DownloadLog {
+    /// Open or create the download log at the specified path
+    pub(crate) fn open(path: PathBuf) -> StreamResult<Self> {
+        let db = Database::create(&path).map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let txn = db.begin_write().map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let _ = txn.open_table(DOWNLOADS_TABLE).map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        Ok(Self { db })
+    }
+
+    /// Remember a download, replacing any earlier one to the same destination
+    pub(crate) fn record(&self, intent: &DownloadIntent) -> StreamResult<()> {
+        let encoded = encode_intent(intent)?;
+
+        let txn = self.db.begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let mut table = txn.open_table(DOWNLOADS_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            table.insert(intent.dest.to_string_lossy().as_ref(), encoded.as_slice())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        debug!("Recorded download intent for {:?}", intent.dest);
+        Ok(())
+    }
+
+    /// Forget the download to `dest`, logging rather than failing
+    ///
+    /// A stale entry only means a finished download is fetched again (as a
+    /// no-op) after the next restart.
+    pub(crate) fn forget(&self, dest: &std::path::Path) {
+        if let Err(e) = self.remove(dest) {
+            warn!("Failed to forget download {:?}: {}", dest, e);
+        }
+    }
+
+    fn remove(&self, dest: &std::path::Path) -> StreamResult<()> {
+        let txn = self.db.begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let mut table = txn.open_table(DOWNLOADS_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            table.remove(dest.to_string_lossy().as_ref())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// List every unfinished download
+    ///
+    /// Rows that can't be decoded are skipped with a warning, so one bad
+    /// entry doesn't hide the others.
+    pub(crate) fn list(&self) -> StreamResult<Vec<DownloadIntent>> {
+        let txn = self.db.begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let table = txn.open_table(DOWNLOADS_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let mut results = Vec::new();
+        for entry in table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (key, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            match decode_intent(value.value()) {
+                Ok(intent) => results.push(intent),
+                Err(e) => warn!("Skipping unreadable download intent for {:?}: {}", key.value(), e),
+            }
+        }
+
+        Ok(results)
+    }
+}
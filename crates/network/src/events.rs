@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use iroh::EndpointId;
+use iroh_blobs::api::blobs::BlobStatus;
+use iroh_blobs::api::Store;
+use iroh_blobs::hashseq::HashSeq;
+use iroh_blobs::protocol::GetRequest;
+use iroh_blobs::Hash;
+use iroh_blobs::provider::events::{
+    AbortReason, ConnectMode, EventMask, EventSender, ProviderMessage, RequestMode, RequestUpdate, ThrottleMode,
+};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::peers::PeerLog;
+use crate::throttle::RateLimiter;
+
+/// Capacity of the provider event channel
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Largest root read to check the members a request reaches (64Ki hashes)
+///
+/// Bigger blobs are no hash sequence we created, so child ranges into them
+/// are refused without reading them.
+const MAX_HASH_SEQ_SIZE: u64 = 32 * 64 * 1024;
+
+/// Policies enforced on incoming blob requests
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProviderPolicy {
+    /// Maximum concurrent requests from a single peer (None = unlimited)
+    pub max_requests_per_peer: Option<usize>,
+    /// Blobs that stay in the store but are not served to anyone
+    pub withheld: Withheld,
+    /// Cap on the bytes served to all peers together
+    pub upload_limit: Option<Arc<RateLimiter>>,
+    /// Store served from, used to resolve the members a request reaches
+    pub store: Option<Store>,
+}
+
+/// Shared set of withheld blob hashes, updated while the node runs
+pub(crate) type Withheld = Arc<RwLock<HashSet<Hash>>>;
+
+/// In-flight request counts per peer
+type InFlight = Arc<Mutex<HashMap<EndpointId, usize>>>;
+
+/// Decrements a peer's in-flight count when the request finishes
+struct InFlightGuard {
+    in_flight: InFlight,
+    peer: EndpointId,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut counts = self.in_flight.lock().expect("in-flight lock poisoned");
+        if let Some(count) = counts.get_mut(&self.peer) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                counts.remove(&self.peer);
+            }
+        }
+    }
+}
+
+/// Create the event sender handed to `BlobsProtocol` and spawn the loop consuming it
+pub(crate) fn spawn_provider_events(peers: Arc<PeerLog>, policy: ProviderPolicy) -> (EventSender, JoinHandle<()>) {
+    let mask = EventMask {
+        connected: ConnectMode::Notify,
+        get: RequestMode::InterceptLog,
+        get_many: RequestMode::Intercept,
+        // Chunks are only held back when uploads are capped
+        throttle: if policy.upload_limit.is_some() { ThrottleMode::Intercept } else { ThrottleMode::None },
+        ..EventMask::DEFAULT
+    };
+    let (sender, mut rx) = EventSender::channel(EVENT_CHANNEL_CAPACITY, mask);
+
+    let handle = tokio::spawn(async move {
+        // Connection ID -> remote node, to attribute requests to peers
+        let mut connections: HashMap<u64, EndpointId> = HashMap::new();
+        let in_flight: InFlight = Arc::default();
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                ProviderMessage::ClientConnectedNotify(msg) => {
+                    let Some(endpoint_id) = msg.inner.endpoint_id else {
+                        continue;
+                    };
+                    connections.insert(msg.inner.connection_id, endpoint_id);
+                    if let Err(e) = peers.record_connect(&endpoint_id.to_string(), unix_now()) {
+                        warn!("Failed to record peer connection: {}", e);
+                    }
+                }
+                ProviderMessage::ConnectionClosed(msg) => {
+                    connections.remove(&msg.inner.connection_id);
+                }
+                ProviderMessage::Throttle(msg) => {
+                    // Release the chunk once the shared budget allows, without stalling the event loop
+                    let limiter = policy.upload_limit.clone();
+                    tokio::spawn(async move {
+                        if let Some(limiter) = limiter {
+                            limiter.acquire(msg.inner.size).await;
+                        }
+                        let _ = msg.tx.send(Ok(())).await;
+                    });
+                }
+                ProviderMessage::GetManyRequestReceived(msg) => {
+                    let blocked = {
+                        let withheld = policy.withheld.read().expect("withheld lock poisoned");
+                        msg.inner.request.hashes.iter().find(|hash| withheld.contains(hash)).copied()
+                    };
+                    let reply = match blocked {
+                        Some(hash) => {
+                            debug!("Rejecting multi-blob request including withheld blob {}", hash);
+                            Err(AbortReason::Permission)
+                        }
+                        None => Ok(()),
+                    };
+                    let _ = msg.tx.send(reply).await;
+                }
+                ProviderMessage::GetRequestReceived(mut msg) => {
+                    let endpoint_id = connections.get(&msg.inner.connection_id).copied();
+                    let policy = policy.clone();
+                    let in_flight = in_flight.clone();
+                    let peers = peers.clone();
+
+                    // Checking collection members reads from the store, so
+                    // decide and follow the transfer without stalling the event loop
+                    tokio::spawn(async move {
+                        let requested = msg.inner.request.hash;
+                        if reaches_withheld(&policy, &msg.inner.request).await {
+                            debug!("Rejecting request for {} reaching withheld content", requested);
+                            let _ = msg.tx.send(Err(AbortReason::Permission)).await;
+                            return;
+                        }
+
+                        let Some(endpoint_id) = endpoint_id else {
+                            let _ = msg.tx.send(Ok(())).await;
+                            return;
+                        };
+
+                        // Enforce the per-peer concurrency cap
+                        let guard = {
+                            let mut counts = in_flight.lock().expect("in-flight lock poisoned");
+                            let count = counts.entry(endpoint_id).or_default();
+                            if policy.max_requests_per_peer.is_some_and(|max| *count >= max) {
+                                None
+                            } else {
+                                *count += 1;
+                                Some(InFlightGuard { in_flight: in_flight.clone(), peer: endpoint_id })
+                            }
+                        };
+                        let Some(_guard) = guard else {
+                            debug!("Rejecting request from {}: concurrency limit reached", endpoint_id);
+                            let _ = msg.tx.send(Err(AbortReason::RateLimited)).await;
+                            return;
+                        };
+                        let _ = msg.tx.send(Ok(())).await;
+
+                        while let Ok(Some(update)) = msg.rx.recv().await {
+                            let stats = match update {
+                                RequestUpdate::Completed(done) => done.stats,
+                                RequestUpdate::Aborted(aborted) => aborted.stats,
+                                _ => continue,
+                            };
+                            debug!("Served {} bytes to {}", stats.payload_bytes_sent, endpoint_id);
+                            if let Err(e) = peers.record_bytes(&endpoint_id.to_string(), stats.payload_bytes_sent, unix_now()) {
+                                warn!("Failed to record served bytes: {}", e);
+                            }
+                            break;
+                        }
+                    });
+                }
+                _ => {}
+            }
+        }
+    });
+
+    (sender, handle)
+}
+
+/// Whether serving `request` would send any withheld blob
+///
+/// Child ranges address the members of a hash sequence root, so those
+/// members are looked up in the store and checked as well. The root is only
+/// read if its size fits a hash sequence of at most [`MAX_HASH_SEQ_SIZE`].
+async fn reaches_withheld(policy: &ProviderPolicy, request: &GetRequest) -> bool {
+    let withheld = {
+        let withheld = policy.withheld.read().expect("withheld lock poisoned");
+        if withheld.contains(&request.hash) {
+            return true;
+        }
+        if withheld.is_empty() || request.ranges.is_blob() {
+            return false;
+        }
+        withheld.clone()
+    };
+
+    // Without the store the members are unknown, so refuse to be safe
+    let Some(store) = &policy.store else {
+        return true;
+    };
+    match store.blobs().status(request.hash).await {
+        // Not a whole number of hashes: the provider can't serve children either
+        Ok(BlobStatus::Complete { size }) if size % 32 != 0 => return false,
+        Ok(BlobStatus::Complete { size }) if size <= MAX_HASH_SEQ_SIZE => {}
+        Ok(BlobStatus::NotFound) => return false,
+        // Too large to check cheaply, or incomplete
+        _ => return true,
+    }
+    let members = match store.get_bytes(request.hash).await.map(HashSeq::try_from) {
+        Ok(Ok(members)) => members,
+        // Nothing the provider could serve children from either
+        _ => return false,
+    };
+
+    for (offset, _) in request.ranges.iter_non_empty_infinite() {
+        if offset == 0 {
+            continue;
+        }
+        let Some(member) = members.get((offset - 1) as usize) else {
+            break;
+        };
+        if withheld.contains(&member) {
+            return true;
+        }
+    }
+    false
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
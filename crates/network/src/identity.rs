@@ -0,0 +1,140 @@
+use std::path::{Path, PathBuf};
+
+use ghostdrive_core::{crypto, StreamError, StreamResult};
+use iroh::SecretKey;
+use tokio::fs;
+use tracing::info;
+
+/// Prefix marking a passphrase-encrypted key file (followed by hex of the sealed key)
+const ENCRYPTED_PREFIX: &str = "encrypted:v1:";
+
+/// Key file of the unlabeled default identity
+const DEFAULT_KEY_FILE: &str = "secret.key";
+
+/// Directory (under the data dir) holding labeled identities
+const IDENTITIES_DIR: &str = "identities";
+
+/// Key file for the identity `label` in `data_dir` (`None` is the default identity)
+pub(crate) fn key_path(data_dir: &Path, label: Option<&str>) -> StreamResult<PathBuf> {
+    match label {
+        None => Ok(data_dir.join(DEFAULT_KEY_FILE)),
+        Some(label) => {
+            validate_label(label)?;
+            Ok(data_dir.join(IDENTITIES_DIR).join(format!("{}.key", label)))
+        }
+    }
+}
+
+/// Labels become file names, so keep them to a safe character set
+fn validate_label(label: &str) -> StreamResult<()> {
+    let valid = !label.is_empty()
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(StreamError::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid identity label {:?}: use letters, digits, '-' or '_'", label)
+        )))
+    }
+}
+
+/// Labels of all identities stored under `data_dir`, sorted
+pub(crate) async fn list_labels(data_dir: &Path) -> StreamResult<Vec<String>> {
+    let mut labels = Vec::new();
+
+    let mut entries = match fs::read_dir(data_dir.join(IDENTITIES_DIR)).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(labels),
+        Err(e) => return Err(StreamError::from(e)),
+    };
+
+    while let Some(entry) = entries.next_entry().await.map_err(StreamError::from)? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "key") {
+            if let Some(label) = path.file_stem().and_then(|s| s.to_str()) {
+                if validate_label(label).is_ok() {
+                    labels.push(label.to_string());
+                }
+            }
+        }
+    }
+
+    labels.sort();
+    Ok(labels)
+}
+
+/// Load the identity at `key_path`, generating and persisting a new one if missing
+///
+/// Plaintext (hex) key files always load; encrypted ones require `passphrase`.
+/// Newly generated keys are encrypted when a passphrase is given.
+pub(crate) async fn load_or_generate(key_path: &Path, passphrase: Option<&str>) -> StreamResult<SecretKey> {
+    if key_path.exists() {
+        return read_key(key_path, passphrase).await;
+    }
+
+    info!("Generating new persistent identity...");
+    if let Some(parent) = key_path.parent() {
+        fs::create_dir_all(parent).await.map_err(StreamError::from)?;
+    }
+    let key = SecretKey::generate(&mut rand::rng());
+    write_key(key_path, &key, passphrase).await?;
+    Ok(key)
+}
+
+/// Read a key file, decrypting it if needed
+pub(crate) async fn read_key(key_path: &Path, passphrase: Option<&str>) -> StreamResult<SecretKey> {
+    let contents = fs::read_to_string(key_path)
+        .await
+        .map_err(StreamError::from)?;
+    let contents = contents.trim();
+
+    let bytes = match contents.strip_prefix(ENCRYPTED_PREFIX) {
+        Some(sealed_hex) => {
+            let passphrase = passphrase.ok_or_else(|| {
+                StreamError::Decryption("Identity key is encrypted; a passphrase is required".to_string())
+            })?;
+            let sealed = hex::decode(sealed_hex)
+                .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+            crypto::open(passphrase, &sealed)?
+        }
+        None => hex::decode(contents)
+            .map_err(|e| StreamError::from(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?,
+    };
+
+    Ok(SecretKey::from_bytes(&bytes.try_into().map_err(|_| {
+        StreamError::from(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid key length"))
+    })?))
+}
+
+/// Write a key file (owner-only permissions), encrypting it when a passphrase is given
+pub(crate) async fn write_key(key_path: &Path, key: &SecretKey, passphrase: Option<&str>) -> StreamResult<()> {
+    let contents = match passphrase {
+        Some(passphrase) => {
+            let sealed = crypto::seal(passphrase, &key.to_bytes())?;
+            format!("{}{}", ENCRYPTED_PREFIX, hex::encode(sealed))
+        }
+        None => hex::encode(key.to_bytes()),
+    };
+
+    fs::write(key_path, contents).await.map_err(StreamError::from)?;
+
+    // Set permissions to 600 (Unix only)
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(key_path).await.map_err(StreamError::from)?.permissions();
+        perms.set_mode(0o600);
+        fs::set_permissions(key_path, perms).await.map_err(StreamError::from)?;
+    }
+
+    Ok(())
+}
+
+/// Whether the key file at `key_path` is passphrase-encrypted
+pub(crate) async fn is_encrypted(key_path: &Path) -> StreamResult<bool> {
+    let contents = fs::read_to_string(key_path)
+        .await
+        .map_err(StreamError::from)?;
+    Ok(contents.trim_start().starts_with(ENCRYPTED_PREFIX))
+}
@@ -1,3 +1,18 @@
+mod config;
+mod downloads;
+mod events;
+mod identity;
 mod node;
+mod peers;
+mod store_compat;
+mod throttle;
+mod ticket;
+mod transfers;
 
-pub use node::StreamNode;
\ No newline at end of file
+pub use config::{DiscoveryMode, RelayMode, StreamNodeConfig};
+pub use downloads::DownloadIntent;
+pub use node::{BlobAvailability, BlobStatus, GcReport, ImportStrategy, NodeMetrics, ShutdownHandle, StreamNode, DEFAULT_MAX_FETCH_BYTES};
+pub use peers::PeerRecord;
+pub use ticket::IrohTicketExt;
+
+pub use transfers::{TransferHandle, TransferId, TransferInfo};
\ No newline at end of file
@@ -1,87 +1,256 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use ghostdrive_core::{MediaHash, ShareTicket, StreamError, StreamResult};
-use iroh::{Endpoint, EndpointId, SecretKey};
+use ghostdrive_core::{Manifest, ManifestEntry, MediaHash, ShareTicket, StreamError, StreamResult, RELAY_DISABLED};
+use iroh::{Endpoint, EndpointAddr, EndpointId, RelayUrl, SecretKey};
+use iroh::discovery::{dns::DnsDiscovery, mdns::MdnsDiscovery, pkarr::PkarrPublisher};
+use iroh::endpoint::Connection;
 use iroh::protocol::Router;
 use iroh_blobs::{
     BlobsProtocol,
     store::fs::FsStore as BlobStore,
-    api::blobs::{AddPathOptions, ImportMode},
-    BlobFormat, Hash, ALPN,
+    api::blobs::{AddPathOptions, BlobStatus as StoreStatus, ImportMode},
+    api::remote::{GetProgress, GetProgressItem},
+    protocol::{ChunkRanges, ChunkRangesExt, GetRequest},
+    BlobFormat, Hash, HashAndFormat, ALPN,
 };
+use bytes::Bytes;
+use futures::StreamExt;
 use tokio::fs;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::config::{DiscoveryMode, RelayMode, StreamNodeConfig};
+use crate::downloads::{DownloadIntent, DownloadLog};
+use crate::events::{spawn_provider_events, ProviderPolicy, Withheld};
+use crate::identity;
+use crate::peers::{PeerLog, PeerRecord};
+use crate::store_compat;
+use crate::throttle::RateLimiter;
+use crate::transfers::{TransferHandle, TransferId, TransferInfo, TransferRegistry};
+
+/// How files are brought into the blob store
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportStrategy {
+    /// Reference the file in place without copying
+    #[default]
+    Reference,
+    /// Copy the content into the store, deduplicating identical sources
+    Copy,
+}
+
+/// Result of [`StreamNode::probe_ticket`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobAvailability {
+    /// The peer has the complete blob
+    Available {
+        /// Verified size of the blob in bytes
+        size: u64,
+    },
+    /// The peer is reachable but doesn't serve the blob
+    Missing,
+    /// The peer couldn't be reached
+    Unreachable,
+}
+
+/// How much of a blob the local store holds, see [`StreamNode::blob_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobStatus {
+    /// Every byte is stored and verified
+    Complete {
+        /// Size of the blob in bytes
+        size: u64,
+    },
+    /// Some verified chunks are stored, e.g. after an interrupted download
+    Partial {
+        /// Verified bytes held locally
+        have: u64,
+        /// Size of the blob, `None` until it has been verified
+        total: Option<u64>,
+    },
+    /// Nothing of the blob is stored
+    Missing,
+}
+
+/// Result of [`StreamNode::gc`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Number of blobs deleted
+    pub blobs: usize,
+    /// Bytes those blobs occupied
+    pub bytes: u64,
+}
+
+/// Traffic counters from the iroh endpoint, see [`StreamNode::metrics`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeMetrics {
+    /// Bytes sent over direct (IPv4/IPv6) and relayed paths since start
+    pub bytes_sent: u64,
+    /// Bytes received over direct and relayed paths since start
+    pub bytes_received: u64,
+    /// Connections currently open, if iroh tracks them
+    pub active_connections: Option<u64>,
+}
+
+/// Tag name prefix marking collection blobs, whose members are kept alive by GC
+const COLLECTION_TAG_PREFIX: &str = "collection/";
+
+/// Tag name prefix keeping downloaded blobs alive
+const DOWNLOAD_TAG_PREFIX: &str = "download/";
+
+/// BLAKE3 chunk size, the unit of range requests
+const CHUNK_SIZE: u64 = 1024;
+
+/// How long [`StreamNode::fetch_multi`] waits for each peer to answer
+const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long [`StreamNode::shutdown`] waits for connections to drain
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default size cap for [`StreamNode::fetch_bytes`] (16 MiB)
+pub const DEFAULT_MAX_FETCH_BYTES: u64 = 16 * 1024 * 1024;
 
 pub struct StreamNode {
+    data_dir: PathBuf,
+    config: StreamNodeConfig,
     endpoint: Endpoint,
     store: BlobStore,
-    _router: Router, // Keep router alive
-    #[allow(dead_code)] // Kept for potential future use/export
+    router: Router,
     secret_key: SecretKey,
+    peers: Arc<PeerLog>,
+    transfers: Arc<TransferRegistry>,
+    withheld: Withheld,
+    /// Whether reference imports fail as unsupported, see [`StreamNode::fail_references`]
+    #[cfg(any(test, feature = "test-util"))]
+    reference_fault: std::sync::atomic::AtomicBool,
+    downloads: Arc<DownloadLog>,
+    /// Shared cap on ticket downloads, see [`StreamNodeConfig::max_download_bps`]
+    download_limit: Option<Arc<RateLimiter>>,
+    /// Blobs being fetched or assembled, which GC must leave alone
+    pins: Pins,
 }
 
 impl StreamNode {
     /// Initialize the Iroh node with persistent identity
     pub async fn new(data_dir: PathBuf) -> StreamResult<Self> {
+        Self::with_config(data_dir, StreamNodeConfig::default()).await
+    }
+
+    /// Initialize the node with custom options
+    pub async fn with_config(data_dir: PathBuf, config: StreamNodeConfig) -> StreamResult<Self> {
+        Self::start(data_dir, None, config).await
+    }
+
+    /// Initialize the node with an identity key encrypted under `passphrase`
+    ///
+    /// A new identity is written encrypted; an existing plaintext key still
+    /// loads (use [`StreamNode::encrypt_identity`] to convert it).
+    pub async fn new_with_passphrase(data_dir: PathBuf, passphrase: &str) -> StreamResult<Self> {
+        Self::start(data_dir, Some(passphrase), StreamNodeConfig::default()).await
+    }
+
+    /// Initialize the node with a given identity key instead of loading one
+    ///
+    /// Used to move an identity between machines (see
+    /// [`StreamNode::export_secret_key`]). The key is saved to `data_dir` if
+    /// no identity exists there yet; an existing key file is left untouched.
+    pub async fn new_with_key(data_dir: PathBuf, key: SecretKey) -> StreamResult<Self> {
+        fs::create_dir_all(&data_dir)
+            .await
+            .map_err(StreamError::from)?;
+
+        let key_path = identity::key_path(&data_dir, None)?;
+        if !key_path.exists() {
+            identity::write_key(&key_path, &key, None).await?;
+        }
+
+        Self::launch(data_dir, key, StreamNodeConfig::default()).await
+    }
+
+    /// Labels of the identities stored in `data_dir`, for use with [`StreamNodeConfig::identity`]
+    ///
+    /// The default unlabeled identity is not included.
+    pub async fn list_identities(data_dir: &Path) -> StreamResult<Vec<String>> {
+        identity::list_labels(data_dir).await
+    }
+
+    /// Encrypt an existing plaintext identity key in `data_dir` under `passphrase`
+    ///
+    /// Does nothing if the key is already encrypted.
+    pub async fn encrypt_identity(data_dir: &Path, passphrase: &str) -> StreamResult<()> {
+        let key_path = identity::key_path(data_dir, None)?;
+        if !key_path.exists() {
+            return Err(StreamError::FileNotFound(key_path));
+        }
+        if identity::is_encrypted(&key_path).await? {
+            return Ok(());
+        }
+
+        let key = identity::read_key(&key_path, None).await?;
+        identity::write_key(&key_path, &key, Some(passphrase)).await?;
+        info!("Encrypted identity key at {:?}", key_path);
+        Ok(())
+    }
+
+    async fn start(
+        data_dir: PathBuf,
+        passphrase: Option<&str>,
+        config: StreamNodeConfig
+    ) -> StreamResult<Self> {
         // Ensure data directory exists
         if !data_dir.exists() {
             fs::create_dir_all(&data_dir)
                 .await
-                .map_err(StreamError::Io)?;
+                .map_err(StreamError::from)?;
         }
 
-        let key_path = data_dir.join("secret.key");
+        let key_path = identity::key_path(&data_dir, config.identity.as_deref())?;
 
         // Load or generate secret key
-        let secret_key = if key_path.exists() {
-            let hex_str = fs::read_to_string(&key_path)
-                .await
-                .map_err(StreamError::Io)?;
-            let bytes = hex::decode(hex_str.trim())
-                .map_err(|e| StreamError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
-            SecretKey::from_bytes(&bytes.try_into().map_err(|_| {
-                StreamError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid key length"))
-            })?)
-        } else {
-            info!("Generating new persistent identity...");
-            let key = SecretKey::generate(&mut rand::rng());
-            let hex_str = hex::encode(key.to_bytes());
-
-            fs::write(&key_path, hex_str).await.map_err(StreamError::Io)?;
-
-            // Set permissions to 600 (Unix only)
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let mut perms = fs::metadata(&key_path).await.map_err(StreamError::Io)?.permissions();
-                perms.set_mode(0o600);
-                fs::set_permissions(&key_path, perms).await.map_err(StreamError::Io)?;
-            }
+        let secret_key = identity::load_or_generate(&key_path, passphrase).await?;
 
-            key
-        };
+        Self::launch(data_dir, secret_key, config).await
+    }
 
+    /// Bring up the store, endpoint and router for an already loaded identity
+    async fn launch(data_dir: PathBuf, secret_key: SecretKey, config: StreamNodeConfig) -> StreamResult<Self> {
         // Initialize Blob Store
         let blobs_dir = data_dir.join("blobs");
         fs::create_dir_all(&blobs_dir)
             .await
-            .map_err(StreamError::Io)?;
+            .map_err(StreamError::from)?;
+
+        // Upgrades legacy layouts and explains how to recover from load failures
+        let store = store_compat::load_store(&blobs_dir).await?;
 
-        let store = BlobStore::load(&blobs_dir)
-            .await
-            .map_err(|e| StreamError::Database(format!("Failed to load blob store: {}", e)))?;
-            
         // Initialize Endpoint
-        let endpoint = Endpoint::builder()
+        let endpoint = with_discovery(Endpoint::builder(), config.discovery)
+            .relay_mode(iroh_relay_mode(&config.relay))
             .secret_key(secret_key.clone())
             .bind()
             .await
             .map_err(|e| StreamError::Iroh(e.to_string()))?;
 
+        // Track peers that connect to us and our own downloads, separately
+        // for each identity
+        let [peers_file, downloads_file] = log_files(config.identity.as_deref());
+        let peers = Arc::new(PeerLog::open(data_dir.join(peers_file))?);
+        let downloads = Arc::new(DownloadLog::open(data_dir.join(downloads_file))?);
+        let withheld = Withheld::default();
+        let policy = ProviderPolicy {
+            max_requests_per_peer: config.max_requests_per_peer,
+            withheld: withheld.clone(),
+            upload_limit: config.max_upload_bps.and_then(RateLimiter::new).map(Arc::new),
+            store: Some((*store).clone()),
+        };
+        let (events, _) = spawn_provider_events(peers.clone(), policy);
+        let download_limit = config.max_download_bps.and_then(RateLimiter::new).map(Arc::new);
+
         // Setup protocol router (Handling Blobs ALPN)
-        let blobs_protocol = BlobsProtocol::new(&store, None); // Use reference and None events
+        let blobs_protocol = BlobsProtocol::new(&store, Some(events));
         let router = Router::builder(endpoint.clone())
             .accept(ALPN, blobs_protocol)
             .spawn();
@@ -104,13 +273,30 @@ impl StreamNode {
         }
 
         Ok(Self {
+            data_dir,
+            config,
             endpoint,
             store,
-            _router: router,
+            router,
             secret_key,
+            peers,
+            transfers: Arc::new(TransferRegistry::default()),
+            withheld,
+            #[cfg(any(test, feature = "test-util"))]
+            reference_fault: Default::default(),
+            downloads,
+            download_limit,
+            pins: Pins::default(),
         })
     }
 
+    /// Hex-encoded identity key, for backing up or moving the node id
+    ///
+    /// Anyone holding this key can impersonate the node.
+    pub fn export_secret_key(&self) -> String {
+        hex::encode(self.secret_key.to_bytes())
+    }
+
     /// Return the base32-encoded Node ID
     pub fn node_id(&self) -> String {
         self.endpoint.id().to_string()
@@ -122,7 +308,13 @@ impl StreamNode {
     }
 
     /// Get the primary relay URL (if connected)
+    ///
+    /// Nodes with relays disabled report [`RELAY_DISABLED`].
     pub fn relay_url(&self) -> String {
+        if self.config.relay == RelayMode::Disabled {
+            return RELAY_DISABLED.to_string();
+        }
+
         self.endpoint
             .addr()
             .relay_urls()
@@ -131,12 +323,77 @@ impl StreamNode {
             .unwrap_or_else(|| "None".to_string())
     }
 
+    /// Snapshot of the endpoint's traffic counters
+    ///
+    /// Reads atomic counters only, so it is cheap enough to poll.
+    pub fn metrics(&self) -> NodeMetrics {
+        let magicsock = &self.endpoint.metrics().magicsock;
+        let opened = magicsock.num_conns_opened.get();
+        let closed = magicsock.num_conns_closed.get();
+
+        NodeMetrics {
+            bytes_sent: magicsock.send_ipv4.get() + magicsock.send_ipv6.get() + magicsock.send_relay.get(),
+            bytes_received: magicsock.recv_data_ipv4.get()
+                + magicsock.recv_data_ipv6.get()
+                + magicsock.recv_data_relay.get(),
+            active_connections: opened.checked_sub(closed),
+        }
+    }
+
+    /// Check relay connectivity and re-establish it if it has lapsed
+    ///
+    /// If the node doesn't come online within `timeout`, iroh is told the
+    /// network may have changed so it re-probes and reconnects to a relay.
+    /// Returns true if a relay URL is known afterwards.
+    pub async fn keep_alive(&self, timeout: Duration) -> bool {
+        let online = tokio::time::timeout(timeout, async {
+            let _ = self.endpoint.online().await;
+        }).await;
+
+        if online.is_err() {
+            warn!("Relay connection lapsed, refreshing network state");
+            self.endpoint.network_change().await;
+        }
+
+        let relay = self.endpoint.addr().relay_urls().next().cloned();
+        match &relay {
+            Some(url) => debug!("Keep-alive: relay {}", url),
+            None => warn!("Keep-alive: no relay available"),
+        }
+        relay.is_some()
+    }
+
+    /// Peers that have connected to this node, with first/last seen and bytes served
+    pub fn known_peers(&self) -> StreamResult<Vec<PeerRecord>> {
+        self.peers.list()
+    }
+
+    /// Forget all recorded peer history
+    pub fn clear_known_peers(&self) -> StreamResult<()> {
+        self.peers.clear()
+    }
+
     /// Get a reference to the underlying Iroh Endpoint
     pub fn endpoint(&self) -> &Endpoint {
         &self.endpoint
     }
 
+    /// Add a file to the blob store using the given import strategy
+    pub async fn add_file(
+        &self,
+        file_path: PathBuf,
+        strategy: ImportStrategy
+    ) -> StreamResult<MediaHash> {
+        match strategy {
+            ImportStrategy::Reference => self.add_file_reference(file_path).await,
+            ImportStrategy::Copy => self.add_file_copy(file_path).await,
+        }
+    }
+
     /// Add a file to the blob store using path reference (no copy)
+    ///
+    /// If the file can't be referenced in place, its content is copied into
+    /// the store instead, unless [`StreamNodeConfig::strict_reference`] is set.
     pub async fn add_file_reference(
         &self,
         file_path: PathBuf
@@ -145,8 +402,27 @@ impl StreamNode {
             return Err(StreamError::FileNotFound(file_path));
         }
 
+        match self.try_reference(&file_path).await {
+            Ok(hash) => Ok(hash),
+            Err(ReferenceError::Unsupported(e)) if !self.config.strict_reference => {
+                warn!("Cannot reference {:?} in place, copying instead: {}", file_path, e);
+                self.add_file_copy(file_path).await
+            }
+            Err(ReferenceError::Unsupported(e) | ReferenceError::Failed(e)) => Err(e),
+        }
+    }
+
+    /// Import a file without copying
+    async fn try_reference(&self, file_path: &Path) -> Result<MediaHash, ReferenceError> {
+        #[cfg(any(test, feature = "test-util"))]
+        if self.reference_fault.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(ReferenceError::Unsupported(StreamError::Iroh(
+                "Failed to add file reference: simulated failure".to_string()
+            )));
+        }
+
         let options = AddPathOptions {
-            path: file_path.clone(),
+            path: file_path.to_path_buf(),
             mode: ImportMode::TryReference,
             format: BlobFormat::Raw,
         };
@@ -155,7 +431,14 @@ impl StreamNode {
         // .await on AddProgress yields the final result (RequestResult<TagInfo>)
         let outcome = self.store.add_path_with_opts(options)
             .await
-            .map_err(|e| StreamError::Iroh(format!("Failed to add file reference: {}", e)))?;
+            .map_err(|e| {
+                let error = StreamError::Iroh(format!("Failed to add file reference: {}", e));
+                if reference_unsupported(&e) {
+                    ReferenceError::Unsupported(error)
+                } else {
+                    ReferenceError::Failed(error)
+                }
+            })?;
 
         let hash = outcome.hash;
         info!("Added file reference: {:?} (Hash: {})", file_path, hash);
@@ -163,6 +446,235 @@ impl StreamNode {
         Ok(MediaHash(hash.to_string()))
     }
 
+    /// Make every reference import fail as unsupported, to exercise the copy fallback
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn fail_references(&self, fail: bool) {
+        self.reference_fault.store(fail, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Add a file to the blob store by copying its content
+    ///
+    /// The file is hashed first; if the store already holds that content the
+    /// import is skipped and only a new tag is added to the existing blob, so
+    /// duplicate sources never cost a second copy.
+    pub async fn add_file_copy(
+        &self,
+        file_path: PathBuf
+    ) -> Result<MediaHash, StreamError> {
+        if !file_path.exists() {
+            return Err(StreamError::FileNotFound(file_path));
+        }
+
+        let hash = hash_path(&file_path).await?;
+        let present = self.store.has(hash)
+            .await
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        if present {
+            self.store.tags().create(HashAndFormat::raw(hash))
+                .await
+                .map_err(|e| StreamError::Iroh(format!("Failed to tag existing blob: {}", e)))?;
+            info!("Skipped duplicate import: {:?} (Hash: {})", file_path, hash);
+            return Ok(MediaHash(hash.to_string()));
+        }
+
+        let options = AddPathOptions {
+            path: file_path.clone(),
+            mode: ImportMode::Copy,
+            format: BlobFormat::Raw,
+        };
+
+        let outcome = self.store.add_path_with_opts(options)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to copy file into store: {}", e)))?;
+
+        info!("Copied file into store: {:?} (Hash: {})", file_path, outcome.hash);
+        Ok(MediaHash(outcome.hash.to_string()))
+    }
+
+    /// How much of `hash` the store holds, read from the store itself
+    ///
+    /// Unlike the file index this reflects what can actually be served, so
+    /// it is worth checking before generating a ticket or after a download
+    /// that may have been interrupted.
+    pub async fn blob_status(&self, hash: &MediaHash) -> StreamResult<BlobStatus> {
+        let hash = parse_hash(hash)?;
+        let status = self.store.blobs().status(hash)
+            .await
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        Ok(match status {
+            StoreStatus::Complete { size } => BlobStatus::Complete { size },
+            StoreStatus::NotFound => BlobStatus::Missing,
+            StoreStatus::Partial { size } => {
+                let local = self.store.remote().local(HashAndFormat::raw(hash))
+                    .await
+                    .map_err(|e| StreamError::Database(e.to_string()))?;
+                BlobStatus::Partial { have: local.local_bytes(), total: size }
+            }
+        })
+    }
+
+    /// Whether the store holds the complete content of `hash`, see [`StreamNode::blob_status`]
+    pub async fn has_blob(&self, hash: &MediaHash) -> StreamResult<bool> {
+        Ok(matches!(self.blob_status(hash).await?, BlobStatus::Complete { .. }))
+    }
+
+    /// List the hashes of all blobs in the store
+    pub async fn list_blobs(&self) -> StreamResult<Vec<MediaHash>> {
+        let hashes = self.store.blobs().list().hashes()
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to list blobs: {}", e)))?;
+
+        Ok(hashes.into_iter().map(|h| MediaHash(h.to_string())).collect())
+    }
+
+    /// Start or stop serving a blob to peers without removing it from the store
+    ///
+    /// Requests for a withheld blob are refused at the protocol layer. Tags
+    /// are left alone: in iroh-blobs they only protect content from garbage
+    /// collection and don't control what is served. The setting lasts for the
+    /// lifetime of this node.
+    pub fn set_servable(&self, hash: &MediaHash, servable: bool) -> StreamResult<()> {
+        let hash = parse_hash(hash)?;
+        let mut withheld = self.withheld.write().expect("withheld lock poisoned");
+        if servable {
+            withheld.remove(&hash);
+        } else {
+            withheld.insert(hash);
+        }
+        debug!("Blob {} servable: {}", hash, servable);
+        Ok(())
+    }
+
+    /// Whether the blob is currently served to peers
+    pub fn is_servable(&self, hash: &MediaHash) -> StreamResult<bool> {
+        let hash = parse_hash(hash)?;
+        Ok(!self.withheld.read().expect("withheld lock poisoned").contains(&hash))
+    }
+
+    /// Drop every tag pointing at `hash` and delete the blob from the store
+    ///
+    /// Deleting a blob that isn't stored is a no-op. The caller must make
+    /// sure nothing else still needs this content (e.g. another indexed file
+    /// with the same hash), since tags are not per file.
+    pub async fn delete_blob(&self, hash: &MediaHash) -> StreamResult<()> {
+        let hash = parse_hash(hash)?;
+
+        let dropped = self.drop_tags(hash).await?;
+        let stored = self.store.blobs().status(hash)
+            .await
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        if dropped == 0 && matches!(stored, StoreStatus::NotFound) {
+            debug!("Blob {} not in store, nothing to delete", hash);
+            return Ok(());
+        }
+
+        self.store.blobs().delete([hash])
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to delete blob: {}", e)))?;
+
+        info!("Removed blob {}", hash);
+        Ok(())
+    }
+
+    /// Delete every tag pointing at `hash`, returning how many were dropped
+    async fn drop_tags(&self, hash: Hash) -> StreamResult<usize> {
+        let mut tags = self.store.tags().list()
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to list tags: {}", e)))?;
+        let mut names = Vec::new();
+        while let Some(tag) = tags.next().await {
+            let tag = tag.map_err(|e| StreamError::Database(e.to_string()))?;
+            if tag.hash == hash {
+                names.push(tag.name);
+            }
+        }
+
+        let dropped = names.len();
+        for name in names {
+            self.store.tags().delete(name)
+                .await
+                .map_err(|e| StreamError::Database(format!("Failed to delete tag: {}", e)))?;
+        }
+
+        Ok(dropped)
+    }
+
+    /// Drop every tag pointing at `hash`, leaving the content for [`StreamNode::gc`]
+    pub async fn untag_blob(&self, hash: &MediaHash) -> StreamResult<()> {
+        let dropped = self.drop_tags(parse_hash(hash)?).await?;
+        debug!("Dropped {} tag(s) for {}", dropped, hash);
+        Ok(())
+    }
+
+    /// Delete every complete blob that no tag keeps alive
+    ///
+    /// A blob is live if a tag points at it or it is a member of a live
+    /// collection. Finished downloads are tagged, and blobs still being
+    /// fetched or only partially stored (so a download can resume) are
+    /// never collected.
+    pub async fn gc(&self) -> StreamResult<GcReport> {
+        let mut live = std::collections::HashSet::new();
+        let mut collections = Vec::new();
+
+        let mut tags = self.store.tags().list()
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to list tags: {}", e)))?;
+        while let Some(tag) = tags.next().await {
+            let tag = tag.map_err(|e| StreamError::Database(e.to_string()))?;
+            if tag.name.0.starts_with(COLLECTION_TAG_PREFIX.as_bytes()) {
+                collections.push(tag.hash);
+            }
+            live.insert(tag.hash);
+        }
+
+        for collection in collections {
+            let bytes = self.store.get_bytes(collection)
+                .await
+                .map_err(|e| StreamError::Iroh(format!("Failed to read collection {}: {}", collection, e)))?;
+            live.extend(collection_members(&bytes)?);
+        }
+
+        let stored = self.store.blobs().list().hashes()
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to list blobs: {}", e)))?;
+
+        let mut report = GcReport::default();
+        for hash in stored.into_iter().filter(|h| !live.contains(h)) {
+            let size = match self.store.blobs().status(hash)
+                .await
+                .map_err(|e| StreamError::Database(e.to_string()))?
+            {
+                StoreStatus::Complete { size } => size,
+                _ => continue,
+            };
+            if self.pins.contains(hash) {
+                debug!("Keeping blob {} in use by a transfer", hash);
+                continue;
+            }
+
+            self.store.blobs().delete([hash])
+                .await
+                .map_err(|e| StreamError::Database(format!("Failed to delete blob: {}", e)))?;
+
+            debug!("Collected blob {} ({} bytes)", hash, size);
+            report.blobs += 1;
+            report.bytes += size;
+        }
+
+        info!("GC removed {} blob(s), {} bytes", report.blobs, report.bytes);
+        Ok(report)
+    }
+
+    /// Mark a stored blob as a collection so GC keeps its members alive
+    async fn tag_collection(&self, hash: Hash) -> StreamResult<()> {
+        self.store.tags().set(format!("{}{}", COLLECTION_TAG_PREFIX, hash), HashAndFormat::raw(hash))
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to tag collection: {}", e)))?;
+        Ok(())
+    }
+
     /// Create a collection (HashSeq) from multiple file hashes
     pub async fn create_collection(
         &self,
@@ -181,17 +693,75 @@ impl StreamNode {
             bytes.extend_from_slice(h.as_bytes());
         }
 
-        // Add the collection blob itself
+        // Add the collection blob itself, pinned until it is tagged
+        let _pin = self.pins.pin(Hash::new(&bytes));
         let outcome = self.store.add_bytes(bytes)
             .await
             .map_err(|e| StreamError::Iroh(format!("Failed to create collection: {}", e)))?;
 
         let hash = outcome.hash;
+        self.tag_collection(hash).await?;
         info!("Created collection with hash: {}", hash);
 
         Ok(MediaHash(hash.to_string()))
     }
 
+    /// List the member hashes of a collection made by [`StreamNode::create_collection`]
+    ///
+    /// The collection blob must already be in the local store, e.g. after
+    /// downloading it from a folder ticket.
+    pub async fn read_collection(&self, hash: &MediaHash) -> StreamResult<Vec<MediaHash>> {
+        let collection_hash = parse_hash(hash)?;
+        let bytes = self.store.get_bytes(collection_hash)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to read collection {}: {}", hash, e)))?;
+
+        let members = raw_collection_members(&bytes)
+            .map_err(|e| StreamError::InvalidHash(format!("Blob {} is not a collection: {}", hash, e)))?;
+
+        Ok(members.into_iter().map(|h| MediaHash(h.to_string())).collect())
+    }
+
+    /// Create a collection that records a name for each member
+    ///
+    /// The collection blob is an encoded [`Manifest`], so names and sizes
+    /// travel with the hashes. Sizes are taken from the local store; members
+    /// that aren't stored locally are listed with size 0.
+    pub async fn create_named_collection(&self, entries: Vec<(String, MediaHash)>) -> StreamResult<MediaHash> {
+        let mut manifest_entries = Vec::with_capacity(entries.len());
+        for (name, hash) in entries {
+            let size = match self.store.blobs().status(parse_hash(&hash)?)
+                .await
+                .map_err(|e| StreamError::Database(e.to_string()))?
+            {
+                StoreStatus::Complete { size } => size,
+                _ => 0,
+            };
+            manifest_entries.push(ManifestEntry { name, hash, size });
+        }
+
+        let encoded = Manifest::new(manifest_entries).encode();
+        let _pin = self.pins.pin(Hash::new(&encoded));
+        let outcome = self.store.add_bytes(encoded)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to create collection: {}", e)))?;
+
+        self.tag_collection(outcome.hash).await?;
+        info!("Created named collection with hash: {}", outcome.hash);
+        Ok(MediaHash(outcome.hash.to_string()))
+    }
+
+    /// Read back a collection made by [`StreamNode::create_named_collection`]
+    ///
+    /// The collection blob must already be in the local store.
+    pub async fn read_named_collection(&self, hash: &MediaHash) -> StreamResult<Manifest> {
+        let bytes = self.store.get_bytes(parse_hash(hash)?)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to read collection {}: {}", hash, e)))?;
+
+        Manifest::decode(&bytes)
+    }
+
     /// Generate a shareable ticket
     pub fn generate_ticket(
         &self,
@@ -203,10 +773,980 @@ impl StreamNode {
             relay_url: self.relay_url(),
             hash,
             name,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            created_at: unix_now(),
+            expires_at: None,
+            direct_addrs: self.endpoint.addr().ip_addrs().map(|addr| addr.to_string()).collect(),
+            hash_seq: false,
         }
     }
-}
\ No newline at end of file
+
+    /// Generate a ticket for a collection made by [`StreamNode::create_collection`]
+    ///
+    /// The ticket is marked as a hash sequence, so iroh tools fetch the
+    /// members along with it. Fetch it with [`StreamNode::fetch_collection`].
+    pub fn generate_collection_ticket(&self, hash: MediaHash, name: String) -> ShareTicket {
+        ShareTicket { hash_seq: true, ..self.generate_ticket(hash, name) }
+    }
+
+    /// Generate a ticket that stops being honored `ttl` after now
+    ///
+    /// Expiry is enforced by the downloading side, which refuses expired
+    /// tickets with [`StreamError::TicketExpired`].
+    pub fn generate_ticket_with_ttl(&self, hash: MediaHash, name: String, ttl: Duration) -> ShareTicket {
+        let mut ticket = self.generate_ticket(hash, name);
+        ticket.expires_at = Some(ticket.created_at.saturating_add(ttl.as_secs()));
+        ticket
+    }
+
+    /// Relocate the node's data (identity, blob store, peer and download
+    /// logs) to `new_dir`
+    ///
+    /// Blobs and tags are copied into a fresh store under `new_dir`, each blob
+    /// is re-hashed on import to verify integrity, and a node started from the
+    /// new location is returned. Blobs already complete in the target store are
+    /// skipped, so an interrupted migration can simply be run again. Peer and
+    /// download logs are copied unless `new_dir` already has its own.
+    ///
+    /// If copying fails this node keeps running untouched. Once the copy is
+    /// done it stops serving and should be dropped in favour of the returned
+    /// node. With `remove_old` the blob store, this identity's key and its
+    /// logs are deleted from the old dir, but only if every blob was copied;
+    /// incomplete blobs can't be copied, so their partial data keeps the old
+    /// data in place. Keys and logs of other identities are left alone.
+    pub async fn migrate_store(&self, new_dir: PathBuf, remove_old: bool) -> StreamResult<Self> {
+        // Compare real locations, `data` and `./data` are the same dir
+        fs::create_dir_all(&new_dir).await.map_err(StreamError::from)?;
+        let new_dir = fs::canonicalize(&new_dir).await.map_err(StreamError::from)?;
+        let old_dir = fs::canonicalize(&self.data_dir).await.map_err(StreamError::from)?;
+        if new_dir == old_dir {
+            return Err(StreamError::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{:?} is already the node's data dir", new_dir)
+            )));
+        }
+        info!("Migrating blob store from {:?} to {:?}", old_dir, new_dir);
+
+        let new_blobs_dir = new_dir.join("blobs");
+        let staging_dir = new_dir.join(".migrate");
+        fs::create_dir_all(&new_blobs_dir).await.map_err(StreamError::from)?;
+        fs::create_dir_all(&staging_dir).await.map_err(StreamError::from)?;
+
+        // Carry the identity over so the node id stays the same
+        let label = self.config.identity.as_deref();
+        let old_key = identity::key_path(&old_dir, label)?;
+        let new_key = identity::key_path(&new_dir, label)?;
+        if new_key.exists() {
+            let existing = fs::read(&new_key).await.map_err(StreamError::from)?;
+            let current = fs::read(&old_key).await.map_err(StreamError::from)?;
+            if existing != current {
+                return Err(StreamError::from(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{:?} already holds a different identity", new_key)
+                )));
+            }
+        } else {
+            if let Some(parent) = new_key.parent() {
+                fs::create_dir_all(parent).await.map_err(StreamError::from)?;
+            }
+            fs::copy(&old_key, &new_key).await.map_err(StreamError::from)?;
+        }
+
+        let target = BlobStore::load(&new_blobs_dir)
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to load blob store: {}", e)))?;
+
+        // Copy every complete blob, verifying the content hash on import
+        let hashes = self.store.blobs().list().hashes()
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to list blobs: {}", e)))?;
+
+        let mut copied = 0usize;
+        let mut skipped = 0usize;
+        let mut kept = Vec::new();
+        for hash in hashes {
+            let already_there = target.has(hash)
+                .await
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            if already_there {
+                continue;
+            }
+
+            let complete = self.store.has(hash)
+                .await
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+            if !complete {
+                warn!("Skipping incomplete blob during migration: {}", hash);
+                skipped += 1;
+                continue;
+            }
+
+            let staged = staging_dir.join(hash.to_string());
+            self.store.blobs().export(hash, staged.clone())
+                .await
+                .map_err(|e| StreamError::Database(format!("Failed to export blob {}: {}", hash, e)))?;
+
+            let options = AddPathOptions {
+                path: staged.clone(),
+                mode: ImportMode::Copy,
+                format: BlobFormat::Raw,
+            };
+            let tag = target.add_path_with_opts(options)
+                .temp_tag()
+                .await
+                .map_err(|e| StreamError::Database(format!("Failed to import blob {}: {}", hash, e)))?;
+            let _ = fs::remove_file(&staged).await;
+
+            if tag.hash() != hash {
+                return Err(StreamError::InvalidHash(format!(
+                    "Integrity check failed migrating {}: imported as {}",
+                    hash,
+                    tag.hash()
+                )));
+            }
+
+            // Hold the temp tag until the persistent tags are in place
+            kept.push(tag);
+            copied += 1;
+        }
+
+        // Recreate the tags that keep blobs alive
+        let mut tags = self.store.tags().list()
+            .await
+            .map_err(|e| StreamError::Database(format!("Failed to list tags: {}", e)))?;
+        while let Some(tag) = tags.next().await {
+            let tag = tag.map_err(|e| StreamError::Database(e.to_string()))?;
+            target.tags().set(&tag.name.0, HashAndFormat { hash: tag.hash, format: tag.format })
+                .await
+                .map_err(|e| StreamError::Database(format!("Failed to copy tag: {}", e)))?;
+        }
+        drop(kept);
+
+        target.shutdown()
+            .await
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let _ = fs::remove_dir_all(&staging_dir).await;
+        info!("Migrated {} blobs to {:?}", copied, new_blobs_dir);
+
+        // Release the old store before touching it on disk
+        let old_blobs_dir = old_dir.join("blobs");
+        if let Err(e) = self.shutdown_handle().shutdown().await {
+            warn!("Failed to shut down the old node cleanly: {}", e);
+        }
+
+        // Nothing writes to the logs once the node is down
+        let logs = log_files(label);
+        for file in &logs {
+            let (from, to) = (old_dir.join(file), new_dir.join(file));
+            if from.exists() && !to.exists() {
+                fs::copy(&from, &to).await.map_err(StreamError::from)?;
+            }
+        }
+
+        if remove_old && skipped > 0 {
+            warn!("Keeping old data at {:?}: {} incomplete blob(s) were not migrated", old_dir, skipped);
+        } else if remove_old {
+            fs::remove_dir_all(&old_blobs_dir).await.map_err(StreamError::from)?;
+            for file in logs.iter().map(|file| old_dir.join(file)).chain([old_key]) {
+                if let Err(e) = fs::remove_file(&file).await {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        return Err(StreamError::from(e));
+                    }
+                }
+            }
+            info!("Removed old blob store, key and logs from {:?}", old_dir);
+        }
+
+        Self::launch(new_dir, self.secret_key.clone(), self.config.clone()).await
+    }
+
+    /// Stop the node: cancel transfers, close connections and flush the store
+    ///
+    /// See [`ShutdownHandle::shutdown`].
+    pub async fn shutdown(self) -> StreamResult<()> {
+        self.shutdown_handle().shutdown().await
+    }
+
+    /// Handle that shuts this node down, for owners that only hold it shared
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            endpoint: self.endpoint.clone(),
+            router: self.router.clone(),
+            store: (*self.store).clone(),
+            transfers: self.transfers.clone(),
+        }
+    }
+
+    /// Download the blob referenced by `ticket` and write it to the file `dest`
+    ///
+    /// The content is written to a hidden `.<name>.part` file next to `dest`
+    /// and only renamed into place once its hash has been verified, so a failed
+    /// or cancelled transfer never leaves a partial file at `dest`. To name the
+    /// file after the ticket instead, use [`StreamNode::download`]. Collection
+    /// tickets are refused, see [`StreamNode::fetch_collection`].
+    pub async fn fetch_to_path(
+        &self,
+        ticket: &ShareTicket,
+        dest: PathBuf
+    ) -> StreamResult<PathBuf> {
+        download(&self.endpoint, &self.store, &self.pins, self.download_limit.as_deref(), ticket, dest, NO_PROGRESS).await
+    }
+
+    /// Download the blob referenced by `ticket` into the directory `dest_dir`
+    ///
+    /// Unlike the other download methods, which take the file path, the file
+    /// is named after the ticket's `name`. The name is validated first so a
+    /// hostile ticket can't write outside `dest_dir`. Otherwise behaves like
+    /// [`StreamNode::fetch_to_path`], including hash verification before the
+    /// file appears. Returns the path of the downloaded file.
+    pub async fn download(&self, ticket: &ShareTicket, dest_dir: PathBuf) -> StreamResult<PathBuf> {
+        ticket.validate()?;
+        download(&self.endpoint, &self.store, &self.pins, self.download_limit.as_deref(), ticket, dest_dir.join(&ticket.name), NO_PROGRESS).await
+    }
+
+    /// Like [`StreamNode::download`], reporting progress as chunks arrive
+    ///
+    /// The file is likewise named after the ticket inside `dest_dir`.
+    /// `on_progress` receives the payload bytes received so far and the total
+    /// size if the peer reported it. Updates come from the transfer itself,
+    /// with a final call reporting the full size once the blob is complete.
+    pub async fn download_with_progress(
+        &self,
+        ticket: &ShareTicket,
+        dest_dir: PathBuf,
+        on_progress: impl Fn(u64, Option<u64>)
+    ) -> StreamResult<PathBuf> {
+        ticket.validate()?;
+        download(&self.endpoint, &self.store, &self.pins, self.download_limit.as_deref(), ticket, dest_dir.join(&ticket.name), Some(on_progress)).await
+    }
+
+    /// Start downloading `ticket` to the file `dest` in the background
+    ///
+    /// Behaves like [`StreamNode::fetch_to_path`]. The transfer shows up in
+    /// [`StreamNode::active_transfers`] until it finishes and can be stopped
+    /// with [`StreamNode::cancel_transfer`]. Until it succeeds or is cancelled
+    /// the download is persisted, so it can be picked up again after a restart
+    /// with [`StreamNode::resume_downloads`].
+    pub fn start_fetch(&self, ticket: &ShareTicket, dest: PathBuf) -> TransferHandle {
+        let dest = std::path::absolute(&dest).unwrap_or(dest);
+        let intent = DownloadIntent { ticket: ticket.clone(), dest: dest.clone() };
+        // Collection tickets are refused right away, there is nothing to resume
+        if ticket.hash_seq {
+            debug!("Not persisting download of collection {}", ticket.hash);
+        } else if let Err(e) = self.downloads.record(&intent) {
+            warn!("Failed to persist download to {:?}, it won't resume after a restart: {}", dest, e);
+        }
+
+        let endpoint = self.endpoint.clone();
+        let store = self.store.clone();
+        let downloads = self.downloads.clone();
+        let limit = self.download_limit.clone();
+        let pins = self.pins.clone();
+
+        self.transfers.spawn(ticket.hash.clone(), ticket.name.clone(), dest, async move {
+            let result = download(&endpoint, &store, &pins, limit.as_deref(), &intent.ticket, intent.dest.clone(), NO_PROGRESS).await;
+            if result.is_ok() {
+                downloads.forget(&intent.dest);
+            }
+            result
+        })
+    }
+
+    /// Downloads that were started but haven't completed, including those
+    /// interrupted by a restart
+    pub fn pending_downloads(&self) -> StreamResult<Vec<DownloadIntent>> {
+        self.downloads.list()
+    }
+
+    /// Restart every pending download that isn't already running
+    ///
+    /// Chunks fetched before the interruption are still in the blob store,
+    /// so only the missing ranges are transferred again.
+    pub fn resume_downloads(&self) -> StreamResult<Vec<TransferHandle>> {
+        let running: Vec<PathBuf> = self.transfers.list().into_iter().map(|t| t.dest).collect();
+
+        let handles: Vec<TransferHandle> = self.downloads.list()?
+            .into_iter()
+            .filter(|intent| !running.contains(&intent.dest))
+            .map(|intent| {
+                info!("Resuming download of {} to {:?}", intent.ticket.hash, intent.dest);
+                self.start_fetch(&intent.ticket, intent.dest)
+            })
+            .collect();
+
+        Ok(handles)
+    }
+
+    /// Transfers started with [`StreamNode::start_fetch`] that haven't finished yet
+    pub fn active_transfers(&self) -> Vec<TransferInfo> {
+        self.transfers.list()
+    }
+
+    /// Abort one in-flight transfer and remove its partial download
+    ///
+    /// Other transfers are unaffected and the download is not resumed after a
+    /// restart. Returns false if no transfer with `id` is running (it
+    /// finished, failed or was already cancelled).
+    pub fn cancel_transfer(&self, id: TransferId) -> bool {
+        let dest = self.transfers.list().into_iter().find(|t| t.id == id).map(|t| t.dest);
+        let cancelled = self.transfers.cancel(id);
+        if cancelled {
+            info!("Cancelled transfer {}", id);
+            if let Some(dest) = dest {
+                self.downloads.forget(&dest);
+            }
+        }
+        cancelled
+    }
+
+    /// Download the blob referenced by `ticket` into memory
+    ///
+    /// Meant for small artifacts (manifests, thumbnails, subtitles). The
+    /// verified size is requested first and the download is refused if it
+    /// exceeds `max_size`, so a ticket to a huge file can't exhaust memory.
+    pub async fn fetch_bytes(&self, ticket: &ShareTicket, max_size: u64) -> StreamResult<Bytes> {
+        let (hash, conn) = self.connect_ticket(ticket).await?;
+        // Held until the bytes are read back; the blob stays untagged
+        let _pin = self.pins.pin(hash);
+
+        let (size, _) = iroh_blobs::get::request::get_verified_size(&conn, &hash)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to query blob size: {}", e)))?;
+        if size > max_size {
+            return Err(StreamError::Iroh(format!(
+                "Blob {} is {} bytes, exceeding the {} byte limit",
+                ticket.hash, size, max_size
+            )));
+        }
+
+        fetch_blob(&self.store, conn, hash, self.download_limit.as_deref(), NO_PROGRESS.as_ref()).await?;
+
+        self.store.get_bytes(hash)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to read blob: {}", e)))
+    }
+
+    /// Fetch a collection ticket's root and all of its members into the store
+    ///
+    /// For tickets with `hash_seq` set, such as those from
+    /// [`StreamNode::generate_collection_ticket`]; other tickets are refused.
+    /// Members already stored aren't requested again. Returns the member
+    /// hashes in collection order.
+    pub async fn fetch_collection(&self, ticket: &ShareTicket) -> StreamResult<Vec<MediaHash>> {
+        ticket.validate()?;
+        if !ticket.hash_seq {
+            return Err(StreamError::InvalidHash(format!("Ticket for {} is not a collection", ticket.hash)));
+        }
+        let (hash, conn) = self.connect_ticket(ticket).await?;
+        let _pin = self.pins.pin(hash);
+
+        let content = HashAndFormat::hash_seq(hash);
+        paced(self.store.remote().fetch(conn, content), self.download_limit.as_deref())
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to fetch collection: {}", e)))?;
+        // Tagged as a hash sequence, so GC keeps the members too
+        self.store.tags().set(format!("{}{}", DOWNLOAD_TAG_PREFIX, hash), content)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to tag download: {}", e)))?;
+
+        info!("Fetched collection {}", hash);
+        self.read_collection(&ticket.hash).await
+    }
+
+    /// Check whether a ticket's content can be fetched right now
+    ///
+    /// Connects to the peer and asks for the blob's verified size, which
+    /// transfers only the last chunk and its proof, never the body. The
+    /// connection is closed again afterwards.
+    pub async fn probe_ticket(&self, ticket: &ShareTicket) -> StreamResult<BlobAvailability> {
+        let hash = parse_hash(&ticket.hash)?;
+        let addr = ticket_addr(ticket)?;
+
+        let conn = match tokio::time::timeout(PEER_CONNECT_TIMEOUT, self.endpoint.connect(addr, ALPN)).await {
+            Ok(Ok(conn)) => conn,
+            Ok(Err(e)) => {
+                debug!("Probe could not connect to {}: {}", ticket.node_id, e);
+                return Ok(BlobAvailability::Unreachable);
+            }
+            Err(_) => {
+                debug!("Probe timed out connecting to {}", ticket.node_id);
+                return Ok(BlobAvailability::Unreachable);
+            }
+        };
+
+        let availability = match iroh_blobs::get::request::get_verified_size(&conn, &hash).await {
+            Ok((size, _)) => BlobAvailability::Available { size },
+            Err(e) => {
+                debug!("Peer {} doesn't serve {}: {}", ticket.node_id, hash, e);
+                BlobAvailability::Missing
+            }
+        };
+        conn.close(0u32.into(), b"probe complete");
+
+        Ok(availability)
+    }
+
+    /// Download one blob from several peers at once, returning its size
+    ///
+    /// The blob is split into contiguous chunk ranges, one per peer, that are
+    /// requested in parallel. Every chunk is verified against the BLAKE3 tree
+    /// as it arrives, so peers can't corrupt each other's parts. Whatever is
+    /// still missing afterwards (a peer dropped or was unreachable) is fetched
+    /// from the remaining peers in turn. The content ends up in the local
+    /// store like any other fetched blob.
+    pub async fn fetch_multi(&self, hash: &MediaHash, peers: Vec<EndpointAddr>) -> StreamResult<u64> {
+        let hash = parse_hash(hash)?;
+        if peers.is_empty() {
+            return Err(StreamError::NotConnected);
+        }
+        let _pin = self.pins.pin(hash);
+
+        // Connect to everyone up front, dropping peers we can't reach
+        let attempts = peers.into_iter().map(|addr| async move {
+            let id = addr.id;
+            let conn = tokio::time::timeout(PEER_CONNECT_TIMEOUT, self.endpoint.connect(addr, ALPN)).await;
+            match conn {
+                Ok(Ok(conn)) => Some((id, conn)),
+                Ok(Err(e)) => {
+                    warn!("Skipping peer {}: {}", id, e);
+                    None
+                }
+                Err(_) => {
+                    warn!("Skipping peer {}: connection timed out", id);
+                    None
+                }
+            }
+        });
+        let mut conns: Vec<(EndpointId, Connection)> = futures::future::join_all(attempts)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        if conns.is_empty() {
+            return Err(StreamError::NotConnected);
+        }
+
+        // Take the verified size from the first peer able to prove it. Peers
+        // that can't are left out of the split, they don't have the blob.
+        let mut size = None;
+        let mut last_err = None;
+        while size.is_none() && !conns.is_empty() {
+            let (id, conn) = &conns[0];
+            match iroh_blobs::get::request::get_verified_size(conn, &hash).await {
+                Ok((verified, _)) => size = Some(verified),
+                Err(e) => {
+                    warn!("Peer {} could not prove the size of {}: {}", id, hash, e);
+                    last_err = Some(e.to_string());
+                    conns.remove(0);
+                }
+            }
+        }
+        let Some(size) = size else {
+            let reason = last_err.unwrap_or_default();
+            return Err(StreamError::Iroh(format!("Failed to query blob size: {}", reason)));
+        };
+
+        // One contiguous slice of chunks per peer, the last one open-ended
+        let total_chunks = size.div_ceil(CHUNK_SIZE).max(1);
+        let per_peer = total_chunks.div_ceil(conns.len() as u64);
+        let parts = conns.iter().enumerate().map(|(i, (id, conn))| {
+            let start = i as u64 * per_peer;
+            let ranges = if i + 1 == conns.len() {
+                ChunkRanges::chunks(start..)
+            } else {
+                ChunkRanges::chunks(start..start + per_peer)
+            };
+            let request = GetRequest::builder().root(ranges).build(hash);
+            async move {
+                if let Err(e) = execute_get(&self.store, conn.clone(), request, self.download_limit.as_deref()).await {
+                    warn!("Peer {} failed to deliver its part of {}: {}", id, hash, e);
+                }
+            }
+        });
+        futures::future::join_all(parts).await;
+
+        // Fill any gaps left by failed peers
+        for (id, conn) in &conns {
+            if self.has_complete(hash).await? {
+                break;
+            }
+            debug!("Fetching remaining parts of {} from {}", hash, id);
+            if let Err(e) = fetch_blob(&self.store, conn.clone(), hash, self.download_limit.as_deref(), NO_PROGRESS.as_ref()).await {
+                warn!("Peer {} failed to fill gaps in {}: {}", id, hash, e);
+            }
+        }
+
+        if !self.has_complete(hash).await? {
+            return Err(StreamError::Iroh(format!("No peer could provide all of {}", hash)));
+        }
+        tag_download(&self.store, hash).await?;
+
+        info!("Fetched {} ({} bytes) from {} peers", hash, size, conns.len());
+        Ok(size)
+    }
+
+    /// Whether the store holds the complete, verified blob
+    async fn has_complete(&self, hash: Hash) -> StreamResult<bool> {
+        self.store.has(hash)
+            .await
+            .map_err(|e| StreamError::Database(e.to_string()))
+    }
+
+    /// Parse a ticket and open a blobs connection to the node it points at
+    async fn connect_ticket(&self, ticket: &ShareTicket) -> StreamResult<(Hash, Connection)> {
+        connect_ticket(&self.endpoint, ticket).await
+    }
+}
+
+/// Peer and download log file names for an identity, in that order
+fn log_files(label: Option<&str>) -> [String; 2] {
+    match label {
+        Some(label) => [format!("peers-{}.db", label), format!("downloads-{}.db", label)],
+        None => ["peers.db".to_string(), "downloads.db".to_string()],
+    }
+}
+
+/// Parse a ticket and open a blobs connection to the node it points at
+async fn connect_ticket(endpoint: &Endpoint, ticket: &ShareTicket) -> StreamResult<(Hash, Connection)> {
+    if ticket.is_expired(unix_now()) {
+        return Err(StreamError::TicketExpired(ticket.expires_at.unwrap_or_default()));
+    }
+    let hash = parse_hash(&ticket.hash)?;
+    let addr = ticket_addr(ticket)?;
+
+    let conn = endpoint.connect(addr, ALPN)
+        .await
+        .map_err(|e| StreamError::Iroh(format!("Failed to connect to {}: {}", ticket.node_id, e)))?;
+
+    Ok((hash, conn))
+}
+
+/// Fetch a blob into `store` and export it atomically to `dest`
+///
+/// Owns no node state, so background transfers can run it on a spawned task.
+/// Dropping the future removes the partial file.
+async fn download<F>(
+    endpoint: &Endpoint,
+    store: &BlobStore,
+    pins: &Pins,
+    limit: Option<&RateLimiter>,
+    ticket: &ShareTicket,
+    dest: PathBuf,
+    on_progress: Option<F>
+) -> StreamResult<PathBuf>
+where
+    F: Fn(u64, Option<u64>),
+{
+    // Export requires an absolute target path
+    if ticket.hash_seq {
+        return Err(StreamError::InvalidHash(format!(
+            "Ticket for {} is a collection, fetch it with fetch_collection",
+            ticket.hash
+        )));
+    }
+    let dest = std::path::absolute(&dest).map_err(StreamError::from)?;
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).await.map_err(StreamError::from)?;
+    }
+    let partial = PartialFile::new(part_path(&dest));
+
+    // Fetch the blob into the local store (verified chunk by chunk). Chunks
+    // kept from an interrupted attempt are reused, only the rest is requested.
+    let (hash, conn) = connect_ticket(endpoint, ticket).await?;
+    let _pin = pins.pin(hash);
+    if let Ok(StoreStatus::Partial { .. }) = store.blobs().status(hash).await {
+        info!("Resuming download of {} from the partially stored blob", hash);
+    }
+    fetch_blob(store, conn.clone(), hash, limit, on_progress.as_ref()).await?;
+
+    // Write it out next to the destination and verify the bytes on disk
+    // before they become visible at `dest`
+    match export_verified(store, hash, &partial.path).await {
+        Ok(()) => {}
+        Err(StreamError::InvalidHash(reason)) => {
+            // Chunks are verified as they arrive, so the stored copy went bad
+            // afterwards: drop it and fetch everything again
+            warn!("Stored data for {} is corrupt ({}), fetching it again", hash, reason);
+            store.blobs().delete([hash])
+                .await
+                .map_err(|e| StreamError::Database(format!("Failed to delete corrupt blob: {}", e)))?;
+            fetch_blob(store, conn, hash, limit, on_progress.as_ref()).await?;
+            export_verified(store, hash, &partial.path).await?;
+        }
+        Err(e) => return Err(e),
+    }
+
+    tag_download(store, hash).await?;
+    fs::rename(&partial.path, &dest).await.map_err(StreamError::from)?;
+    partial.keep();
+
+    info!("Downloaded {} to {:?}", ticket.hash, dest);
+    Ok(dest)
+}
+
+/// Fetch whatever of `hash` the store is missing from the peer on `conn`
+///
+/// With a `limit` the transfer is paced by holding back progress events;
+/// the fetch waits on them, so reading from the peer slows down too.
+async fn fetch_blob<F>(
+    store: &BlobStore,
+    conn: Connection,
+    hash: Hash,
+    limit: Option<&RateLimiter>,
+    on_progress: Option<&F>
+) -> StreamResult<()>
+where
+    F: Fn(u64, Option<u64>),
+{
+    if limit.is_none() && on_progress.is_none() {
+        store.remote().fetch(conn, hash)
+            .await
+            .map_err(|e| StreamError::Iroh(format!("Failed to fetch blob: {}", e)))?;
+        return Ok(());
+    }
+    fetch_with_progress(store, conn, hash, limit, on_progress).await
+}
+
+/// Run a range request into `store`, paced to `limit` like [`fetch_blob`]
+async fn execute_get(
+    store: &BlobStore,
+    conn: Connection,
+    request: GetRequest,
+    limit: Option<&RateLimiter>
+) -> StreamResult<()> {
+    paced(store.remote().execute_get(conn, request), limit)
+        .await
+        .map_err(|e| StreamError::Iroh(format!("Failed to fetch ranges: {}", e)))
+}
+
+/// Drive a transfer to completion, holding back progress events to pace it to `limit`
+async fn paced(progress: GetProgress, limit: Option<&RateLimiter>) -> Result<(), String> {
+    let Some(limit) = limit else {
+        return progress.await.map(|_| ()).map_err(|e| e.to_string());
+    };
+
+    let mut received = 0;
+    let mut progress = progress.stream();
+    while let Some(item) = progress.next().await {
+        match item {
+            GetProgressItem::Progress(bytes) => {
+                limit.acquire(bytes.saturating_sub(received)).await;
+                received = bytes;
+            }
+            GetProgressItem::Done(_) => return Ok(()),
+            GetProgressItem::Error(e) => return Err(e.to_string()),
+        }
+    }
+
+    Err("transfer ended without completing".to_string())
+}
+
+/// Tag a fetched blob so GC keeps it
+async fn tag_download(store: &BlobStore, hash: Hash) -> StreamResult<()> {
+    store.tags().set(format!("{}{}", DOWNLOAD_TAG_PREFIX, hash), HashAndFormat::raw(hash))
+        .await
+        .map_err(|e| StreamError::Iroh(format!("Failed to tag download: {}", e)))?;
+    Ok(())
+}
+
+/// Export a stored blob to `path` and check the written file hashes to `hash`
+async fn export_verified(store: &BlobStore, hash: Hash, path: &Path) -> StreamResult<()> {
+    store.blobs().export(hash, path.to_path_buf())
+        .await
+        .map_err(|e| StreamError::Iroh(format!("Failed to export blob: {}", e)))?;
+    verify_file_hash(path, hash).await
+}
+
+/// Placeholder for downloads nobody watches the progress of
+const NO_PROGRESS: Option<fn(u64, Option<u64>)> = None;
+
+/// Fetch a blob into `store`, forwarding the transfer's progress events
+/// and pacing it to `limit`
+async fn fetch_with_progress<F>(
+    store: &BlobStore,
+    conn: Connection,
+    hash: Hash,
+    limit: Option<&RateLimiter>,
+    on_progress: Option<&F>
+) -> StreamResult<()>
+where
+    F: Fn(u64, Option<u64>),
+{
+    // The size is only a hint for the progress bar, the fetch verifies it anyway
+    let total = match on_progress {
+        Some(_) => match iroh_blobs::get::request::get_verified_size(&conn, &hash).await {
+            Ok((size, _)) => Some(size),
+            Err(e) => {
+                debug!("Size of {} unknown, reporting progress without a total: {}", hash, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut received = 0;
+    let mut progress = store.remote().fetch(conn, hash).stream();
+    while let Some(item) = progress.next().await {
+        match item {
+            GetProgressItem::Progress(bytes) => {
+                if let Some(limit) = limit {
+                    limit.acquire(bytes.saturating_sub(received)).await;
+                }
+                received = bytes;
+                if let Some(on_progress) = on_progress {
+                    on_progress(bytes, total);
+                }
+            }
+            GetProgressItem::Done(_) => {
+                // The blob is complete now, whatever was already local included
+                if let Some(on_progress) = on_progress {
+                    on_progress(total.unwrap_or(received), total);
+                }
+                return Ok(());
+            }
+            GetProgressItem::Error(e) => {
+                return Err(StreamError::Iroh(format!("Failed to fetch blob: {}", e)));
+            }
+        }
+    }
+
+    Err(StreamError::Iroh(format!("Fetch of {} ended without completing", hash)))
+}
+
+/// Blobs in use by transfers, with a count per hash
+#[derive(Debug, Clone, Default)]
+struct Pins(Arc<std::sync::Mutex<HashMap<Hash, usize>>>);
+
+impl Pins {
+    /// Keep `hash` out of GC until the guard is dropped
+    fn pin(&self, hash: Hash) -> PinGuard {
+        *self.0.lock().expect("pins lock poisoned").entry(hash).or_default() += 1;
+        PinGuard { pins: self.clone(), hash }
+    }
+
+    fn contains(&self, hash: Hash) -> bool {
+        self.0.lock().expect("pins lock poisoned").contains_key(&hash)
+    }
+}
+
+struct PinGuard {
+    pins: Pins,
+    hash: Hash,
+}
+
+impl Drop for PinGuard {
+    fn drop(&mut self) {
+        let mut pins = self.pins.0.lock().expect("pins lock poisoned");
+        if let Some(count) = pins.get_mut(&self.hash) {
+            *count -= 1;
+            if *count == 0 {
+                pins.remove(&self.hash);
+            }
+        }
+    }
+}
+
+/// A temporary download file that is removed on drop unless kept
+struct PartialFile {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl PartialFile {
+    fn new(path: PathBuf) -> Self {
+        Self { path, keep: false }
+    }
+
+    fn keep(mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.keep && self.path.exists() {
+            debug!("Removing partial download {:?}", self.path);
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Shuts a [`StreamNode`] down through its shared endpoint, router and store
+///
+/// Obtained from [`StreamNode::shutdown_handle`].
+pub struct ShutdownHandle {
+    endpoint: Endpoint,
+    router: Router,
+    store: iroh_blobs::api::Store,
+    transfers: Arc<TransferRegistry>,
+}
+
+impl ShutdownHandle {
+    /// Stop the node: cancel transfers, close connections and flush the store
+    ///
+    /// Running downloads are cancelled but stay recorded, so
+    /// [`StreamNode::resume_downloads`] picks them up on the next start.
+    /// In-flight connections get a few seconds to drain before the endpoint
+    /// is closed regardless.
+    pub async fn shutdown(self) -> StreamResult<()> {
+        for transfer in self.transfers.list() {
+            self.transfers.cancel(transfer.id);
+            debug!("Cancelled transfer {} for shutdown", transfer.id);
+        }
+
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, self.router.shutdown()).await.is_err() {
+            warn!("Connections did not drain within {:?}, closing anyway", SHUTDOWN_TIMEOUT);
+        }
+        self.endpoint.close().await;
+
+        self.store.shutdown()
+            .await
+            .map_err(|e| StreamError::Database(format!("Blob store shutdown failed: {}", e)))?;
+
+        info!("Node {} shut down", self.endpoint.id());
+        Ok(())
+    }
+}
+
+/// Why a file couldn't be referenced in place
+enum ReferenceError {
+    /// The filesystem can't back a reference, so copying is the way in
+    Unsupported(StreamError),
+    /// Anything else, which copying would run into as well
+    Failed(StreamError),
+}
+
+/// Whether a reference import failed because the file's filesystem can't hold one
+///
+/// Walks the error's sources for the I/O errors seen on network mounts and
+/// when the file is on another device than the store.
+fn reference_unsupported(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                std::io::ErrorKind::Unsupported
+                    | std::io::ErrorKind::CrossesDevices
+                    | std::io::ErrorKind::StaleNetworkFileHandle
+            ) {
+                return true;
+            }
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Hidden sibling of `dest` used while a download is in flight
+/// (dotfiles are skipped by the watcher, so it never gets indexed)
+fn part_path(dest: &Path) -> PathBuf {
+    let name = dest.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "download".to_string());
+    dest.with_file_name(format!(".{}.part", name))
+}
+
+/// Replace the builder's default discovery with the configured mechanisms
+fn with_discovery(builder: iroh::endpoint::Builder, mode: DiscoveryMode) -> iroh::endpoint::Builder {
+    let builder = builder.clear_discovery();
+    let dns = |b: iroh::endpoint::Builder| {
+        b.discovery(PkarrPublisher::n0_dns())
+            .discovery(DnsDiscovery::n0_dns())
+    };
+
+    match mode {
+        DiscoveryMode::Dns => dns(builder),
+        DiscoveryMode::LocalNetwork => builder.discovery(MdnsDiscovery::builder()),
+        DiscoveryMode::DnsAndLocalNetwork => dns(builder).discovery(MdnsDiscovery::builder()),
+        DiscoveryMode::Disabled => builder,
+    }
+}
+
+/// Map the configured relay mode onto iroh's
+fn iroh_relay_mode(mode: &RelayMode) -> iroh::RelayMode {
+    match mode {
+        RelayMode::Default => iroh::RelayMode::Default,
+        RelayMode::Custom(url) => iroh::RelayMode::custom([url.clone()]),
+        RelayMode::Disabled => iroh::RelayMode::Disabled,
+    }
+}
+
+/// Split a raw collection (concatenated 32-byte hashes) into its members
+fn raw_collection_members(bytes: &[u8]) -> Result<Vec<Hash>, String> {
+    if bytes.len() % 32 != 0 {
+        return Err(format!("{} bytes is not a multiple of 32", bytes.len()));
+    }
+
+    Ok(bytes.chunks_exact(32)
+        .map(|chunk| {
+            let raw: [u8; 32] = chunk.try_into().expect("chunks_exact yields 32 bytes");
+            Hash::from_bytes(raw)
+        })
+        .collect())
+}
+
+/// Members of either collection format: a named manifest or raw hashes
+fn collection_members(bytes: &[u8]) -> StreamResult<Vec<Hash>> {
+    match Manifest::decode(bytes) {
+        Ok(manifest) => manifest.entries.iter().map(|e| parse_hash(&e.hash)).collect(),
+        Err(_) => raw_collection_members(bytes).map_err(StreamError::InvalidHash),
+    }
+}
+
+/// Convert a MediaHash into an iroh Hash
+fn parse_hash(hash: &MediaHash) -> StreamResult<Hash> {
+    Hash::from_str(&hash.0).map_err(|e| StreamError::InvalidHash(e.to_string()))
+}
+
+/// Current unix time in seconds
+pub(crate) fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Build the dialing address for the node referenced by a ticket
+pub(crate) fn ticket_addr(ticket: &ShareTicket) -> StreamResult<EndpointAddr> {
+    let node_id = EndpointId::from_str(&ticket.node_id)
+        .map_err(|e| StreamError::InvalidHash(format!("Invalid node id: {}", e)))?;
+
+    let mut addr = EndpointAddr::new(node_id);
+    if let Ok(relay) = RelayUrl::from_str(&ticket.relay_url) {
+        addr = addr.with_relay_url(relay);
+    }
+    for direct in &ticket.direct_addrs {
+        match SocketAddr::from_str(direct) {
+            Ok(direct) => addr = addr.with_ip_addr(direct),
+            Err(e) => debug!("Ignoring malformed direct address {:?} in ticket: {}", direct, e),
+        }
+    }
+    Ok(addr)
+}
+
+/// Compute the BLAKE3 content hash of a file on disk
+pub(crate) async fn hash_path(path: &Path) -> StreamResult<Hash> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> std::io::Result<Hash> {
+        let mut file = std::fs::File::open(&path)?;
+        let mut hasher = blake3::Hasher::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(Hash::from_bytes(*hasher.finalize().as_bytes()))
+    })
+    .await
+    .map_err(|e| StreamError::from(std::io::Error::other(e)))?
+    .map_err(StreamError::from)
+}
+
+/// Re-hash a file on disk and compare against the expected content hash
+async fn verify_file_hash(path: &Path, expected: Hash) -> StreamResult<()> {
+    let actual = hash_path(path).await?;
+
+    if actual != expected {
+        return Err(StreamError::InvalidHash(format!(
+            "Downloaded content hash mismatch: expected {}, got {}",
+            expected, actual
+        )));
+    }
+    Ok(())
+}
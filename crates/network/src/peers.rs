@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use ghostdrive_core::{StreamError, StreamResult};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Table: Node ID (String) -> Serialized PeerRecord (Bytes)
+const PEERS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("peers");
+
+/// History of a remote node that connected to us
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    /// Node ID of the remote peer
+    pub node_id: String,
+    /// Unix timestamp of the first connection
+    pub first_seen: u64,
+    /// Unix timestamp of the most recent connection
+    pub last_seen: u64,
+    /// Total payload bytes served to this peer
+    pub bytes_served: u64,
+}
+
+/// Persistent log of peers that have connected to this node
+pub(crate) struct PeerLog {
+    db: Database,
+}
+
+impl PeerLog {
+    /// Open or create the peer log at the specified path
+    pub(crate) fn open(path: PathBuf) -> StreamResult<Self> {
+        let db = Database::create(&path).map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let txn = db.begin_write().map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let _ = txn.open_table(PEERS_TABLE).map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        Ok(Self { db })
+    }
+
+    /// Record a connection from `node_id` at time `now`
+    pub(crate) fn record_connect(&self, node_id: &str, now: u64) -> StreamResult<()> {
+        self.update(node_id, now, |record| record.last_seen = now)
+    }
+
+    /// Add `bytes` to the total served to `node_id`
+    pub(crate) fn record_bytes(&self, node_id: &str, bytes: u64, now: u64) -> StreamResult<()> {
+        self.update(node_id, now, |record| record.bytes_served += bytes)
+    }
+
+    /// Read-modify-write a single record, creating it if missing
+    fn update(&self, node_id: &str, now: u64, apply: impl FnOnce(&mut PeerRecord)) -> StreamResult<()> {
+        let config = bincode::config::standard();
+
+        let txn = self.db.begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let mut table = txn.open_table(PEERS_TABLE)
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+
+            let existing = match table.get(node_id).map_err(|e| StreamError::Database(e.to_string()))? {
+                Some(access) => {
+                    let (record, _): (PeerRecord, usize) = bincode::serde::decode_from_slice(access.value(), config)
+                        .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+                    Some(record)
+                }
+                None => None,
+            };
+
+            let mut record = existing.unwrap_or_else(|| PeerRecord {
+                node_id: node_id.to_string(),
+                first_seen: now,
+                last_seen: now,
+                bytes_served: 0,
+            });
+            apply(&mut record);
+
+            let encoded = bincode::serde::encode_to_vec(&record, config)
+                .map_err(|e| StreamError::Database(format!("Serialization error: {}", e)))?;
+            table.insert(node_id, encoded.as_slice())
+                .map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+
+        debug!("Updated peer record: {}", node_id);
+        Ok(())
+    }
+
+    /// List every known peer
+    pub(crate) fn list(&self) -> StreamResult<Vec<PeerRecord>> {
+        let txn = self.db.begin_read()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        let table = txn.open_table(PEERS_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+
+        let config = bincode::config::standard();
+        let mut results = Vec::new();
+        for entry in table.iter().map_err(|e| StreamError::Database(e.to_string()))? {
+            let (_, value) = entry.map_err(|e| StreamError::Database(e.to_string()))?;
+            let (record, _): (PeerRecord, usize) = bincode::serde::decode_from_slice(value.value(), config)
+                .map_err(|e| StreamError::Database(format!("Deserialization error: {}", e)))?;
+            results.push(record);
+        }
+
+        Ok(results)
+    }
+
+    /// Forget all peer history
+    pub(crate) fn clear(&self) -> StreamResult<()> {
+        let txn = self.db.begin_write()
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        txn.delete_table(PEERS_TABLE)
+            .map_err(|e| StreamError::Database(e.to_string()))?;
+        {
+            let _ = txn.open_table(PEERS_TABLE).map_err(|e| StreamError::Database(e.to_string()))?;
+        }
+        txn.commit().map_err(|e| StreamError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ghostdrive_core::{StreamError, StreamResult};
+use iroh_blobs::{
+    api::blobs::{AddPathOptions, ImportMode},
+    store::fs::FsStore as BlobStore,
+    BlobFormat, Hash,
+};
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::node::hash_path;
+
+/// On-disk layout found in a blob store directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StoreLayout {
+    /// Nothing there yet
+    Empty,
+    /// Layout of the current iroh-blobs `FsStore` (`blobs.db` + `data/`)
+    Current,
+    /// Flat store of older iroh releases (`complete/`, `partial/`, `meta/`)
+    LegacyFlat,
+    /// Something we don't recognize
+    Unknown,
+}
+
+/// Inspect `dir` to find out which store format wrote it
+pub(crate) async fn detect_layout(dir: &Path) -> StreamResult<StoreLayout> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(StoreLayout::Empty),
+        Err(e) => return Err(StreamError::from(e)),
+    };
+
+    let mut names = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(StreamError::from)? {
+        names.push(entry.file_name().to_string_lossy().to_string());
+    }
+    let has = |name: &str| names.iter().any(|n| n == name);
+
+    Ok(if names.is_empty() {
+        StoreLayout::Empty
+    } else if has("blobs.db") {
+        StoreLayout::Current
+    } else if has("complete") || has("partial") || has("meta") {
+        StoreLayout::LegacyFlat
+    } else {
+        StoreLayout::Unknown
+    })
+}
+
+/// Open the blob store in `dir`, upgrading a legacy layout first
+///
+/// A legacy flat store is moved aside to `<dir>.legacy` and every complete
+/// blob in it is re-imported (and re-hashed) into a fresh store. The backup
+/// is left in place for the user to delete. An upgrade interrupted by a crash
+/// is resumed on the next start. If the store still can't be loaded, the
+/// error explains how to recover instead of surfacing the raw database failure.
+pub(crate) async fn load_store(dir: &Path) -> StreamResult<BlobStore> {
+    let layout = detect_layout(dir).await?;
+
+    if layout == StoreLayout::LegacyFlat {
+        return migrate_legacy_flat(dir).await;
+    }
+
+    let backup = legacy_backup_path(dir);
+    if upgrade_marker_path(dir).exists() && backup.exists() {
+        warn!("Resuming interrupted upgrade of legacy blob store at {:?}", dir);
+        return import_legacy_blobs(dir, &backup).await;
+    }
+
+    match BlobStore::load(dir).await {
+        Ok(store) => Ok(store),
+        Err(e) => Err(StreamError::Database(recovery_message(dir, layout, &e.to_string()))),
+    }
+}
+
+/// Move an old flat store aside and re-import its complete blobs into a new store
+async fn migrate_legacy_flat(dir: &Path) -> StreamResult<BlobStore> {
+    let backup = legacy_backup_path(dir);
+    if backup.exists() {
+        return Err(StreamError::Database(format!(
+            "Cannot upgrade legacy blob store at {:?}: backup location {:?} already exists. \
+             Move or delete it and restart.",
+            dir, backup
+        )));
+    }
+
+    warn!("Blob store at {:?} uses a legacy layout, upgrading (backup at {:?})", dir, backup);
+    fs::write(upgrade_marker_path(dir), b"").await.map_err(StreamError::from)?;
+    fs::rename(dir, &backup).await.map_err(StreamError::from)?;
+
+    import_legacy_blobs(dir, &backup).await
+}
+
+/// Re-import the complete blobs of the backed up flat store into a store at `dir`
+///
+/// Blobs already imported by an interrupted run are simply imported again.
+/// The upgrade marker is removed once every blob has been tried.
+async fn import_legacy_blobs(dir: &Path, backup: &Path) -> StreamResult<BlobStore> {
+    fs::create_dir_all(dir).await.map_err(StreamError::from)?;
+
+    let store = BlobStore::load(dir)
+        .await
+        .map_err(|e| StreamError::Database(recovery_message(dir, StoreLayout::Empty, &e.to_string())))?;
+
+    let mut imported = 0usize;
+    let mut skipped = 0usize;
+
+    for (expected, path) in legacy_blobs(&backup.join("complete")).await? {
+        // Check before importing, so a mismatched blob never enters the store
+        match hash_path(&path).await {
+            Ok(actual) if actual == expected => {}
+            Ok(actual) => {
+                warn!("Legacy blob {:?} hashed to {}, expected {}; skipping", path, actual, expected);
+                skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                warn!("Failed to read legacy blob {:?}: {}", path, e);
+                skipped += 1;
+                continue;
+            }
+        }
+
+        let options = AddPathOptions {
+            path: path.clone(),
+            mode: ImportMode::Copy,
+            format: BlobFormat::Raw,
+        };
+
+        match store.add_path_with_opts(options).await {
+            Ok(_) => imported += 1,
+            Err(e) => {
+                warn!("Failed to re-import legacy blob {:?}: {}", path, e);
+                skipped += 1;
+            }
+        }
+    }
+
+    fs::remove_file(upgrade_marker_path(dir)).await.map_err(StreamError::from)?;
+    info!(
+        "Upgraded legacy blob store: {} blobs imported, {} skipped. Old store kept at {:?}",
+        imported, skipped, backup
+    );
+    Ok(store)
+}
+
+/// Complete blobs of a flat store: `<hex hash>.data` files
+async fn legacy_blobs(complete_dir: &Path) -> StreamResult<Vec<(Hash, PathBuf)>> {
+    let mut blobs = Vec::new();
+
+    let mut entries = match fs::read_dir(complete_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(blobs),
+        Err(e) => return Err(StreamError::from(e)),
+    };
+
+    while let Some(entry) = entries.next_entry().await.map_err(StreamError::from)? {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "data") {
+            continue;
+        }
+        let parsed = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Hash::from_str(stem).ok());
+        if let Some(hash) = parsed {
+            blobs.push((hash, path));
+        }
+    }
+
+    Ok(blobs)
+}
+
+fn legacy_backup_path(dir: &Path) -> PathBuf {
+    let mut name = dir.as_os_str().to_owned();
+    name.push(".legacy");
+    PathBuf::from(name)
+}
+
+/// Sibling of `dir` that exists while a legacy upgrade is under way
+fn upgrade_marker_path(dir: &Path) -> PathBuf {
+    let mut name = dir.as_os_str().to_owned();
+    name.push(".upgrading");
+    PathBuf::from(name)
+}
+
+/// Actionable explanation for a store that failed to load
+fn recovery_message(dir: &Path, layout: StoreLayout, cause: &str) -> String {
+    format!(
+        "Failed to load blob store at {:?} ({:?} layout): {}. \
+         It may have been written by an incompatible iroh-blobs version. \
+         To recover: stop GhostDrive, move {:?} aside, and restart. Files in \
+         watch paths are re-imported by the next scan; content that was only \
+         stored as a copy has to be re-added from its original source.",
+        dir, layout, cause, dir
+    )
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Token bucket shared by every transfer in one direction
+///
+/// Holds up to a second's worth of bytes, so short bursts go out at full
+/// speed while the long-run rate stays at `bytes_per_sec`. Callers that
+/// overdraw the bucket wait off their debt in order, which keeps the cap
+/// an aggregate over all connections rather than per connection.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bytes_per_sec: u64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Bytes that may be sent right away, negative when in debt
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Limiter for `bytes_per_sec`, `None` if the rate is zero (no limit)
+    pub(crate) fn new(bytes_per_sec: u64) -> Option<Self> {
+        (bytes_per_sec > 0).then(|| Self {
+            bytes_per_sec,
+            bucket: Mutex::new(Bucket { tokens: bytes_per_sec as f64, refilled_at: Instant::now() }),
+        })
+    }
+
+    /// Wait until `bytes` may be transferred
+    pub(crate) async fn acquire(&self, bytes: u64) {
+        let rate = self.bytes_per_sec as f64;
+        let wait = {
+            let mut bucket = self.bucket.lock().await;
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * rate;
+            bucket.tokens = (bucket.tokens + refill).min(rate) - bytes as f64;
+            bucket.refilled_at = now;
+            bucket.tokens
+        };
+
+        if wait < 0.0 {
+            tokio::time::sleep(Duration::from_secs_f64(-wait / rate)).await;
+        }
+    }
+}
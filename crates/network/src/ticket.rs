@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use ghostdrive_core::{MediaHash, ShareTicket, StreamError, StreamResult};
+use iroh_blobs::BlobFormat;
+use iroh_blobs::ticket::BlobTicket;
+
+use crate::node::{ticket_addr, unix_now};
+
+/// Conversion between [`ShareTicket`] and iroh-blobs' standard `BlobTicket`
+///
+/// The blob format carries over in both directions: a `HashSeq` ticket
+/// becomes a ticket with [`ShareTicket::hash_seq`] set, and back.
+pub trait IrohTicketExt: Sized {
+    /// Encode as an iroh `blob...` ticket string
+    fn to_iroh_blob_ticket(&self) -> StreamResult<String>;
+
+    /// Build a ticket from an iroh `blob...` ticket string
+    ///
+    /// iroh tickets carry no name, so the hash is used as the name.
+    fn from_iroh_blob_ticket(ticket: &str) -> StreamResult<Self>;
+}
+
+impl IrohTicketExt for ShareTicket {
+    fn to_iroh_blob_ticket(&self) -> StreamResult<String> {
+        self.validate()?;
+        let hash = iroh_blobs::Hash::from_str(&self.hash.0)
+            .map_err(|e| StreamError::InvalidHash(e.to_string()))?;
+
+        let format = if self.hash_seq { BlobFormat::HashSeq } else { BlobFormat::Raw };
+        Ok(BlobTicket::new(ticket_addr(self)?, hash, format).to_string())
+    }
+
+    fn from_iroh_blob_ticket(ticket: &str) -> StreamResult<Self> {
+        let ticket = BlobTicket::from_str(ticket.trim())
+            .map_err(|e| StreamError::InvalidHash(format!("Invalid iroh blob ticket: {}", e)))?;
+
+        let addr = ticket.addr();
+        let hash = ticket.hash().to_string();
+        Ok(ShareTicket {
+            node_id: addr.id.to_string(),
+            relay_url: addr.relay_urls().next().map(|r| r.to_string()).unwrap_or_else(|| "None".to_string()),
+            hash: MediaHash(hash.clone()),
+            name: hash,
+            created_at: unix_now(),
+            expires_at: None,
+            direct_addrs: addr.ip_addrs().map(|addr| addr.to_string()).collect(),
+            hash_seq: ticket.format() == BlobFormat::HashSeq,
+        })
+    }
+}
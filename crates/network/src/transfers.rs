@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use ghostdrive_core::{MediaHash, StreamError, StreamResult};
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// Stable identifier of a transfer started on a [`StreamNode`](crate::StreamNode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TransferId(pub u64);
+
+impl std::fmt::Display for TransferId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Snapshot of an in-flight transfer
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferInfo {
+    pub id: TransferId,
+    /// Content being transferred
+    pub hash: MediaHash,
+    /// Display name from the ticket
+    pub name: String,
+    /// Where the content will land once verified
+    pub dest: PathBuf,
+    pub started_at: SystemTime,
+}
+
+/// Handle to a background transfer, used to await its result
+pub struct TransferHandle {
+    id: TransferId,
+    join: JoinHandle<StreamResult<PathBuf>>,
+}
+
+impl TransferHandle {
+    pub fn id(&self) -> TransferId {
+        self.id
+    }
+
+    /// Wait for the transfer to finish, returning the final path
+    ///
+    /// Fails with [`StreamError::Cancelled`] if the transfer was cancelled.
+    pub async fn wait(self) -> StreamResult<PathBuf> {
+        match self.join.await {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => Err(StreamError::Cancelled(self.id.to_string())),
+            Err(e) => Err(StreamError::Iroh(format!("Transfer {} failed: {}", self.id, e))),
+        }
+    }
+}
+
+struct ActiveTransfer {
+    info: TransferInfo,
+    abort: AbortHandle,
+}
+
+/// In-flight transfers by id
+#[derive(Default)]
+pub(crate) struct TransferRegistry {
+    next_id: AtomicU64,
+    active: Mutex<HashMap<TransferId, ActiveTransfer>>,
+}
+
+impl TransferRegistry {
+    /// Run `transfer` in the background and track it until it finishes
+    pub(crate) fn spawn<F>(
+        self: &Arc<Self>,
+        hash: MediaHash,
+        name: String,
+        dest: PathBuf,
+        transfer: F
+    ) -> TransferHandle
+    where
+        F: Future<Output = StreamResult<PathBuf>> + Send + 'static,
+    {
+        let id = TransferId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let registry = self.clone();
+
+        // Hold the lock across spawn so the task can't finish before it's registered
+        let mut active = self.lock();
+        let join = tokio::spawn(async move {
+            let result = transfer.await;
+            registry.lock().remove(&id);
+            result
+        });
+
+        active.insert(id, ActiveTransfer {
+            info: TransferInfo {
+                id,
+                hash,
+                name,
+                dest,
+                started_at: SystemTime::now(),
+            },
+            abort: join.abort_handle(),
+        });
+
+        TransferHandle { id, join }
+    }
+
+    /// Abort a transfer, returning false if it is unknown or already finished
+    pub(crate) fn cancel(&self, id: TransferId) -> bool {
+        match self.lock().remove(&id) {
+            Some(transfer) => {
+                transfer.abort.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Snapshot of all in-flight transfers, oldest first
+    pub(crate) fn list(&self) -> Vec<TransferInfo> {
+        let mut infos: Vec<TransferInfo> = self.lock().values().map(|t| t.info.clone()).collect();
+        infos.sort_by_key(|info| info.id);
+        infos
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<TransferId, ActiveTransfer>> {
+        self.active.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,27 @@
+use ghostdrive_core::MediaHash;
+use ghostdrive_network::{BlobStatus, StreamNode};
+
+#[tokio::test]
+async fn test_blob_status() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_blob_status_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("movie.bin");
+    let content: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+    let hash = node.add_file_reference(src).await.unwrap();
+
+    assert!(node.has_blob(&hash).await.unwrap());
+    assert_eq!(node.blob_status(&hash).await.unwrap(), BlobStatus::Complete { size: content.len() as u64 });
+
+    // A hash that was never added
+    let unknown = MediaHash(blake3::hash(b"never stored").to_hex().to_string());
+    assert!(!node.has_blob(&unknown).await.unwrap());
+    assert_eq!(node.blob_status(&unknown).await.unwrap(), BlobStatus::Missing);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,54 @@
+use ghostdrive_core::MediaHash;
+use ghostdrive_network::StreamNode;
+
+fn known_hash(content: &[u8]) -> MediaHash {
+    MediaHash(blake3::hash(content).to_hex().to_string())
+}
+
+#[tokio::test]
+async fn test_read_collection_round_trip() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_collection_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+
+    let members = vec![known_hash(b"one"), known_hash(b"two"), known_hash(b"three")];
+    let collection = node.create_collection(members.clone()).await.unwrap();
+    assert_eq!(node.read_collection(&collection).await.unwrap(), members);
+
+    // A blob whose length isn't a multiple of 32 isn't a collection
+    let odd = temp_dir.join("odd.bin");
+    tokio::fs::write(&odd, vec![0u8; 33]).await.unwrap();
+    let odd_hash = node.add_file_copy(odd).await.unwrap();
+    assert!(node.read_collection(&odd_hash).await.is_err());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_named_collection_round_trip() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_named_collection_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+
+    let mut entries = Vec::new();
+    for (name, content) in [("intro.mp4", &b"intro"[..]), ("Überraschung – 第二話.mkv", b"episode two"), ("notes.txt", b"notes")] {
+        let path = temp_dir.join(format!("{}.bin", entries.len()));
+        tokio::fs::write(&path, content).await.unwrap();
+        let hash = node.add_file_copy(path).await.unwrap();
+        entries.push((name.to_string(), hash));
+    }
+
+    let collection = node.create_named_collection(entries.clone()).await.unwrap();
+    let manifest = node.read_named_collection(&collection).await.unwrap();
+
+    assert_eq!(manifest.version, ghostdrive_core::MANIFEST_VERSION);
+    let read: Vec<_> = manifest.entries.iter().map(|e| (e.name.clone(), e.hash.clone())).collect();
+    assert_eq!(read, entries);
+    assert_eq!(manifest.entries[1].size, b"episode two".len() as u64);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,48 @@
+use ghostdrive_network::StreamNode;
+
+#[tokio::test]
+async fn test_copy_import_deduplicates() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_dedup_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+
+    // Same content under two paths
+    let content = vec![42u8; 128 * 1024];
+    let first = temp_dir.join("a.bin");
+    let second = temp_dir.join("copy_of_a.bin");
+    tokio::fs::write(&first, &content).await.unwrap();
+    tokio::fs::write(&second, &content).await.unwrap();
+
+    let hash_a = node.add_file_copy(first).await.unwrap();
+    let hash_b = node.add_file_copy(second).await.unwrap();
+    assert_eq!(hash_a, hash_b);
+
+    let blobs = node.list_blobs().await.unwrap();
+    assert_eq!(blobs, vec![hash_a]);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_delete_blob() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_delete_blob_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+
+    let file = temp_dir.join("a.bin");
+    tokio::fs::write(&file, vec![7u8; 64 * 1024]).await.unwrap();
+    let hash = node.add_file_copy(file).await.unwrap();
+    assert_eq!(node.list_blobs().await.unwrap(), vec![hash.clone()]);
+
+    node.delete_blob(&hash).await.unwrap();
+    assert!(node.list_blobs().await.unwrap().is_empty());
+
+    // Deleting again is a no-op
+    node.delete_blob(&hash).await.unwrap();
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+use ghostdrive_network::{DiscoveryMode, RelayMode, StreamNode, StreamNodeConfig};
+
+#[tokio::test]
+async fn test_local_discovery_reaches_peer_without_relay() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_discovery_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let local = || StreamNodeConfig {
+        discovery: DiscoveryMode::LocalNetwork,
+        ..Default::default()
+    };
+    let sender = StreamNode::with_config(temp_dir.join("sender"), local()).await.unwrap();
+    let receiver = StreamNode::with_config(temp_dir.join("receiver"), local()).await.unwrap();
+
+    let src = temp_dir.join("lan.bin");
+    let content = vec![42u8; 64 * 1024];
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+
+    // Strip the relay: the node id has to be resolved via mDNS alone
+    let mut ticket = sender.generate_ticket(hash, "lan.bin".to_string());
+    ticket.relay_url = "None".to_string();
+
+    let dest = temp_dir.join("out.bin");
+    tokio::time::timeout(Duration::from_secs(30), receiver.fetch_to_path(&ticket, dest.clone()))
+        .await
+        .expect("Fetch timed out")
+        .expect("Peer was not reachable via local discovery");
+    assert_eq!(tokio::fs::read(&dest).await.unwrap(), content);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_direct_only_node_starts() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_direct_only_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let config = StreamNodeConfig {
+        relay: RelayMode::Disabled,
+        discovery: DiscoveryMode::LocalNetwork,
+        ..Default::default()
+    };
+    let node = StreamNode::with_config(temp_dir.join("node"), config).await.unwrap();
+
+    assert!(!node.node_id().is_empty());
+    assert_eq!(node.relay_url(), ghostdrive_core::RELAY_DISABLED);
+
+    // Tickets from a direct-only node are still valid
+    let ticket = node.generate_ticket(ghostdrive_core::MediaHash(blake3::hash(b"x").to_hex().to_string()), "x.bin".into());
+    ticket.validate().unwrap();
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_ticket_direct_addrs_reach_peer() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_direct_addrs_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // No relay and no discovery: the ticket's direct addresses are the only way in
+    let isolated = || StreamNodeConfig {
+        relay: RelayMode::Disabled,
+        discovery: DiscoveryMode::Disabled,
+        ..Default::default()
+    };
+    let sender = StreamNode::with_config(temp_dir.join("sender"), isolated()).await.unwrap();
+    let receiver = StreamNode::with_config(temp_dir.join("receiver"), isolated()).await.unwrap();
+
+    let src = temp_dir.join("direct.bin");
+    let content = vec![7u8; 32 * 1024];
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+
+    // Local addresses show up shortly after binding
+    let mut ticket = sender.generate_ticket(hash.clone(), "direct.bin".to_string());
+    for _ in 0..50 {
+        if !ticket.direct_addrs.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        ticket = sender.generate_ticket(hash.clone(), "direct.bin".to_string());
+    }
+    assert!(!ticket.direct_addrs.is_empty(), "Ticket from a bound node has no direct address");
+
+    // Survives the compact encoding, and junk entries are skipped when dialing
+    let mut ticket = ghostdrive_core::ShareTicket::decode_compact(&ticket.encode_compact().unwrap()).unwrap();
+    ticket.direct_addrs.insert(0, "not-an-address".to_string());
+
+    let dest = temp_dir.join("out.bin");
+    tokio::time::timeout(Duration::from_secs(30), receiver.fetch_to_path(&ticket, dest.clone()))
+        .await
+        .expect("Fetch timed out")
+        .expect("Peer was not reachable via its direct addresses");
+    assert_eq!(tokio::fs::read(&dest).await.unwrap(), content);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
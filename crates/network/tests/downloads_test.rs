@@ -0,0 +1,77 @@
+use std::time::Duration;
+use ghostdrive_network::{StreamNode, StreamNodeConfig};
+
+#[tokio::test]
+async fn test_download_intents_round_trip() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_intents_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("clip.bin");
+    tokio::fs::write(&src, vec![3u8; 16 * 1024]).await.unwrap();
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    sender.set_servable(&hash, false).unwrap();
+
+    // Neither optional field set on one ticket, both on the other
+    let mut plain = sender.generate_ticket(hash.clone(), "plain.bin".to_string());
+    plain.direct_addrs.clear();
+    let mut expiring = sender.generate_ticket_with_ttl(hash, "expiring.bin".to_string(), Duration::from_secs(3600));
+    expiring.direct_addrs.push("127.0.0.1:9".to_string());
+    assert!(plain.expires_at.is_none() && expiring.expires_at.is_some());
+
+    let receiver_dir = temp_dir.join("receiver");
+    {
+        let receiver = StreamNode::new(receiver_dir.clone()).await.unwrap();
+        for ticket in [&plain, &expiring] {
+            let handle = receiver.start_fetch(ticket, temp_dir.join("out").join(&ticket.name));
+            let result = tokio::time::timeout(Duration::from_secs(30), handle.wait()).await.unwrap();
+            assert!(result.is_err());
+        }
+    }
+
+    let receiver = StreamNode::new(receiver_dir).await.unwrap();
+    let mut pending = receiver.pending_downloads().unwrap();
+    pending.sort_by(|a, b| a.ticket.name.cmp(&b.ticket.name));
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].ticket, expiring);
+    assert_eq!(pending[1].ticket, plain);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_download_intents_kept_per_identity() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_intents_identity_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("clip.bin");
+    tokio::fs::write(&src, vec![4u8; 16 * 1024]).await.unwrap();
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    sender.set_servable(&hash, false).unwrap();
+    let ticket = sender.generate_ticket(hash, "clip.bin".to_string());
+
+    // The work identity leaves an unfinished download behind
+    let receiver_dir = temp_dir.join("receiver");
+    let work = StreamNodeConfig { identity: Some("work".to_string()), ..Default::default() };
+    {
+        let receiver = StreamNode::with_config(receiver_dir.clone(), work.clone()).await.unwrap();
+        let handle = receiver.start_fetch(&ticket, temp_dir.join("out").join("clip.bin"));
+        let result = tokio::time::timeout(Duration::from_secs(30), handle.wait()).await.unwrap();
+        assert!(result.is_err());
+    }
+
+    // Only that identity resumes it
+    let receiver = StreamNode::new(receiver_dir.clone()).await.unwrap();
+    assert!(receiver.pending_downloads().unwrap().is_empty());
+    drop(receiver);
+
+    let receiver = StreamNode::with_config(receiver_dir, work).await.unwrap();
+    assert_eq!(receiver.pending_downloads().unwrap().len(), 1);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
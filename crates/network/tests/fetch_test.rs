@@ -0,0 +1,224 @@
+use std::time::Duration;
+use ghostdrive_network::{StreamNode, DEFAULT_MAX_FETCH_BYTES};
+
+#[tokio::test]
+async fn test_fetch_to_path_is_atomic() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_fetch_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    // Share a file from the sender
+    let src = temp_dir.join("source.bin");
+    let content: Vec<u8> = (0..512 * 1024u32).map(|i| (i % 251) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "source.bin".to_string());
+
+    let dest = temp_dir.join("downloads").join("source.bin");
+
+    // Interrupted transfer: nothing may appear at the destination
+    let interrupted = tokio::time::timeout(
+        Duration::from_millis(1),
+        receiver.fetch_to_path(&ticket, dest.clone())
+    ).await;
+    assert!(interrupted.is_err(), "Fetch should have been cancelled");
+    assert!(!dest.exists(), "Cancelled fetch left a file at the destination");
+
+    // Full transfer lands at the destination with the right bytes
+    let written = tokio::time::timeout(
+        Duration::from_secs(30),
+        receiver.fetch_to_path(&ticket, dest.clone())
+    ).await.expect("Fetch timed out").expect("Fetch failed");
+
+    assert_eq!(written, dest);
+    assert_eq!(tokio::fs::read(&dest).await.unwrap(), content);
+    assert!(!temp_dir.join("downloads").join(".source.bin.part").exists());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_fetch_bytes() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_fetch_bytes_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("subtitles.srt");
+    let content = b"1\n00:00:01,000 --> 00:00:02,000\nHello\n".to_vec();
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "subtitles.srt".to_string());
+
+    let bytes = tokio::time::timeout(Duration::from_secs(30), receiver.fetch_bytes(&ticket, DEFAULT_MAX_FETCH_BYTES))
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch failed");
+    assert_eq!(bytes.as_ref(), content.as_slice());
+
+    // Guard rejects blobs above the limit
+    let too_small = receiver.fetch_bytes(&ticket, 4).await;
+    assert!(too_small.is_err());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_probe_ticket_reports_availability() {
+    use ghostdrive_core::MediaHash;
+    use ghostdrive_network::BlobAvailability;
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_probe_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("large.bin");
+    tokio::fs::write(&src, vec![3u8; 300 * 1024]).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+
+    // Peer with the blob: size known, body not transferred
+    let present = sender.generate_ticket(hash, "large.bin".to_string());
+    let availability = tokio::time::timeout(Duration::from_secs(30), receiver.probe_ticket(&present))
+        .await
+        .expect("Probe timed out")
+        .unwrap();
+    assert_eq!(availability, BlobAvailability::Available { size: 300 * 1024 });
+    assert!(receiver.list_blobs().await.unwrap().is_empty());
+
+    // Same peer, content it never had
+    let unknown = MediaHash(blake3::hash(b"never shared").to_hex().to_string());
+    let absent = sender.generate_ticket(unknown, "ghost.bin".to_string());
+    let availability = tokio::time::timeout(Duration::from_secs(30), receiver.probe_ticket(&absent))
+        .await
+        .expect("Probe timed out")
+        .unwrap();
+    assert_eq!(availability, BlobAvailability::Missing);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_download_uses_ticket_name() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_download_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("holiday.mp4");
+    let content: Vec<u8> = (0..200 * 1024u32).map(|i| (i % 253) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "holiday.mp4".to_string());
+
+    let inbox = temp_dir.join("inbox");
+    let path = tokio::time::timeout(Duration::from_secs(30), receiver.download(&ticket, inbox.clone()))
+        .await
+        .expect("Download timed out")
+        .expect("Download failed");
+    assert_eq!(path, inbox.join("holiday.mp4"));
+    assert_eq!(tokio::fs::read(&path).await.unwrap(), content);
+
+    // Names that would escape the destination are refused
+    let mut hostile = ticket.clone();
+    hostile.name = "../escape.mp4".to_string();
+    assert!(receiver.download(&hostile, inbox).await.is_err());
+    assert!(!temp_dir.join("escape.mp4").exists());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_download_reports_progress() {
+    use std::sync::Mutex;
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_progress_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("big.bin");
+    let size = 2 * 1024 * 1024u64;
+    tokio::fs::write(&src, vec![1u8; size as usize]).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "big.bin".to_string());
+
+    let updates = Mutex::new(Vec::new());
+    tokio::time::timeout(
+        Duration::from_secs(60),
+        receiver.download_with_progress(&ticket, temp_dir.join("inbox"), |received, total| {
+            updates.lock().unwrap().push((received, total));
+        })
+    ).await.expect("Download timed out").expect("Download failed");
+
+    let updates = updates.into_inner().unwrap();
+    assert!(!updates.is_empty());
+    // Monotonic, and ends at the full size
+    assert!(updates.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert_eq!(*updates.last().unwrap(), (size, Some(size)));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_metrics_count_transfer_bytes() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_metrics_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("counted.bin");
+    tokio::fs::write(&src, vec![3u8; 256 * 1024]).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "counted.bin".to_string());
+
+    tokio::time::timeout(Duration::from_secs(30), receiver.fetch_to_path(&ticket, temp_dir.join("out.bin")))
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch failed");
+
+    let served = sender.metrics();
+    let fetched = receiver.metrics();
+    assert!(served.bytes_sent > 0);
+    assert!(fetched.bytes_received > 0);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_expired_ticket_is_refused() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_expired_ticket_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("short_lived.bin");
+    tokio::fs::write(&src, b"short lived").await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+
+    let ticket = sender.generate_ticket_with_ttl(hash, "short_lived.bin".to_string(), Duration::ZERO);
+    assert_eq!(ticket.expires_at, Some(ticket.created_at));
+
+    let result = receiver.download(&ticket, temp_dir.join("out")).await;
+    assert!(matches!(result, Err(ghostdrive_core::StreamError::TicketExpired(_))));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
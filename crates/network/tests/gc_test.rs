@@ -0,0 +1,65 @@
+use ghostdrive_network::{GcReport, StreamNode};
+
+#[tokio::test]
+async fn test_gc_reclaims_untagged_blobs() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_gc_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+
+    let add = |name: &'static str, len: usize| {
+        let path = temp_dir.join(name);
+        let node = &node;
+        async move {
+            tokio::fs::write(&path, vec![name.len() as u8; len]).await.unwrap();
+            node.add_file_copy(path).await.unwrap()
+        }
+    };
+    let kept = add("kept.bin", 4096).await;
+    let dropped = add("dropped_blob.bin", 8192).await;
+    let member = add("member.bin", 1024).await;
+    node.create_named_collection(vec![("member.bin".into(), member.clone())]).await.unwrap();
+
+    // Nothing is collectable while everything is tagged
+    assert_eq!(node.gc().await.unwrap(), GcReport::default());
+
+    // Untagged blobs go, collection members stay
+    node.untag_blob(&dropped).await.unwrap();
+    node.untag_blob(&member).await.unwrap();
+    let report = node.gc().await.unwrap();
+    assert_eq!(report, GcReport { blobs: 1, bytes: 8192 });
+
+    let remaining = node.list_blobs().await.unwrap();
+    assert!(remaining.contains(&kept));
+    assert!(remaining.contains(&member));
+    assert!(!remaining.contains(&dropped));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_gc_keeps_downloaded_blobs() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_gc_download_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("clip.bin");
+    tokio::fs::write(&src, vec![4u8; 64 * 1024]).await.unwrap();
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash.clone(), "clip.bin".to_string());
+
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    tokio::time::timeout(std::time::Duration::from_secs(30), receiver.download(&ticket, temp_dir.join("out")))
+        .await
+        .expect("Download timed out")
+        .expect("Download failed");
+
+    // The finished download is tagged, so there is nothing to collect
+    assert_eq!(receiver.gc().await.unwrap(), GcReport::default());
+    assert!(receiver.list_blobs().await.unwrap().contains(&hash));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -1,4 +1,5 @@
-use ghostdrive_network::StreamNode;
+use ghostdrive_core::StreamError;
+use ghostdrive_network::{StreamNode, StreamNodeConfig};
 
 #[tokio::test]
 async fn test_persistent_identity() {
@@ -25,4 +26,119 @@ async fn test_persistent_identity() {
 
     // Cleanup
     let _ = tokio::fs::remove_dir_all(temp_dir).await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_passphrase_encrypted_identity() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_test_node_encrypted");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // New identity is written encrypted
+    let node = StreamNode::new_with_passphrase(temp_dir.clone(), "hunter2").await.unwrap();
+    let id = node.node_id();
+    drop(node);
+
+    let contents = tokio::fs::read_to_string(temp_dir.join("secret.key")).await.unwrap();
+    assert!(contents.starts_with("encrypted:"));
+
+    // Correct passphrase restores the same identity
+    let node = StreamNode::new_with_passphrase(temp_dir.clone(), "hunter2").await.unwrap();
+    assert_eq!(node.node_id(), id);
+    drop(node);
+
+    // Wrong or missing passphrase is rejected
+    assert!(matches!(
+        StreamNode::new_with_passphrase(temp_dir.clone(), "wrong").await,
+        Err(StreamError::Decryption(_))
+    ));
+    assert!(matches!(StreamNode::new(temp_dir.clone()).await, Err(StreamError::Decryption(_))));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_encrypt_existing_plaintext_identity() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_test_node_migrate_key");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // Plaintext identity from an older run
+    let node = StreamNode::new(temp_dir.clone()).await.unwrap();
+    let id = node.node_id();
+    drop(node);
+
+    // Plaintext keys still load when a passphrase is supplied
+    let node = StreamNode::new_with_passphrase(temp_dir.clone(), "s3cret").await.unwrap();
+    assert_eq!(node.node_id(), id);
+    drop(node);
+
+    StreamNode::encrypt_identity(&temp_dir, "s3cret").await.unwrap();
+
+    let node = StreamNode::new_with_passphrase(temp_dir.clone(), "s3cret").await.unwrap();
+    assert_eq!(node.node_id(), id);
+    drop(node);
+    assert!(StreamNode::new(temp_dir.clone()).await.is_err());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_multiple_identities_in_one_data_dir() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_test_multi_identity");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let labeled = |label: &str| StreamNodeConfig {
+        identity: Some(label.to_string()),
+        ..Default::default()
+    };
+
+    assert!(StreamNode::list_identities(&temp_dir).await.unwrap().is_empty());
+
+    // Run each persona in turn, they share the data dir but not the node id
+    let default_id = StreamNode::new(temp_dir.clone()).await.unwrap().node_id();
+    let personal_id = StreamNode::with_config(temp_dir.clone(), labeled("personal")).await.unwrap().node_id();
+    let work_id = StreamNode::with_config(temp_dir.clone(), labeled("work")).await.unwrap().node_id();
+
+    assert_ne!(default_id, personal_id);
+    assert_ne!(default_id, work_id);
+    assert_ne!(personal_id, work_id);
+
+    // Selecting a label again restores the same identity
+    let personal_again = StreamNode::with_config(temp_dir.clone(), labeled("personal")).await.unwrap();
+    assert_eq!(personal_again.node_id(), personal_id);
+    drop(personal_again);
+
+    assert_eq!(
+        StreamNode::list_identities(&temp_dir).await.unwrap(),
+        vec!["personal".to_string(), "work".to_string()]
+    );
+
+    // Labels that could escape the identities directory are refused
+    assert!(StreamNode::with_config(temp_dir.clone(), labeled("../evil")).await.is_err());
+
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_export_and_import_identity() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_test_node_export");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let original = StreamNode::new(temp_dir.join("machine_a")).await.unwrap();
+    let exported = original.export_secret_key();
+    let id = original.node_id();
+    drop(original);
+
+    // Bring the identity up on another machine
+    let bytes: [u8; 32] = hex::decode(&exported).unwrap().try_into().unwrap();
+    let moved = StreamNode::new_with_key(temp_dir.join("machine_b"), iroh::SecretKey::from_bytes(&bytes)).await.unwrap();
+    assert_eq!(moved.node_id(), id);
+    drop(moved);
+
+    // The imported key is persisted for later restarts
+    let restarted = StreamNode::new(temp_dir.join("machine_b")).await.unwrap();
+    assert_eq!(restarted.node_id(), id);
+
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
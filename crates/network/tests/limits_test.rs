@@ -0,0 +1,46 @@
+use std::time::Duration;
+use ghostdrive_network::{StreamNode, StreamNodeConfig, DEFAULT_MAX_FETCH_BYTES};
+
+#[tokio::test]
+async fn test_per_peer_request_cap() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_limits_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let src = temp_dir.join("small.bin");
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+    tokio::fs::write(&src, vec![9u8; 4096]).await.unwrap();
+
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    // Cap of one: sequential requests are fine, the slot is released each time
+    let capped = StreamNode::with_config(
+        temp_dir.join("capped"),
+        StreamNodeConfig { max_requests_per_peer: Some(1), ..Default::default() }
+    ).await.unwrap();
+    let hash = capped.add_file_reference(src.clone()).await.unwrap();
+    let ticket = capped.generate_ticket(hash, "small.bin".to_string());
+
+    for _ in 0..3 {
+        let bytes = tokio::time::timeout(Duration::from_secs(30), receiver.fetch_bytes(&ticket, DEFAULT_MAX_FETCH_BYTES))
+            .await
+            .expect("Fetch timed out")
+            .expect("Fetch under the cap should succeed");
+        assert_eq!(bytes.len(), 4096);
+    }
+
+    // Cap of zero: every request is rejected
+    let closed = StreamNode::with_config(
+        temp_dir.join("closed"),
+        StreamNodeConfig { max_requests_per_peer: Some(0), ..Default::default() }
+    ).await.unwrap();
+    let hash = closed.add_file_reference(src).await.unwrap();
+    let ticket = closed.generate_ticket(hash, "small.bin".to_string());
+
+    let rejected = tokio::time::timeout(Duration::from_secs(30), receiver.fetch_bytes(&ticket, DEFAULT_MAX_FETCH_BYTES))
+        .await
+        .expect("Fetch timed out");
+    assert!(rejected.is_err());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
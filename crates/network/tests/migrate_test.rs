@@ -0,0 +1,116 @@
+use std::time::Duration;
+use ghostdrive_network::StreamNode;
+
+#[tokio::test]
+async fn test_migrate_store() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_migrate_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let old_dir = temp_dir.join("old");
+    let new_dir = temp_dir.join("new");
+
+    let node = StreamNode::new(old_dir.clone()).await.unwrap();
+    let node_id = node.node_id();
+
+    let src = temp_dir.join("movie.bin");
+    let content = vec![7u8; 256 * 1024];
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = node.add_file_reference(src).await.unwrap();
+
+    // Move everything and drop the old store
+    let node = node.migrate_store(new_dir.clone(), true).await.expect("Migration failed");
+
+    assert_eq!(node.node_id(), node_id, "Identity must survive migration");
+    assert!(new_dir.join("blobs").exists());
+    assert!(!old_dir.join("blobs").exists());
+
+    // Content is still servable from the new location
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    let ticket = node.generate_ticket(hash, "movie.bin".to_string());
+    let dest = temp_dir.join("downloads").join("movie.bin");
+    tokio::time::timeout(Duration::from_secs(30), receiver.fetch_to_path(&ticket, dest.clone()))
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch from migrated node failed");
+    assert_eq!(tokio::fs::read(&dest).await.unwrap(), content);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_failed_migration_keeps_node_running() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_migrate_conflict_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // The target already belongs to another identity
+    let new_dir = temp_dir.join("new");
+    drop(StreamNode::new(new_dir.clone()).await.unwrap());
+
+    let node = StreamNode::new(temp_dir.join("old")).await.unwrap();
+    assert!(node.migrate_store(new_dir, true).await.is_err());
+
+    // Nothing was shut down or removed
+    let src = temp_dir.join("after.bin");
+    tokio::fs::write(&src, vec![1u8; 4096]).await.unwrap();
+    let hash = node.add_file_copy(src).await.expect("Node unusable after failed migration");
+    assert!(node.list_blobs().await.unwrap().contains(&hash));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_migration_carries_download_log() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_migrate_logs_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    // A download that can't finish yet, so its intent stays pending
+    let src = temp_dir.join("clip.bin");
+    tokio::fs::write(&src, vec![2u8; 16 * 1024]).await.unwrap();
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    sender.set_servable(&hash, false).unwrap();
+    let ticket = sender.generate_ticket(hash, "clip.bin".to_string());
+
+    let old_dir = temp_dir.join("old");
+    let node = StreamNode::new(old_dir.clone()).await.unwrap();
+    let handle = node.start_fetch(&ticket, temp_dir.join("out").join("clip.bin"));
+    assert!(tokio::time::timeout(Duration::from_secs(30), handle.wait()).await.unwrap().is_err());
+
+    let node = node.migrate_store(temp_dir.join("new"), true).await.expect("Migration failed");
+    assert_eq!(node.pending_downloads().unwrap().len(), 1);
+
+    // Nothing of this identity is left behind
+    assert!(!old_dir.join("blobs").exists());
+    assert!(!old_dir.join("downloads.db").exists());
+    assert!(!old_dir.join("peers.db").exists());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_migration_into_same_dir_refused() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_migrate_same_dir_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let data_dir = temp_dir.join("node");
+    let node = StreamNode::new(data_dir.clone()).await.unwrap();
+    let src = temp_dir.join("kept.bin");
+    tokio::fs::write(&src, vec![3u8; 4096]).await.unwrap();
+    let hash = node.add_file_copy(src).await.unwrap();
+
+    // Another spelling of the same directory
+    let alias = temp_dir.join("alias");
+    std::os::unix::fs::symlink(&data_dir, &alias).unwrap();
+    assert!(node.migrate_store(alias, true).await.is_err());
+    assert!(node.migrate_store(data_dir.join("."), true).await.is_err());
+
+    assert!(data_dir.join("blobs").exists());
+    assert!(node.list_blobs().await.unwrap().contains(&hash));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+use ghostdrive_network::StreamNode;
+use iroh::{EndpointAddr, SecretKey};
+
+#[tokio::test]
+async fn test_fetch_multi_from_two_peers() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_multi_source_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let seed_a = StreamNode::new(temp_dir.join("seed_a")).await.unwrap();
+    let seed_b = StreamNode::new(temp_dir.join("seed_b")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    // Both seeds hold the same content
+    let src = temp_dir.join("movie.bin");
+    let content: Vec<u8> = (0..2 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = seed_a.add_file_reference(src.clone()).await.unwrap();
+    assert_eq!(seed_b.add_file_reference(src).await.unwrap(), hash);
+
+    let peers = vec![seed_a.endpoint().addr(), seed_b.endpoint().addr()];
+    let size = tokio::time::timeout(Duration::from_secs(60), receiver.fetch_multi(&hash, peers))
+        .await
+        .expect("Fetch timed out")
+        .expect("Multi-source fetch failed");
+
+    assert_eq!(size, content.len() as u64);
+    assert!(receiver.list_blobs().await.unwrap().contains(&hash));
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_fetch_multi_survives_unreachable_peer() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_multi_source_fallback_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let seed = StreamNode::new(temp_dir.join("seed")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("movie.bin");
+    let content = vec![7u8; 512 * 1024];
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = seed.add_file_reference(src).await.unwrap();
+
+    // A peer nobody is running
+    let ghost = EndpointAddr::new(SecretKey::generate(&mut rand::rng()).public());
+
+    let size = tokio::time::timeout(Duration::from_secs(60), receiver.fetch_multi(&hash, vec![ghost, seed.endpoint().addr()]))
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch should fall back to the live peer");
+
+    assert_eq!(size, content.len() as u64);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_fetch_multi_skips_peer_without_blob() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_multi_source_missing_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let seed = StreamNode::new(temp_dir.join("seed")).await.unwrap();
+    let empty = StreamNode::new(temp_dir.join("empty")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("movie.bin");
+    let content = vec![8u8; 512 * 1024];
+    tokio::fs::write(&src, &content).await.unwrap();
+    let hash = seed.add_file_reference(src).await.unwrap();
+
+    // Reachable, but has nothing to prove the size with
+    let peers = vec![empty.endpoint().addr(), seed.endpoint().addr()];
+    let size = tokio::time::timeout(Duration::from_secs(60), receiver.fetch_multi(&hash, peers))
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch should take the size from the peer that has the blob");
+
+    assert_eq!(size, content.len() as u64);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+use ghostdrive_network::StreamNode;
+
+#[tokio::test]
+async fn test_known_peers() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_peers_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    assert!(sender.known_peers().unwrap().is_empty());
+
+    let src = temp_dir.join("shared.bin");
+    tokio::fs::write(&src, vec![1u8; 64 * 1024]).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "shared.bin".to_string());
+
+    tokio::time::timeout(Duration::from_secs(30), receiver.fetch_to_path(&ticket, temp_dir.join("out.bin")))
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch failed");
+
+    // Events are recorded asynchronously
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let peers = sender.known_peers().unwrap();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].node_id, receiver.node_id());
+    assert!(peers[0].bytes_served >= 64 * 1024);
+    assert!(peers[0].first_seen <= peers[0].last_seen);
+
+    // Privacy: history can be wiped
+    sender.clear_known_peers().unwrap();
+    assert!(sender.known_peers().unwrap().is_empty());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+use ghostdrive_network::{StreamNode, StreamNodeConfig, DEFAULT_MAX_FETCH_BYTES};
+
+#[tokio::test]
+async fn test_reference_failure_falls_back_to_copy() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_reference_fallback_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("remote.bin");
+    let content = vec![5u8; 32 * 1024];
+    tokio::fs::write(&src, &content).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    sender.fail_references(true);
+
+    // The reference fails, the copy fallback still makes the file servable
+    let hash = sender.add_file_reference(src.clone()).await.expect("Fallback to copy failed");
+    assert_eq!(sender.list_blobs().await.unwrap(), vec![hash.clone()]);
+
+    let ticket = sender.generate_ticket(hash, "remote.bin".to_string());
+    let bytes = tokio::time::timeout(Duration::from_secs(30), receiver.fetch_bytes(&ticket, DEFAULT_MAX_FETCH_BYTES))
+        .await
+        .expect("Fetch timed out")
+        .unwrap();
+    assert_eq!(&bytes[..], &content[..]);
+
+    // Strict mode surfaces the failure instead
+    let strict = StreamNode::with_config(
+        temp_dir.join("strict"),
+        StreamNodeConfig { strict_reference: true, ..Default::default() }
+    ).await.unwrap();
+    strict.fail_references(true);
+    assert!(strict.add_file_reference(src).await.is_err());
+    assert!(strict.list_blobs().await.unwrap().is_empty());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,185 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use ghostdrive_network::{BlobStatus, StreamNode, StreamNodeConfig};
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn test_interrupted_download_resumes_after_restart() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_resume_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("movie.bin");
+    let content: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash.clone(), "movie.bin".to_string());
+    let dest = temp_dir.join("downloads").join("movie.bin");
+
+    // The sender withholds the blob, so the first attempt fails before any
+    // bytes move and only the persisted intent is left to resume
+    sender.set_servable(&hash, false).unwrap();
+    let receiver_dir = temp_dir.join("receiver");
+    {
+        let receiver = StreamNode::new(receiver_dir.clone()).await.unwrap();
+        let handle = receiver.start_fetch(&ticket, dest.clone());
+        let result = tokio::time::timeout(Duration::from_secs(30), handle.wait()).await.unwrap();
+        assert!(result.is_err());
+        assert_eq!(receiver.pending_downloads().unwrap().len(), 1);
+    }
+
+    // "Restart" the receiver: the intent survived and the download completes
+    sender.set_servable(&hash, true).unwrap();
+    let receiver = StreamNode::new(receiver_dir).await.unwrap();
+    let pending = receiver.pending_downloads().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].ticket.hash, hash);
+
+    let handles = receiver.resume_downloads().unwrap();
+    assert_eq!(handles.len(), 1);
+    for handle in handles {
+        let path = tokio::time::timeout(Duration::from_secs(30), handle.wait())
+            .await
+            .expect("Resumed download timed out")
+            .expect("Resumed download failed");
+        assert_eq!(tokio::fs::read(path).await.unwrap(), content);
+    }
+
+    // Finished downloads are forgotten
+    assert!(receiver.pending_downloads().unwrap().is_empty());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+/// Find the receiver store's data file for `hash`
+fn find_data_file(dir: &std::path::Path, hash: &str) -> Option<std::path::PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()? {
+        let path = entry.ok()?.path();
+        if path.is_dir() {
+            if let Some(found) = find_data_file(&path, hash) {
+                return Some(found);
+            }
+        } else if path.file_name()?.to_string_lossy() == format!("{}.data", hash) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+#[tokio::test]
+async fn test_corrupt_stored_blob_is_refetched() {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_resume_corrupt_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("movie.bin");
+    let content: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash.clone(), "movie.bin".to_string());
+
+    let receiver_dir = temp_dir.join("receiver");
+    let receiver = StreamNode::new(receiver_dir.clone()).await.unwrap();
+    let first = tokio::time::timeout(Duration::from_secs(30), receiver.download(&ticket, temp_dir.join("first")))
+        .await
+        .expect("Download timed out")
+        .expect("Download failed");
+    assert_eq!(tokio::fs::read(first).await.unwrap(), content);
+
+    // Damage the stored copy behind the store's back
+    let data_file = find_data_file(&receiver_dir, &hash.0).expect("Stored blob data not found");
+    let mut file = std::fs::OpenOptions::new().write(true).open(&data_file).unwrap();
+    file.seek(SeekFrom::Start(100 * 1024)).unwrap();
+    file.write_all(&[0xFF; 4096]).unwrap();
+    drop(file);
+
+    // The mismatch is caught and the blob fetched again instead of exported bad
+    let second = tokio::time::timeout(Duration::from_secs(30), receiver.download(&ticket, temp_dir.join("second")))
+        .await
+        .expect("Download timed out")
+        .expect("Download of corrupt blob failed");
+    assert_eq!(tokio::fs::read(second).await.unwrap(), content);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_cut_transfer_resumes_from_partial() {
+    const SIZE: u64 = 1024 * 1024;
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_resume_partial_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let src = temp_dir.join("movie.bin");
+    let content: Vec<u8> = (0..SIZE).map(|i| (i % 251) as u8).collect();
+    tokio::fs::write(&src, &content).await.unwrap();
+
+    // Capped so the transfer is still running when the sender goes away
+    let sender_dir = temp_dir.join("sender");
+    let sender = StreamNode::with_config(
+        sender_dir.clone(),
+        StreamNodeConfig { max_upload_bps: Some(128 * 1024), ..Default::default() }
+    ).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash.clone(), "movie.bin".to_string());
+
+    // Take the sender down after a few progress events
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    let events = Mutex::new(0usize);
+    let cut = Notify::new();
+    let first = receiver.download_with_progress(&ticket, temp_dir.join("first"), |_, _| {
+        let mut events = events.lock().unwrap();
+        *events += 1;
+        if *events == 4 {
+            cut.notify_one();
+        }
+    });
+    let shutdown = async {
+        cut.notified().await;
+        sender.shutdown_handle().shutdown().await.unwrap();
+    };
+    let (first, ()) = tokio::time::timeout(Duration::from_secs(60), async { tokio::join!(first, shutdown) })
+        .await
+        .expect("Cut transfer timed out");
+    assert!(first.is_err());
+    drop(sender);
+
+    let have = match receiver.blob_status(&hash).await.unwrap() {
+        BlobStatus::Partial { have, .. } => have,
+        other => panic!("Expected a partial blob, got {:?}", other),
+    };
+    assert!(have > 0 && have < SIZE);
+
+    // Same identity and store, so the same content is offered again
+    let sender = StreamNode::new(sender_dir).await.unwrap();
+    let ticket = sender.generate_ticket(hash.clone(), "movie.bin".to_string());
+
+    let reported = Mutex::new(Vec::new());
+    let path = tokio::time::timeout(
+        Duration::from_secs(60),
+        receiver.download_with_progress(&ticket, temp_dir.join("second"), |bytes, _| reported.lock().unwrap().push(bytes))
+    )
+        .await
+        .expect("Resumed download timed out")
+        .expect("Resumed download failed");
+    assert_eq!(tokio::fs::read(path).await.unwrap(), content);
+
+    // Only the missing part crossed the wire: every update but the final
+    // one counts bytes of this transfer, which is at most what was missing
+    // plus the chunk group that was cut off
+    let reported = reported.into_inner().unwrap();
+    let transferred = reported[..reported.len() - 1].iter().copied().max().unwrap_or(0);
+    assert!(transferred <= SIZE - have + 16 * 1024, "Resume moved {} bytes with {} already stored", transferred, have);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
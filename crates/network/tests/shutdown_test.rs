@@ -0,0 +1,56 @@
+use std::time::Duration;
+use ghostdrive_network::{BlobAvailability, StreamNode};
+
+#[tokio::test]
+async fn test_shutdown_with_download_in_progress() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_shutdown_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let src = temp_dir.join("large.bin");
+    tokio::fs::write(&src, vec![9u8; 8 * 1024 * 1024]).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "large.bin".to_string());
+
+    // Shutting down mid-download cancels it but keeps it resumable
+    let _handle = receiver.start_fetch(&ticket, temp_dir.join("out").join("large.bin"));
+    tokio::time::timeout(Duration::from_secs(30), receiver.shutdown())
+        .await
+        .expect("Shutdown hung")
+        .expect("Shutdown failed");
+
+    let restarted = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    assert_eq!(restarted.pending_downloads().unwrap().len(), 1);
+
+    // A stopped sender is reported unreachable rather than failing
+    sender.shutdown().await.unwrap();
+    let availability = tokio::time::timeout(Duration::from_secs(60), restarted.probe_ticket(&ticket))
+        .await
+        .expect("Probe timed out")
+        .unwrap();
+    assert_eq!(availability, BlobAvailability::Unreachable);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_shutdown_handle_closes_shared_node() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_shutdown_handle_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // Another owner still holds the node, as a daemon's background tasks do
+    let node = std::sync::Arc::new(StreamNode::new(temp_dir.clone()).await.unwrap());
+    let shared = node.clone();
+
+    tokio::time::timeout(Duration::from_secs(30), node.shutdown_handle().shutdown())
+        .await
+        .expect("Shutdown hung")
+        .expect("Shutdown failed");
+    assert!(shared.endpoint().is_closed());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
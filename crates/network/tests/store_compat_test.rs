@@ -0,0 +1,58 @@
+use ghostdrive_network::StreamNode;
+
+#[tokio::test]
+async fn test_legacy_flat_store_is_upgraded() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_store_compat_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // Lay out an old flat store: complete/<hex hash>.data plus metadata
+    let complete = temp_dir.join("blobs").join("complete");
+    tokio::fs::create_dir_all(&complete).await.unwrap();
+    tokio::fs::create_dir_all(temp_dir.join("blobs").join("meta")).await.unwrap();
+
+    let content = b"content from an older release".to_vec();
+    let hash = blake3::hash(&content).to_hex().to_string();
+    tokio::fs::write(complete.join(format!("{}.data", hash)), &content).await.unwrap();
+
+    // A blob whose name doesn't match its content is not carried over
+    let bogus = blake3::hash(b"something else").to_hex().to_string();
+    tokio::fs::write(complete.join(format!("{}.data", bogus)), b"tampered").await.unwrap();
+
+    let node = StreamNode::new(temp_dir.clone()).await.expect("Legacy store should be upgraded");
+
+    let blobs: Vec<String> = node.list_blobs().await.unwrap().into_iter().map(|h| h.0).collect();
+    assert!(blobs.contains(&hash));
+    assert!(!blobs.contains(&bogus));
+    assert!(!blobs.contains(&blake3::hash(b"tampered").to_hex().to_string()));
+
+    // The old store is kept as a backup
+    assert!(temp_dir.join("blobs.legacy").join("complete").exists());
+    assert!(!temp_dir.join("blobs.upgrading").exists());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_interrupted_upgrade_is_resumed() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_store_compat_resume_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    // State left by a crash right after the old store was moved aside
+    let complete = temp_dir.join("blobs.legacy").join("complete");
+    tokio::fs::create_dir_all(&complete).await.unwrap();
+    tokio::fs::write(temp_dir.join("blobs.upgrading"), b"").await.unwrap();
+
+    let content = b"content moved aside before the crash".to_vec();
+    let hash = blake3::hash(&content).to_hex().to_string();
+    tokio::fs::write(complete.join(format!("{}.data", hash)), &content).await.unwrap();
+
+    let node = StreamNode::new(temp_dir.clone()).await.expect("Interrupted upgrade should resume");
+
+    let blobs: Vec<String> = node.list_blobs().await.unwrap().into_iter().map(|h| h.0).collect();
+    assert_eq!(blobs, vec![hash]);
+    assert!(!temp_dir.join("blobs.upgrading").exists());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+use ghostdrive_network::{StreamNode, StreamNodeConfig};
+
+const CAP: u64 = 64 * 1024;
+const SIZE: usize = 256 * 1024;
+
+/// Least time `SIZE` bytes can take at `CAP`, after the one second burst
+const MIN_ELAPSED: Duration = Duration::from_secs(2);
+
+#[tokio::test]
+async fn test_download_cap_slows_transfer() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_throttle_download_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let src = temp_dir.join("data.bin");
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+    tokio::fs::write(&src, vec![5u8; SIZE]).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "data.bin".to_string());
+
+    let receiver = StreamNode::with_config(
+        temp_dir.join("receiver"),
+        StreamNodeConfig { max_download_bps: Some(CAP), ..Default::default() }
+    ).await.unwrap();
+
+    let started = Instant::now();
+    let path = tokio::time::timeout(Duration::from_secs(60), receiver.download(&ticket, temp_dir.join("out")))
+        .await
+        .expect("Download timed out")
+        .expect("Download should succeed");
+    let elapsed = started.elapsed();
+
+    assert_eq!(tokio::fs::read(&path).await.unwrap().len(), SIZE);
+    assert!(elapsed >= MIN_ELAPSED, "Capped download took only {:?}", elapsed);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_upload_cap_slows_transfer() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_throttle_upload_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let src = temp_dir.join("data.bin");
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+    tokio::fs::write(&src, vec![6u8; SIZE]).await.unwrap();
+
+    let sender = StreamNode::with_config(
+        temp_dir.join("sender"),
+        StreamNodeConfig { max_upload_bps: Some(CAP), ..Default::default() }
+    ).await.unwrap();
+    let hash = sender.add_file_reference(src).await.unwrap();
+    let ticket = sender.generate_ticket(hash, "data.bin".to_string());
+
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let started = Instant::now();
+    let path = tokio::time::timeout(Duration::from_secs(60), receiver.download(&ticket, temp_dir.join("out")))
+        .await
+        .expect("Download timed out")
+        .expect("Download should succeed");
+    let elapsed = started.elapsed();
+
+    assert_eq!(tokio::fs::read(&path).await.unwrap().len(), SIZE);
+    assert!(elapsed >= MIN_ELAPSED, "Download from a capped sender took only {:?}", elapsed);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_download_cap_covers_multi_source_fetch() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_throttle_multi_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let src = temp_dir.join("data.bin");
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+    tokio::fs::write(&src, vec![7u8; SIZE]).await.unwrap();
+
+    let seed_a = StreamNode::new(temp_dir.join("seed_a")).await.unwrap();
+    let seed_b = StreamNode::new(temp_dir.join("seed_b")).await.unwrap();
+    let hash = seed_a.add_file_reference(src.clone()).await.unwrap();
+    seed_b.add_file_reference(src).await.unwrap();
+
+    let receiver = StreamNode::with_config(
+        temp_dir.join("receiver"),
+        StreamNodeConfig { max_download_bps: Some(CAP), ..Default::default() }
+    ).await.unwrap();
+
+    // Two peers don't double the cap: it is shared by the whole node
+    let peers = vec![seed_a.endpoint().addr(), seed_b.endpoint().addr()];
+    let started = Instant::now();
+    let size = tokio::time::timeout(Duration::from_secs(60), receiver.fetch_multi(&hash, peers))
+        .await
+        .expect("Fetch timed out")
+        .expect("Multi-source fetch failed");
+    let elapsed = started.elapsed();
+
+    assert_eq!(size, SIZE as u64);
+    assert!(elapsed >= MIN_ELAPSED, "Capped multi-source fetch took only {:?}", elapsed);
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -0,0 +1,91 @@
+use std::str::FromStr;
+use ghostdrive_core::ShareTicket;
+use ghostdrive_network::{IrohTicketExt, StreamNode};
+use iroh::{EndpointAddr, SecretKey};
+use iroh_blobs::{ticket::BlobTicket, BlobFormat, Hash};
+
+#[tokio::test]
+async fn test_iroh_blob_ticket_round_trip() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_iroh_ticket_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let node = StreamNode::new(temp_dir.join("node")).await.unwrap();
+    let src = temp_dir.join("clip.bin");
+    tokio::fs::write(&src, b"interop").await.unwrap();
+    let hash = node.add_file_copy(src).await.unwrap();
+    let ticket = node.generate_ticket(hash.clone(), "clip.bin".to_string());
+
+    // GhostDrive -> iroh
+    let iroh_ticket = BlobTicket::from_str(&ticket.to_iroh_blob_ticket().unwrap()).unwrap();
+    assert_eq!(iroh_ticket.hash().to_string(), hash.0);
+    assert_eq!(iroh_ticket.format(), BlobFormat::Raw);
+    assert_eq!(iroh_ticket.addr().id, node.id());
+
+    // iroh -> GhostDrive
+    let back = ShareTicket::from_iroh_blob_ticket(&iroh_ticket.to_string()).unwrap();
+    assert_eq!(back.hash, hash);
+    assert_eq!(back.node_id, node.node_id());
+    assert_eq!(back.relay_url, ticket.relay_url);
+    back.validate().unwrap();
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[test]
+fn test_hash_seq_ticket_round_trip() {
+    let addr = EndpointAddr::new(SecretKey::generate(&mut rand::rng()).public());
+    let seq = BlobTicket::new(addr, Hash::new(b"collection"), BlobFormat::HashSeq);
+
+    // iroh -> GhostDrive keeps the collection format
+    let ticket = ShareTicket::from_iroh_blob_ticket(&seq.to_string()).unwrap();
+    assert!(ticket.hash_seq);
+    assert_eq!(ticket.hash.0, seq.hash().to_string());
+
+    // GhostDrive -> iroh gives the same ticket back
+    let back = BlobTicket::from_str(&ticket.to_iroh_blob_ticket().unwrap()).unwrap();
+    assert_eq!(back.format(), BlobFormat::HashSeq);
+    assert_eq!(back, seq);
+
+    assert!(ShareTicket::from_iroh_blob_ticket("blobnotaticket").is_err());
+}
+
+#[tokio::test]
+async fn test_collection_ticket_fetches_members() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_collection_ticket_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let mut members = Vec::new();
+    for (name, byte) in [("a.bin", 1u8), ("b.bin", 2u8)] {
+        let src = temp_dir.join(name);
+        tokio::fs::write(&src, vec![byte; 8192]).await.unwrap();
+        members.push(sender.add_file_copy(src).await.unwrap());
+    }
+    let collection = sender.create_collection(members.clone()).await.unwrap();
+    let ticket = sender.generate_collection_ticket(collection.clone(), "album".to_string());
+
+    // GhostDrive -> iroh -> GhostDrive keeps the collection format
+    let iroh_ticket = BlobTicket::from_str(&ticket.to_iroh_blob_ticket().unwrap()).unwrap();
+    assert_eq!(iroh_ticket.format(), BlobFormat::HashSeq);
+    let ticket = ShareTicket::from_iroh_blob_ticket(&iroh_ticket.to_string()).unwrap();
+    assert!(ticket.hash_seq);
+
+    // The members come along with the root
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    let fetched = tokio::time::timeout(std::time::Duration::from_secs(30), receiver.fetch_collection(&ticket))
+        .await
+        .expect("Fetch timed out")
+        .expect("Collection fetch failed");
+    assert_eq!(fetched, members);
+    for member in &members {
+        assert!(receiver.has_blob(member).await.unwrap());
+    }
+
+    // A single-file download can't stand in for it
+    assert!(receiver.download(&ticket, temp_dir.join("out")).await.is_err());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
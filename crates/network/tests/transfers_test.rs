@@ -0,0 +1,50 @@
+use std::time::Duration;
+use ghostdrive_core::StreamError;
+use ghostdrive_network::StreamNode;
+
+#[tokio::test]
+async fn test_cancel_one_transfer_while_other_completes() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transfers_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+
+    let mut tickets = Vec::new();
+    for (name, seed) in [("keep.bin", 3u32), ("drop.bin", 7u32)] {
+        let src = temp_dir.join(name);
+        let content: Vec<u8> = (0..256 * 1024u32).map(|i| ((i * seed) % 251) as u8).collect();
+        tokio::fs::write(&src, &content).await.unwrap();
+        let hash = sender.add_file_reference(src).await.unwrap();
+        tickets.push((sender.generate_ticket(hash, name.to_string()), content));
+    }
+
+    let downloads = temp_dir.join("downloads");
+    let keep = receiver.start_fetch(&tickets[0].0, downloads.join("keep.bin"));
+    let dropped = receiver.start_fetch(&tickets[1].0, downloads.join("drop.bin"));
+
+    assert_ne!(keep.id(), dropped.id());
+    let active: Vec<_> = receiver.active_transfers().into_iter().map(|t| t.id).collect();
+    assert_eq!(active, vec![keep.id(), dropped.id()]);
+
+    // Cancel just the second transfer
+    assert!(receiver.cancel_transfer(dropped.id()));
+    assert!(!receiver.cancel_transfer(dropped.id()), "Second cancel should be a no-op");
+
+    let cancelled = dropped.wait().await;
+    assert!(matches!(cancelled, Err(StreamError::Cancelled(_))));
+
+    let written = tokio::time::timeout(Duration::from_secs(30), keep.wait())
+        .await
+        .expect("Fetch timed out")
+        .expect("Fetch failed");
+    assert_eq!(tokio::fs::read(&written).await.unwrap(), tickets[0].1);
+
+    // Finished and cancelled transfers leave the registry, the cancelled one leaves no files
+    assert!(receiver.active_transfers().is_empty());
+    assert!(!downloads.join("drop.bin").exists());
+    assert!(!downloads.join(".drop.bin.part").exists());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
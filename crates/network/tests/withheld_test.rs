@@ -0,0 +1,100 @@
+use std::str::FromStr;
+use std::time::Duration;
+use ghostdrive_network::StreamNode;
+use iroh_blobs::protocol::{ChunkRanges, ChunkRangesExt, GetManyRequest, GetRequest};
+use iroh_blobs::store::mem::MemStore;
+use iroh_blobs::{Hash, ALPN};
+
+#[tokio::test]
+async fn test_withheld_member_not_served_through_collection() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_withheld_collection_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let add = |name: &'static str, byte: u8| {
+        let path = temp_dir.join(name);
+        let sender = &sender;
+        async move {
+            tokio::fs::write(&path, vec![byte; 8192]).await.unwrap();
+            sender.add_file_copy(path).await.unwrap()
+        }
+    };
+    let open = add("open.bin", 1).await;
+    let secret = add("secret.bin", 2).await;
+    let collection = sender.create_collection(vec![open.clone(), secret.clone()]).await.unwrap();
+    sender.set_servable(&secret, false).unwrap();
+
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    let connect = || async {
+        tokio::time::timeout(Duration::from_secs(30), receiver.endpoint().connect(sender.endpoint().addr(), ALPN))
+            .await
+            .expect("Connect timed out")
+            .expect("Connect failed")
+    };
+    let collection = Hash::from_str(&collection.0).unwrap();
+    let open = Hash::from_str(&open.0).unwrap();
+    let secret = Hash::from_str(&secret.0).unwrap();
+
+    // The collection root and its servable member are fine
+    let store = MemStore::new();
+    let request = GetRequest::builder().root(ChunkRanges::all()).child(0, ChunkRanges::all()).build(collection);
+    store.remote().execute_get(connect().await, request).await.expect("Servable member refused");
+    assert!(store.has(open).await.unwrap());
+
+    // Reaching the withheld member as a child of the collection is refused
+    let store = MemStore::new();
+    let request = GetRequest::builder().child(1, ChunkRanges::all()).build(collection);
+    assert!(store.remote().execute_get(connect().await, request).await.is_err());
+    assert!(!store.has(secret).await.unwrap());
+
+    // So is naming it in a multi-blob request
+    let store = MemStore::new();
+    let request = GetManyRequest::builder().hash(open, ChunkRanges::all()).hash(secret, ChunkRanges::all()).build();
+    assert!(store.remote().execute_get_many(connect().await, request).await.is_err());
+    assert!(!store.has(secret).await.unwrap());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_children_of_large_blob_refused_without_reading_it() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_withheld_large_root_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+
+    let sender = StreamNode::new(temp_dir.join("sender")).await.unwrap();
+    let video = temp_dir.join("video.bin");
+    tokio::fs::write(&video, vec![3u8; 4 * 1024 * 1024]).await.unwrap();
+    let video = sender.add_file_reference(video).await.unwrap();
+    let secret = temp_dir.join("secret.bin");
+    tokio::fs::write(&secret, vec![4u8; 8192]).await.unwrap();
+    let secret = sender.add_file_copy(secret).await.unwrap();
+    sender.set_servable(&secret, false).unwrap();
+
+    let receiver = StreamNode::new(temp_dir.join("receiver")).await.unwrap();
+    let connect = || async {
+        tokio::time::timeout(Duration::from_secs(30), receiver.endpoint().connect(sender.endpoint().addr(), ALPN))
+            .await
+            .expect("Connect timed out")
+            .expect("Connect failed")
+    };
+    let video = Hash::from_str(&video.0).unwrap();
+
+    // Far too big to be one of our collections, so it isn't loaded to check
+    let store = MemStore::new();
+    let request = GetRequest::builder().child(0, ChunkRanges::all()).build(video);
+    assert!(store.remote().execute_get(connect().await, request).await.is_err());
+
+    // The blob itself is still served
+    let request = GetRequest::builder().root(ChunkRanges::all()).build(video);
+    tokio::time::timeout(Duration::from_secs(30), store.remote().execute_get(connect().await, request))
+        .await
+        .expect("Fetch timed out")
+        .expect("Plain request refused");
+    assert!(store.has(video).await.unwrap());
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
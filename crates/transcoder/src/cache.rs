@@ -0,0 +1,297 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use ghostdrive_core::{MediaHash, StreamError, StreamResult};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use tracing::{debug, info};
+
+use crate::TranscodeOptions;
+
+/// Leading bytes of a chunk index sidecar file
+const INDEX_MAGIC: &[u8; 8] = b"GDCHIDX1";
+
+/// Most bytes returned by one [`CachedTranscode::read_range`]
+pub const MAX_RANGE_READ: u64 = 1024 * 1024;
+
+/// Byte layout of a cached transcode: where each output chunk starts
+///
+/// Built while the output is written, so serving a range never needs to
+/// scan or re-run ffmpeg.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkIndex {
+    /// Start offset of each chunk, ascending
+    offsets: Vec<u64>,
+    /// Total length of the cached output in bytes
+    len: u64,
+}
+
+impl ChunkIndex {
+    /// Record a chunk of `size` bytes written after all previous ones
+    fn push(&mut self, size: u64) {
+        self.offsets.push(self.len);
+        self.len += size;
+    }
+
+    /// Total length of the indexed output
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Start offsets of every chunk
+    pub fn offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+
+    /// Position of the chunk containing byte `offset`, if in bounds
+    pub fn chunk_at(&self, offset: u64) -> Option<usize> {
+        if offset >= self.len {
+            return None;
+        }
+        match self.offsets.binary_search(&offset) {
+            Ok(i) => Some(i),
+            Err(i) => Some(i - 1),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(INDEX_MAGIC.len() + 16 + self.offsets.len() * 8);
+        buf.extend_from_slice(INDEX_MAGIC);
+        buf.extend_from_slice(&self.len.to_le_bytes());
+        buf.extend_from_slice(&(self.offsets.len() as u64).to_le_bytes());
+        for offset in &self.offsets {
+            buf.extend_from_slice(&offset.to_le_bytes());
+        }
+        buf
+    }
+
+    fn decode(bytes: &[u8]) -> StreamResult<Self> {
+        let invalid = || StreamError::Transcode("Corrupt chunk index".to_string());
+
+        let rest = bytes.strip_prefix(INDEX_MAGIC.as_slice()).ok_or_else(invalid)?;
+        let mut words = rest.chunks_exact(8)
+            .map(|w| u64::from_le_bytes(w.try_into().expect("chunks_exact yields 8 bytes")));
+
+        let len = words.next().ok_or_else(invalid)?;
+        let count = words.next().ok_or_else(invalid)?;
+        let offsets: Vec<u64> = words.collect();
+
+        if rest.len() % 8 != 0 || offsets.len() as u64 != count {
+            return Err(invalid());
+        }
+        if offsets.windows(2).any(|w| w[0] >= w[1]) || offsets.last().is_some_and(|&o| o >= len) {
+            return Err(invalid());
+        }
+
+        Ok(Self { offsets, len })
+    }
+}
+
+/// On-disk cache of finished transcodes, keyed by source hash and options
+pub struct TranscodeCache {
+    dir: PathBuf,
+}
+
+impl TranscodeCache {
+    /// Open (creating if needed) a cache rooted at `dir`
+    pub async fn new(dir: PathBuf) -> StreamResult<Self> {
+        fs::create_dir_all(&dir).await.map_err(StreamError::from)?;
+        Ok(Self { dir })
+    }
+
+    /// Path of the cached output for `source` transcoded with `options`
+    pub fn entry_path(&self, source: &MediaHash, options: &TranscodeOptions) -> PathBuf {
+        self.dir.join(format!("{}-{}.{}", source.0, options.fingerprint(), options.format))
+    }
+
+    /// Write a transcode output stream into the cache, indexing chunks as they land
+    ///
+    /// Output and index are written to temporary files and renamed into place,
+    /// so a failed or interrupted transcode never leaves a partial entry.
+    pub async fn store<S>(
+        &self,
+        source: &MediaHash,
+        options: &TranscodeOptions,
+        stream: S
+    ) -> StreamResult<CachedTranscode>
+    where
+        S: Stream<Item = StreamResult<Bytes>>,
+    {
+        let path = self.entry_path(source, options);
+        let part_path = with_suffix(&path, ".part");
+        let index_path = index_path(&path);
+        let index_part_path = with_suffix(&index_path, ".part");
+
+        let result = async {
+            let mut file = fs::File::create(&part_path).await.map_err(StreamError::from)?;
+            let mut index = ChunkIndex::default();
+
+            tokio::pin!(stream);
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                if chunk.is_empty() {
+                    continue;
+                }
+                file.write_all(&chunk).await.map_err(StreamError::from)?;
+                index.push(chunk.len() as u64);
+            }
+            file.sync_all().await.map_err(StreamError::from)?;
+
+            fs::write(&index_part_path, index.encode()).await.map_err(StreamError::from)?;
+
+            // Index last: an output without its index is treated as a miss
+            fs::rename(&part_path, &path).await.map_err(StreamError::from)?;
+            fs::rename(&index_part_path, &index_path).await.map_err(StreamError::from)?;
+
+            Ok(index)
+        }.await;
+
+        match result {
+            Ok(index) => {
+                info!("Cached transcode {:?} ({} bytes, {} chunks)", path, index.len(), index.offsets.len());
+                Ok(CachedTranscode { path, index })
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&part_path).await;
+                let _ = fs::remove_file(&index_part_path).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Look up a cached transcode, returning None on a miss
+    pub async fn open(
+        &self,
+        source: &MediaHash,
+        options: &TranscodeOptions
+    ) -> StreamResult<Option<CachedTranscode>> {
+        let path = self.entry_path(source, options);
+
+        let encoded = match fs::read(index_path(&path)).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(StreamError::from(e)),
+        };
+        let index = ChunkIndex::decode(&encoded)?;
+
+        // The index must describe the output actually on disk
+        match fs::metadata(&path).await {
+            Ok(meta) if meta.len() == index.len() => {}
+            Ok(_) => {
+                debug!("Cached transcode {:?} does not match its index, ignoring", path);
+                return Ok(None);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(StreamError::from(e)),
+        }
+
+        Ok(Some(CachedTranscode { path, index }))
+    }
+}
+
+/// A finished transcode in the cache that can serve byte ranges
+#[derive(Debug, Clone)]
+pub struct CachedTranscode {
+    path: PathBuf,
+    index: ChunkIndex,
+}
+
+impl CachedTranscode {
+    /// Location of the cached output
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Chunk layout of the cached output
+    pub fn index(&self) -> &ChunkIndex {
+        &self.index
+    }
+
+    /// Total length of the cached output
+    pub fn len(&self) -> u64 {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Read the start of `range` (end exclusive), as needed for an HTTP Range request
+    ///
+    /// Never more than [`MAX_RANGE_READ`] bytes are read, so a client asking
+    /// for the whole file can't make the host buffer it. The end is clamped
+    /// to the output length; a start at or past the end is an error. Use
+    /// [`CachedTranscode::stream_range`] for the whole range.
+    pub async fn read_range(&self, range: Range<u64>) -> StreamResult<Bytes> {
+        let end = self.clamp(&range)?.min(range.start + MAX_RANGE_READ);
+        debug!("Serving {}..{} of {:?}", range.start, end, self.path);
+
+        let mut file = self.open_at(range.start).await?;
+        read_exactly(&mut file, end - range.start).await
+    }
+
+    /// Stream the bytes in `range` from one open file, [`MAX_RANGE_READ`] bytes at a time
+    ///
+    /// Errors the same way as [`CachedTranscode::read_range`] for a start at
+    /// or past the end.
+    pub fn stream_range(&self, range: Range<u64>) -> impl Stream<Item = StreamResult<Bytes>> + '_ {
+        futures::stream::try_unfold(None, move |state: Option<(fs::File, u64, u64)>| {
+            let range = range.clone();
+            async move {
+                let (mut file, pos, end) = match state {
+                    Some(state) => state,
+                    None => {
+                        let end = self.clamp(&range)?;
+                        (self.open_at(range.start).await?, range.start, end)
+                    }
+                };
+                if pos >= end {
+                    return Ok(None);
+                }
+                let bytes = read_exactly(&mut file, (end - pos).min(MAX_RANGE_READ)).await?;
+                let next = pos + bytes.len() as u64;
+                Ok(Some((bytes, Some((file, next, end)))))
+            }
+        })
+    }
+
+    /// End of `range` clamped to the output, or an error if it starts past the end
+    fn clamp(&self, range: &Range<u64>) -> StreamResult<u64> {
+        let end = range.end.min(self.len());
+        if range.start >= end {
+            return Err(StreamError::Transcode(format!(
+                "Range {}..{} outside cached output of {} bytes",
+                range.start, range.end, self.len()
+            )));
+        }
+        Ok(end)
+    }
+
+    async fn open_at(&self, offset: u64) -> StreamResult<fs::File> {
+        let mut file = fs::File::open(&self.path).await.map_err(StreamError::from)?;
+        file.seek(SeekFrom::Start(offset)).await.map_err(StreamError::from)?;
+        Ok(file)
+    }
+}
+
+async fn read_exactly(file: &mut fs::File, len: u64) -> StreamResult<Bytes> {
+    let mut buf = vec![0u8; len as usize];
+    file.read_exact(&mut buf).await.map_err(StreamError::from)?;
+    Ok(Bytes::from(buf))
+}
+
+fn index_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".idx")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
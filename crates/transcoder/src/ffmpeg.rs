@@ -1,13 +1,60 @@
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::time::Duration;
 use async_stream::try_stream;
 use bytes::{Bytes, BytesMut};
 use futures_core::Stream;
 use tokio::process::{Child, Command};
 use tokio::io::AsyncReadExt;
-use tracing::{debug, error, info, instrument};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, instrument, warn};
 use ghostdrive_core::{StreamError, StreamResult};
 
+use crate::progress::{spawn_stderr_reader, TranscodeProgress};
+
+/// Hardware video encoder to use instead of software encoding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HwAccel {
+    /// Software encoding with [`TranscodeOptions::video_codec`]
+    #[default]
+    None,
+    /// NVIDIA NVENC
+    Nvenc,
+    /// VA-API (Intel/AMD on Linux), using the first render node
+    Vaapi,
+    /// Apple VideoToolbox
+    VideoToolbox,
+    /// Intel Quick Sync Video
+    Qsv,
+}
+
+impl HwAccel {
+    /// H.264 encoder replacing the configured codec, `None` for software encoding
+    pub fn encoder(&self) -> Option<&'static str> {
+        match self {
+            HwAccel::None => None,
+            HwAccel::Nvenc => Some("h264_nvenc"),
+            HwAccel::Vaapi => Some("h264_vaapi"),
+            HwAccel::VideoToolbox => Some("h264_videotoolbox"),
+            HwAccel::Qsv => Some("h264_qsv"),
+        }
+    }
+
+    /// Device setup arguments placed before `-i`
+    fn input_args(&self) -> &'static [&'static str] {
+        match self {
+            HwAccel::None => &[],
+            HwAccel::Nvenc => &["-hwaccel", "cuda"],
+            HwAccel::Vaapi => &["-init_hw_device", "vaapi=va:/dev/dri/renderD128", "-filter_hw_device", "va"],
+            HwAccel::VideoToolbox => &["-hwaccel", "videotoolbox"],
+            HwAccel::Qsv => &["-hwaccel", "qsv"],
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TranscodeOptions {
     pub video_codec: String,
@@ -16,6 +63,338 @@ pub struct TranscodeOptions {
     pub format: String,
     pub resolution: Option<String>,
     pub frame_rate: Option<u32>,
+    /// Threads ffmpeg may use (`-threads`), `None` lets ffmpeg decide
+    pub threads: Option<u32>,
+    /// CPU cores the ffmpeg process is pinned to, `None` allows any core
+    ///
+    /// Only applied on Linux; elsewhere the setting is ignored with a warning.
+    pub cpu_affinity: Option<Vec<usize>>,
+    /// Position in the input to start from (`-ss` before `-i`, keyframe seek)
+    pub start_offset: Option<Duration>,
+    /// Maximum length of output to produce (`-t`)
+    pub duration: Option<Duration>,
+    /// Hardware encoder, overriding `video_codec` unless [`HwAccel::None`]
+    pub hw_accel: HwAccel,
+    /// Copy the audio and video streams as-is, only remuxing into `format`
+    ///
+    /// Codec, bitrate, resolution, frame rate and hardware settings are
+    /// ignored, since they would require re-encoding.
+    pub copy: bool,
+    /// ffmpeg binary to run, `None` looks up `ffmpeg` on PATH
+    pub ffmpeg_path: Option<PathBuf>,
+    /// Extra arguments inserted just before the output spec
+    pub extra_args: Vec<String>,
+    /// Drop any video track (`-vn`) and produce audio only
+    ///
+    /// Video settings are ignored, so this also works for audio files that
+    /// have no video stream at all.
+    pub audio_only: bool,
+    /// Audio bitrate (`-b:a`), `None` keeps the encoder default
+    pub audio_bitrate: Option<String>,
+}
+
+impl TranscodeOptions {
+    /// Stable hex key identifying this option set, for caches and in-flight dedup
+    ///
+    /// Every field is fed into a BLAKE3 hash in a fixed order, so equal option
+    /// sets always produce the same key across runs. The struct is destructured
+    /// exhaustively: adding a field to `TranscodeOptions` fails to compile until
+    /// it is included here too. Scheduling settings (`threads`, `cpu_affinity`)
+    /// and the binary location (`ffmpeg_path`) don't change what is produced,
+    /// so they are left out.
+    pub fn fingerprint(&self) -> String {
+        let TranscodeOptions {
+            video_codec,
+            video_bitrate,
+            audio_codec,
+            format,
+            resolution,
+            frame_rate,
+            threads: _,
+            cpu_affinity: _,
+            start_offset,
+            duration,
+            hw_accel,
+            copy,
+            ffmpeg_path: _,
+            extra_args,
+            audio_only,
+            audio_bitrate,
+        } = self;
+
+        let mut hasher = blake3::Hasher::new();
+        fingerprint_str(&mut hasher, video_codec);
+        fingerprint_str(&mut hasher, video_bitrate);
+        fingerprint_str(&mut hasher, audio_codec);
+        fingerprint_str(&mut hasher, format);
+        fingerprint_opt(&mut hasher, resolution.as_deref().map(str::as_bytes));
+        fingerprint_opt(&mut hasher, frame_rate.map(u32::to_le_bytes).as_ref().map(|b| b.as_slice()));
+        fingerprint_opt(&mut hasher, start_offset.map(|d| d.as_nanos().to_le_bytes()).as_ref().map(|b| b.as_slice()));
+        fingerprint_opt(&mut hasher, duration.map(|d| d.as_nanos().to_le_bytes()).as_ref().map(|b| b.as_slice()));
+        fingerprint_opt(&mut hasher, hw_accel.encoder().map(str::as_bytes));
+        hasher.update(&[*copy as u8]);
+        hasher.update(&(extra_args.len() as u64).to_le_bytes());
+        for arg in extra_args {
+            fingerprint_str(&mut hasher, arg);
+        }
+        hasher.update(&[*audio_only as u8]);
+        fingerprint_opt(&mut hasher, audio_bitrate.as_deref().map(str::as_bytes));
+
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Check the options for values ffmpeg would reject
+    ///
+    /// Catches obvious mistakes up front with a clear error, rather than a
+    /// cryptic one from ffmpeg after the process has started.
+    pub fn validate(&self) -> StreamResult<()> {
+        if self.video_codec.trim().is_empty() {
+            return Err(StreamError::Transcode("Video codec is empty".to_string()));
+        }
+        if self.audio_codec.trim().is_empty() {
+            return Err(StreamError::Transcode("Audio codec is empty".to_string()));
+        }
+        if !is_bitrate(&self.video_bitrate) {
+            return Err(StreamError::Transcode(format!(
+                "Invalid video bitrate {:?}, expected e.g. \"2M\" or \"800k\"", self.video_bitrate
+            )));
+        }
+        if let Some(bitrate) = self.audio_bitrate.as_deref().filter(|b| !is_bitrate(b)) {
+            return Err(StreamError::Transcode(format!(
+                "Invalid audio bitrate {:?}, expected e.g. \"128k\"", bitrate
+            )));
+        }
+        if let Some(resolution) = self.resolution.as_deref().filter(|r| !is_resolution(r)) {
+            return Err(StreamError::Transcode(format!(
+                "Invalid resolution {:?}, expected WIDTHxHEIGHT", resolution
+            )));
+        }
+        if self.frame_rate == Some(0) {
+            return Err(StreamError::Transcode("Frame rate must be non-zero".to_string()));
+        }
+        Ok(())
+    }
+
+    /// These options adjusted for a source of type `mime`
+    ///
+    /// `audio/*` sources are switched to [`TranscodeOptions::audio_only`].
+    pub fn for_mime(&self, mime: &str) -> Self {
+        Self {
+            audio_only: self.audio_only || mime.starts_with("audio/"),
+            ..self.clone()
+        }
+    }
+
+    /// The ffmpeg binary these options run
+    pub fn ffmpeg_binary(&self) -> &Path {
+        self.ffmpeg_path.as_deref().unwrap_or(Path::new(DEFAULT_FFMPEG))
+    }
+
+    /// Options that remux without re-encoding (see [`TranscodeOptions::copy`])
+    pub fn passthrough() -> Self {
+        Self {
+            copy: true,
+            resolution: None,
+            frame_rate: None,
+            ..Default::default()
+        }
+    }
+
+    /// Arguments passed to ffmpeg to transcode `input` to stdout
+    pub fn ffmpeg_args(&self, input: &Path) -> Vec<OsString> {
+        self.ffmpeg_args_for(input, &TranscodeOutput::Pipe)
+    }
+
+    /// Arguments passed to ffmpeg to transcode `input` to `output`
+    pub fn ffmpeg_args_for(&self, input: &Path, output: &TranscodeOutput) -> Vec<OsString> {
+        // Input options
+        let mut args: Vec<OsString> = vec![
+            "-hide_banner".into(),
+            "-loglevel".into(),
+            "error".into(),
+            // Machine-readable progress on stderr, keeping stdout for data
+            "-nostats".into(),
+            "-progress".into(),
+            "pipe:2".into(),
+        ];
+
+        if !self.copy && !self.audio_only {
+            args.extend(self.hw_accel.input_args().iter().map(OsString::from));
+        }
+
+        // Before -i, so ffmpeg seeks to a keyframe instead of decoding up to it
+        if let Some(offset) = self.start_offset {
+            args.extend(["-ss".into(), seconds_arg(offset).into()]);
+        }
+        args.extend(["-i".into(), input.into()]);
+
+        let mut opts: Vec<String> = Vec::new();
+
+        if let Some(duration) = self.duration {
+            opts.extend(["-t".to_string(), seconds_arg(duration)]);
+        }
+
+        if let Some(threads) = self.threads {
+            opts.extend(["-threads".to_string(), threads.to_string()]);
+        }
+
+        if self.audio_only {
+            opts.push("-vn".to_string());
+        } else if self.copy {
+            opts.extend(["-c:v", "copy"].map(String::from));
+        } else {
+            opts.extend(self.video_args());
+        }
+
+        // Audio options
+        if self.copy {
+            // Remux only: no encoder settings apply
+            opts.extend(["-c:a", "copy"].map(String::from));
+        } else {
+            opts.extend(["-c:a".to_string(), self.audio_codec.clone()]);
+            if let Some(bitrate) = &self.audio_bitrate {
+                opts.extend(["-b:a".to_string(), bitrate.clone()]);
+            }
+        }
+
+        opts.extend(self.extra_args.iter().cloned());
+
+        args.extend(opts.into_iter().map(OsString::from));
+        self.push_output_args(&mut args, output);
+        args
+    }
+
+    /// Encoder settings for re-encoding video
+    fn video_args(&self) -> Vec<String> {
+        let mut opts: Vec<String> = Vec::new();
+
+        // Video options
+        let encoder = self.hw_accel.encoder().unwrap_or(&self.video_codec);
+        opts.extend(["-c:v".to_string(), encoder.to_string()]);
+        opts.extend(["-b:v".to_string(), self.video_bitrate.clone()]);
+
+        if self.hw_accel == HwAccel::Vaapi {
+            // Frames have to be uploaded to the GPU, so scaling happens in the filter first
+            let scale = self.resolution.as_ref()
+                .map(|res| format!("scale={},", res.replace('x', ":")))
+                .unwrap_or_default();
+            opts.extend(["-vf".to_string(), format!("{}format=nv12,hwupload", scale)]);
+        } else if let Some(res) = &self.resolution {
+            opts.extend(["-s".to_string(), res.clone()]);
+        }
+
+        if let Some(fps) = self.frame_rate {
+            opts.extend(["-r".to_string(), fps.to_string()]);
+        }
+
+        // Optimization for latency (zerolatency tuning for x264 only)
+        if encoder == "libx264" {
+            opts.extend(["-preset", "veryfast", "-tune", "zerolatency"].map(String::from));
+        }
+
+        opts
+    }
+
+    /// Append the muxer and destination for `output`
+    fn push_output_args(&self, args: &mut Vec<OsString>, output: &TranscodeOutput) {
+        match output {
+            // Output options (Stdout pipe)
+            TranscodeOutput::Pipe => {
+                args.extend(["-f".into(), self.format.clone().into(), "pipe:1".into()]);
+            }
+            // Rolling segment window, older segments are deleted from disk
+            TranscodeOutput::Hls { segment_duration, out_dir } => {
+                args.extend([
+                    "-f".into(),
+                    "hls".into(),
+                    "-hls_time".into(),
+                    segment_duration.to_string().into(),
+                    "-hls_list_size".into(),
+                    HLS_LIST_SIZE.to_string().into(),
+                    "-hls_flags".into(),
+                    "delete_segments".into(),
+                    "-hls_segment_filename".into(),
+                    out_dir.join(HLS_SEGMENT_PATTERN).into(),
+                    output.playlist_path().expect("HLS output has a playlist").into(),
+                ]);
+            }
+        }
+    }
+}
+
+/// Number of segments kept in an HLS playlist (and on disk)
+const HLS_LIST_SIZE: u32 = 6;
+
+/// File name of the HLS playlist inside the output directory
+const HLS_PLAYLIST_NAME: &str = "playlist.m3u8";
+
+/// File name pattern of HLS segments inside the output directory
+const HLS_SEGMENT_PATTERN: &str = "segment_%05d.ts";
+
+/// Where the transcoded media goes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TranscodeOutput {
+    /// A single stream on stdout, in [`TranscodeOptions::format`]
+    #[default]
+    Pipe,
+    /// HLS: MPEG-TS segments plus an `.m3u8` playlist written to `out_dir`
+    Hls {
+        /// Target length of each segment in seconds
+        segment_duration: u32,
+        /// Directory receiving the playlist and segments
+        out_dir: PathBuf,
+    },
+}
+
+impl TranscodeOutput {
+    /// Playlist written for HLS output
+    pub fn playlist_path(&self) -> Option<PathBuf> {
+        match self {
+            TranscodeOutput::Pipe => None,
+            TranscodeOutput::Hls { out_dir, .. } => Some(out_dir.join(HLS_PLAYLIST_NAME)),
+        }
+    }
+}
+
+/// Format a duration as ffmpeg seconds with millisecond precision
+fn seconds_arg(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// Whether `value` is a bitrate ffmpeg accepts: `<digits>[kKmM]`, e.g. `2M` or `800k`
+fn is_bitrate(value: &str) -> bool {
+    let digits = value.strip_suffix(['k', 'K', 'm', 'M']).unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Whether `value` is a resolution: `<width>x<height>`, both non-zero
+fn is_resolution(value: &str) -> bool {
+    let Some((width, height)) = value.split_once('x') else {
+        return false;
+    };
+    [width, height].iter().all(|side| {
+        !side.is_empty() && side.bytes().all(|b| b.is_ascii_digit()) && side.parse::<u32>().is_ok_and(|n| n > 0)
+    })
+}
+
+/// Length-prefix each value so adjacent fields can't run into each other
+fn fingerprint_str(hasher: &mut blake3::Hasher, value: &str) {
+    hasher.update(&(value.len() as u64).to_le_bytes());
+    hasher.update(value.as_bytes());
+}
+
+/// Tag optional values so `None` differs from an empty `Some`
+fn fingerprint_opt(hasher: &mut blake3::Hasher, value: Option<&[u8]>) {
+    match value {
+        Some(bytes) => {
+            hasher.update(&[1]);
+            hasher.update(&(bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        }
+        None => {
+            hasher.update(&[0]);
+        }
+    }
 }
 
 impl Default for TranscodeOptions {
@@ -27,68 +406,114 @@ impl Default for TranscodeOptions {
             format: "mpegts".to_string(),
             resolution: Some("1280x720".to_string()),
             frame_rate: Some(30),
+            threads: None,
+            cpu_affinity: None,
+            start_offset: None,
+            duration: None,
+            hw_accel: HwAccel::None,
+            copy: false,
+            ffmpeg_path: None,
+            extra_args: Vec::new(),
+            audio_only: false,
+            audio_bitrate: None,
         }
     }
 }
 
+/// Restrict the spawned process to `cores`
+#[cfg(target_os = "linux")]
+fn pin_to_cores(cmd: &mut Command, cores: Vec<usize>) -> StreamResult<()> {
+    if cores.is_empty() {
+        return Err(StreamError::Transcode("CPU affinity needs at least one core".to_string()));
+    }
+    if let Some(core) = cores.iter().find(|&&core| core >= libc::CPU_SETSIZE as usize) {
+        return Err(StreamError::Transcode(format!("CPU core {} is out of range", core)));
+    }
+
+    // SAFETY: runs in the forked child before exec and only calls the
+    // async-signal-safe sched_setaffinity on a stack-local cpu set
+    unsafe {
+        cmd.pre_exec(move || {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for &core in &cores {
+                libc::CPU_SET(core, &mut set);
+            }
+            if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_to_cores(_cmd: &mut Command, _cores: Vec<usize>) -> StreamResult<()> {
+    warn!("CPU affinity is only supported on Linux, ignoring");
+    Ok(())
+}
+
+/// ffmpeg binary used when no path is configured
+const DEFAULT_FFMPEG: &str = "ffmpeg";
+
+/// Fail early with a helpful message if ffmpeg isn't installed
+async fn ensure_ffmpeg(binary: &Path) -> StreamResult<()> {
+    match Command::new(binary).arg("-version").output().await {
+        Ok(output) if output.status.success() => {
+            debug!("FFmpeg detected successfully at {:?}", binary);
+            Ok(())
+        }
+        _ => Err(StreamError::DependencyMissing {
+            tool: binary.to_string_lossy().into_owned(),
+        }),
+    }
+}
+
 pub struct Transcoder {
     process: Child,
+    playlist: Option<PathBuf>,
+    progress: Option<mpsc::UnboundedReceiver<TranscodeProgress>>,
+    /// Collects ffmpeg's error output, see [`spawn_stderr_reader`]
+    stderr: Option<JoinHandle<String>>,
+    cancel: CancellationToken,
 }
 
 impl Transcoder {
     /// Spawns a new FFmpeg process to transcode the input file
     /// Returns immediately with the Transcoder handle
-    #[instrument(skip(options))]
     pub async fn new(input_path: PathBuf, options: TranscodeOptions) -> StreamResult<Self> {
-        // Validate FFmpeg installation
-        match Command::new("ffmpeg").arg("-version").output().await {
-            Ok(output) if output.status.success() => {
-                debug!("FFmpeg detected successfully");
-            }
-            _ => {
-                return Err(StreamError::Transcode(
-                    "FFmpeg not found. Please ensure ffmpeg is installed and in PATH".to_string()
-                ));
-            }
-        }
+        Self::with_output(input_path, options, TranscodeOutput::Pipe).await
+    }
+
+    /// Like [`Transcoder::new`], writing to `output` instead of stdout
+    ///
+    /// For HLS the output directory is created if needed, and the playlist
+    /// path is available from [`Transcoder::playlist_path`] right away.
+    #[instrument(skip(options))]
+    pub async fn with_output(
+        input_path: PathBuf,
+        options: TranscodeOptions,
+        output: TranscodeOutput
+    ) -> StreamResult<Self> {
+        options.validate()?;
+        ensure_ffmpeg(options.ffmpeg_binary()).await?;
 
         if !input_path.exists() {
             return Err(StreamError::FileNotFound(input_path));
         }
 
-        // Build command
-        let mut cmd = Command::new("ffmpeg");
-
-        // Input options
-        cmd.arg("-hide_banner")
-            .arg("-loglevel").arg("error")
-            .arg("-i").arg(&input_path);
-
-        // Video options
-        cmd.arg("-c:v").arg(&options.video_codec)
-            .arg("-b:v").arg(&options.video_bitrate);
-
-        if let Some(res) = &options.resolution {
-            cmd.arg("-s").arg(res);
+        if let TranscodeOutput::Hls { out_dir, .. } = &output {
+            tokio::fs::create_dir_all(out_dir).await.map_err(StreamError::from)?;
         }
 
-        if let Some(fps) = options.frame_rate {
-            cmd.arg("-r").arg(fps.to_string());
-        }
+        // Build command
+        let mut cmd = Command::new(options.ffmpeg_binary());
+        cmd.args(options.ffmpeg_args_for(&input_path, &output));
 
-        // Optimization for latency (zerolatency tuning for x264)
-        if options.video_codec == "libx264" {
-            cmd.arg("-preset").arg("veryfast")
-                .arg("-tune").arg("zerolatency");
+        if let Some(cores) = &options.cpu_affinity {
+            pin_to_cores(&mut cmd, cores.clone())?;
         }
 
-        // Audio options
-        cmd.arg("-c:a").arg(&options.audio_codec);
-
-        // Output options (Stdout pipe)
-        cmd.arg("-f").arg(&options.format)
-            .arg("pipe:1");
-
         // Cleanup configuration
         cmd.kill_on_drop(true);
         cmd.stdout(Stdio::piped());
@@ -98,10 +523,102 @@ impl Transcoder {
         info!("Spawning FFmpeg for {:?}", input_path);
         debug!("Command: {:?}", cmd);
 
-        let process = cmd.spawn()
-            .map_err(|e| StreamError::Io(e))?;
+        let mut process = cmd.spawn()
+            .map_err(|e| StreamError::from(e))?;
 
-        Ok(Self { process })
+        let (progress, stderr) = match process.stderr.take() {
+            Some(stderr) => {
+                let (progress, task) = spawn_stderr_reader(stderr);
+                (Some(progress), Some(task))
+            }
+            None => (None, None),
+        };
+
+        Ok(Self {
+            process,
+            playlist: output.playlist_path(),
+            progress,
+            stderr,
+            cancel: CancellationToken::new(),
+        })
+    }
+
+    /// Stop the transcode when `token` is cancelled
+    ///
+    /// [`Transcoder::stream_chunks`] then kills and reaps ffmpeg and ends the
+    /// stream without an error, e.g. when an HTTP client disconnects.
+    pub fn with_cancel(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Progress reports parsed from ffmpeg, roughly twice a second
+    ///
+    /// Returns `None` if already taken. The stream ends when ffmpeg exits;
+    /// compare `out_time` with the duration from [`probe`](crate::probe) for
+    /// a percentage.
+    pub fn progress(&mut self) -> Option<impl Stream<Item = TranscodeProgress> + use<>> {
+        let mut rx = self.progress.take()?;
+        Some(async_stream::stream! {
+            while let Some(update) = rx.recv().await {
+                yield update;
+            }
+        })
+    }
+
+    /// Capture the frame at `at` as a JPEG, optionally scaled to `size` (e.g. "320x180")
+    ///
+    /// Runs a one-shot ffmpeg process, the binary chosen by `options`
+    /// (see [`TranscodeOptions::ffmpeg_binary`]), and returns the image in memory.
+    #[instrument(skip(options))]
+    pub async fn extract_thumbnail(
+        input: PathBuf,
+        at: Duration,
+        size: Option<String>,
+        options: &TranscodeOptions
+    ) -> StreamResult<Bytes> {
+        let ffmpeg = options.ffmpeg_binary();
+        ensure_ffmpeg(ffmpeg).await?;
+
+        if !input.exists() {
+            return Err(StreamError::FileNotFound(input));
+        }
+
+        let mut cmd = Command::new(ffmpeg);
+        cmd.args(["-hide_banner", "-loglevel", "error", "-ss", &seconds_arg(at), "-i"]);
+        cmd.arg(&input);
+        cmd.args(["-frames:v", "1"]);
+        if let Some(size) = &size {
+            cmd.args(["-s", size]);
+        }
+        cmd.args(["-c:v", "mjpeg", "-f", "image2", "pipe:1"]);
+        cmd.kill_on_drop(true);
+
+        debug!("Command: {:?}", cmd);
+        let output = cmd.output().await.map_err(StreamError::from)?;
+
+        if !output.status.success() {
+            return Err(StreamError::Transcode(format!(
+                "Thumbnail capture exited with code {:?}: {}",
+                output.status.code(),
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        // Seeking past the end succeeds but yields no frame
+        if output.stdout.is_empty() {
+            return Err(StreamError::Transcode(format!(
+                "No frame at {:?} in {:?}: timestamp is past the end of the video",
+                at, input
+            )));
+        }
+
+        Ok(Bytes::from(output.stdout))
+    }
+
+    /// Playlist path for HLS output, `None` when streaming to stdout
+    pub fn playlist_path(&self) -> Option<&Path> {
+        self.playlist.as_deref()
     }
     
     /// Take the stdout handle from the child process
@@ -113,14 +630,14 @@ impl Transcoder {
     /// Wait for the process to complete and check status
     /// If non-zero exit code, reads stderr for details
     pub async fn wait(mut self) -> StreamResult<()> {
-        let status = self.process.wait().await.map_err(StreamError::Io)?;
+        let status = self.process.wait().await.map_err(StreamError::from)?;
         
         if !status.success() {
-            // Attempt to read stderr if available
-            let mut err_msg = String::new();
-            if let Some(mut stderr) = self.process.stderr.take() {
-                let _ = stderr.read_to_string(&mut err_msg).await;
-            }
+            // Error output collected from stderr, if available
+            let err_msg = match self.stderr.take() {
+                Some(task) => task.await.unwrap_or_default(),
+                None => String::new(),
+            };
             
             error!("FFmpeg exited with error: {}", err_msg);
             return Err(StreamError::Transcode(format!(
@@ -136,7 +653,9 @@ impl Transcoder {
     /// Stream the output of the transcoding process in chunks
     ///
     /// This consumes the Transcoder instance. The underlying FFmpeg process
-    /// will be killed when stream is dropped, or waited upon when EOF if reached
+    /// will be killed when stream is dropped, or waited upon when EOF if reached.
+    /// Cancelling the token from [`Transcoder::with_cancel`] kills the process
+    /// and ends the stream cleanly.
     pub fn stream_chunks(
         mut self,
         chunk_size: usize
@@ -154,8 +673,20 @@ impl Transcoder {
                     buffer.reserve(chunk_size);
                 }
 
-                // Read from pipe directly into buffer
-                let n = stdout.read_buf(&mut buffer).await.map_err(StreamError::Io)?;
+                // Read from pipe directly into buffer, unless cancelled first
+                let n = tokio::select! {
+                    biased;
+                    _ = self.cancel.cancelled() => {
+                        drop(stdout);
+                        // kill() also reaps the child, so no zombie is left behind
+                        if let Err(e) = self.process.kill().await {
+                            warn!("Failed to kill cancelled FFmpeg: {}", e);
+                        }
+                        info!("Transcode cancelled");
+                        break;
+                    }
+                    read = stdout.read_buf(&mut buffer) => read.map_err(StreamError::from)?,
+                };
 
                 if n == 0 {
                     // EOF reached. Verify process exit status
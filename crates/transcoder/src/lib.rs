@@ -1,3 +1,9 @@
+mod cache;
 mod ffmpeg;
+mod probe;
+mod progress;
 
-pub use ffmpeg::{Transcoder, TranscodeOptions};
\ No newline at end of file
+pub use ffmpeg::{HwAccel, Transcoder, TranscodeOptions, TranscodeOutput};
+pub use probe::{probe, MediaInfo, StreamInfo};
+pub use progress::TranscodeProgress;
+pub use cache::{CachedTranscode, ChunkIndex, TranscodeCache, MAX_RANGE_READ};
\ No newline at end of file
@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use ghostdrive_core::{StreamError, StreamResult};
+use serde::Deserialize;
+use tokio::process::Command;
+use tracing::{debug, instrument};
+
+/// Container-level facts about a media file, from ffprobe
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaInfo {
+    /// Duration in seconds, if the container reports one
+    pub duration: Option<f64>,
+    /// Container format name(s), e.g. "mov,mp4,m4a,3gp,3g2,mj2"
+    pub format: String,
+    /// Streams in file order
+    pub streams: Vec<StreamInfo>,
+}
+
+/// One audio, video, subtitle or data stream of a [`MediaInfo`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Stream kind as reported by ffprobe ("video", "audio", ...)
+    pub kind: String,
+    /// Codec name, e.g. "h264"
+    pub codec: String,
+    /// Frame width (video only)
+    pub width: Option<u32>,
+    /// Frame height (video only)
+    pub height: Option<u32>,
+    /// Bitrate in bits per second, if known
+    pub bit_rate: Option<u64>,
+}
+
+impl MediaInfo {
+    /// The first video stream, if any
+    pub fn video(&self) -> Option<&StreamInfo> {
+        self.streams.iter().find(|s| s.kind == "video")
+    }
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    format: ProbeFormat,
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    format_name: String,
+    duration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    #[serde(default)]
+    codec_type: String,
+    #[serde(default)]
+    codec_name: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    bit_rate: Option<String>,
+}
+
+/// Read duration, container and stream details of `input` with ffprobe
+#[instrument]
+pub async fn probe(input: &Path) -> StreamResult<MediaInfo> {
+    if !input.exists() {
+        return Err(StreamError::FileNotFound(input.to_path_buf()));
+    }
+
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(input)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => StreamError::DependencyMissing { tool: "ffprobe".to_string() },
+            _ => StreamError::from(e),
+        })?;
+
+    if !output.status.success() {
+        return Err(StreamError::Transcode(format!(
+            "ffprobe could not read {:?} (exit code {:?})",
+            input,
+            output.status.code()
+        )));
+    }
+
+    let parsed: ProbeOutput = serde_json::from_slice(&output.stdout)
+        .map_err(|e| StreamError::Transcode(format!("Unexpected ffprobe output: {}", e)))?;
+    debug!("Probed {:?}: {} stream(s)", input, parsed.streams.len());
+
+    Ok(MediaInfo {
+        duration: parsed.format.duration.and_then(|d| d.parse().ok()),
+        format: parsed.format.format_name,
+        streams: parsed.streams.into_iter()
+            .map(|s| StreamInfo {
+                kind: s.codec_type,
+                codec: s.codec_name,
+                width: s.width,
+                height: s.height,
+                bit_rate: s.bit_rate.and_then(|b| b.parse().ok()),
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::ChildStderr;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// One progress report from a running ffmpeg, see [`Transcoder::progress`](crate::Transcoder::progress)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TranscodeProgress {
+    /// Position in the output reached so far
+    pub out_time: Duration,
+    /// Frames encoded so far
+    pub frame: u64,
+    /// Encoding speed relative to real time (0 while unknown)
+    pub speed: f64,
+}
+
+/// Drain ffmpeg's stderr, splitting `-progress pipe:2` reports from error output
+///
+/// Reading continuously keeps ffmpeg from blocking on a full stderr pipe
+/// even when nobody consumes the progress updates. The task resolves to the
+/// non-progress output (ffmpeg's error messages) once stderr closes.
+pub(crate) fn spawn_stderr_reader(stderr: ChildStderr) -> (mpsc::UnboundedReceiver<TranscodeProgress>, JoinHandle<String>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    let task = tokio::spawn(async move {
+        let mut errors = String::new();
+        let mut lines = BufReader::new(stderr).lines();
+        let mut current = TranscodeProgress::default();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Some((key, value)) = progress_field(&line) else {
+                errors.push_str(&line);
+                errors.push('\n');
+                continue;
+            };
+
+            match key {
+                "frame" => current.frame = value.parse().unwrap_or(current.frame),
+                "out_time_us" => {
+                    if let Ok(us) = value.parse::<u64>() {
+                        current.out_time = Duration::from_micros(us);
+                    }
+                }
+                "speed" => current.speed = value.trim_end_matches('x').trim().parse().unwrap_or(0.0),
+                // Closes each report
+                "progress" => {
+                    // Receiver gone just means nobody is listening
+                    let _ = tx.send(current);
+                }
+                _ => {}
+            }
+        }
+
+        errors
+    });
+
+    (rx, task)
+}
+
+/// Split a `key=value` progress line; keys are plain lowercase identifiers
+fn progress_field(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    let is_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    is_key.then_some((key, value))
+}
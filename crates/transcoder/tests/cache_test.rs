@@ -0,0 +1,73 @@
+use bytes::Bytes;
+use futures::TryStreamExt;
+use ghostdrive_core::MediaHash;
+use ghostdrive_transcoder::{TranscodeCache, TranscodeOptions, MAX_RANGE_READ};
+
+#[tokio::test]
+async fn test_cached_transcode_serves_mid_file_range() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_cache_range_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let cache = TranscodeCache::new(temp_dir.clone()).await.unwrap();
+    let source = MediaHash("source".into());
+    let options = TranscodeOptions::default();
+
+    // Stand-in for transcoder output: 10 chunks of 1000 bytes with distinct content
+    let output: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let chunks: Vec<_> = output.chunks(1000)
+        .map(|c| Ok(Bytes::copy_from_slice(c)))
+        .collect();
+
+    assert!(cache.open(&source, &options).await.unwrap().is_none());
+    cache.store(&source, &options, futures::stream::iter(chunks)).await.unwrap();
+
+    // Reopen from disk, as a server handling a later request would
+    let cached = cache.open(&source, &options).await.unwrap().expect("Cache miss");
+    assert_eq!(cached.len(), 10_000);
+    assert_eq!(cached.index().offsets().len(), 10);
+    assert_eq!(cached.index().chunk_at(4_500), Some(4));
+
+    // A range spanning several chunks is read in one go
+    let range = cached.read_range(4_500..6_200).await.unwrap();
+    assert_eq!(&range[..], &output[4_500..6_200]);
+
+    let parts: Vec<Bytes> = cached.stream_range(4_500..6_200).try_collect().await.unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts.concat(), &output[4_500..6_200]);
+
+    // End is clamped, start past the end is rejected
+    assert_eq!(cached.read_range(9_990..20_000).await.unwrap().len(), 10);
+    assert!(cached.read_range(10_000..10_001).await.is_err());
+    assert!(cached.stream_range(10_000..10_001).try_collect::<Vec<_>>().await.is_err());
+
+    // A different option set is a separate entry
+    let other = TranscodeOptions { video_bitrate: "4M".into(), ..options };
+    assert!(cache.open(&source, &other).await.unwrap().is_none());
+
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
+
+#[tokio::test]
+async fn test_cached_read_is_bounded() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_cache_bounded_test");
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+
+    let cache = TranscodeCache::new(temp_dir.clone()).await.unwrap();
+    let source = MediaHash("large".into());
+    let options = TranscodeOptions::default();
+
+    // A single chunk larger than one read
+    let size = (MAX_RANGE_READ * 2 + 5) as usize;
+    let chunk = Bytes::from(vec![9u8; size]);
+    let cached = cache.store(&source, &options, futures::stream::iter([Ok(chunk)])).await.unwrap();
+
+    let first = cached.read_range(0..u64::MAX).await.unwrap();
+    assert_eq!(first.len() as u64, MAX_RANGE_READ);
+
+    let parts: Vec<Bytes> = cached.stream_range(0..u64::MAX).try_collect().await.unwrap();
+    assert_eq!(parts.len(), 3);
+    assert!(parts.iter().all(|part| part.len() as u64 <= MAX_RANGE_READ));
+    assert_eq!(parts.iter().map(Bytes::len).sum::<usize>(), size);
+
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
@@ -2,7 +2,8 @@ use std::path::PathBuf;
 use std::time::Duration;
 use tokio::io::AsyncReadExt;
 use tokio::process::Command;
-use ghostdrive_transcoder::{Transcoder, TranscodeOptions};
+use ghostdrive_core::StreamError;
+use ghostdrive_transcoder::{Transcoder, TranscodeOptions, TranscodeOutput};
 
 /// Helper to generate a dummy test video if it doesn't exist
 async fn ensure_test_video(path: &PathBuf) {
@@ -65,4 +66,202 @@ async fn test_ffmpeg_spawn() {
 
     // Cleanup: dropping transcoder kills the process
     drop(transcoder);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_hls_output() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transcode_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let video_path = temp_dir.join("test_src.mp4");
+
+    ensure_test_video(&video_path).await;
+
+    let out_dir = temp_dir.join("hls");
+    let _ = tokio::fs::remove_dir_all(&out_dir).await;
+    let output = TranscodeOutput::Hls { segment_duration: 1, out_dir: out_dir.clone() };
+
+    let transcoder = Transcoder::with_output(video_path, TranscodeOptions::default(), output)
+        .await
+        .expect("Failed to spawn transcoder");
+    let playlist = transcoder.playlist_path().expect("HLS output has a playlist").to_path_buf();
+
+    tokio::time::timeout(Duration::from_secs(30), transcoder.wait())
+        .await
+        .expect("Timed out waiting for ffmpeg")
+        .expect("HLS transcode failed");
+
+    assert!(tokio::fs::read_to_string(&playlist).await.unwrap().starts_with("#EXTM3U"));
+
+    let mut segments = 0;
+    let mut entries = tokio::fs::read_dir(&out_dir).await.unwrap();
+    while let Some(entry) = entries.next_entry().await.unwrap() {
+        if entry.path().extension().is_some_and(|ext| ext == "ts") {
+            segments += 1;
+        }
+    }
+    assert!(segments >= 1, "No HLS segments written");
+
+    let _ = tokio::fs::remove_dir_all(out_dir).await;
+}
+
+#[tokio::test]
+async fn test_start_offset_shortens_output() {
+    use futures::StreamExt;
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transcode_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let video_path = temp_dir.join("test_src.mp4");
+
+    ensure_test_video(&video_path).await;
+
+    let transcoded_len = |options: TranscodeOptions| {
+        let video_path = video_path.clone();
+        async move {
+            let transcoder = Transcoder::new(video_path, options).await.expect("Failed to spawn transcoder");
+            let stream = transcoder.stream_chunks(64 * 1024);
+            tokio::pin!(stream);
+
+            let mut total = 0;
+            while let Some(chunk) = stream.next().await {
+                total += chunk.expect("Transcode failed").len();
+            }
+            total
+        }
+    };
+
+    let full = transcoded_len(TranscodeOptions::default()).await;
+    let seeked = transcoded_len(TranscodeOptions {
+        start_offset: Some(Duration::from_secs(1)),
+        ..Default::default()
+    }).await;
+
+    assert!(seeked > 0);
+    assert!(seeked < full, "Seeked output ({} bytes) not shorter than full ({} bytes)", seeked, full);
+}
+
+#[tokio::test]
+async fn test_extract_thumbnail() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transcode_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let video_path = temp_dir.join("test_src.mp4");
+
+    ensure_test_video(&video_path).await;
+
+    let options = TranscodeOptions::default();
+    let jpeg = Transcoder::extract_thumbnail(video_path.clone(), Duration::from_secs(1), Some("160x90".into()), &options)
+        .await
+        .expect("Thumbnail capture failed");
+    assert_eq!(&jpeg[..2], &[0xFF, 0xD8], "Output is not a JPEG");
+
+    // Past the end of the 3 second clip
+    let err = Transcoder::extract_thumbnail(video_path, Duration::from_secs(60), None, &options).await;
+    assert!(matches!(err, Err(ghostdrive_core::StreamError::Transcode(_))));
+}
+
+#[tokio::test]
+async fn test_probe_media_info() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transcode_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let video_path = temp_dir.join("test_src.mp4");
+
+    ensure_test_video(&video_path).await;
+
+    let info = ghostdrive_transcoder::probe(&video_path).await.expect("Probe failed");
+
+    let duration = info.duration.expect("No duration reported");
+    assert!((duration - 3.0).abs() < 0.2, "Unexpected duration {}", duration);
+    assert!(info.format.contains("mp4"));
+
+    let video = info.video().expect("No video stream");
+    assert_eq!(video.codec, "h264");
+    assert_eq!((video.width, video.height), (Some(640), Some(360)));
+    assert!(info.streams.iter().any(|s| s.kind == "audio"));
+}
+
+#[tokio::test]
+async fn test_progress_updates() {
+    use futures::StreamExt;
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transcode_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let video_path = temp_dir.join("test_src.mp4");
+
+    ensure_test_video(&video_path).await;
+
+    let mut transcoder = Transcoder::new(video_path, TranscodeOptions::default())
+        .await
+        .expect("Failed to spawn transcoder");
+    let progress = transcoder.progress().expect("Progress already taken");
+    assert!(transcoder.progress().is_none());
+
+    // Keep the data pipe flowing while progress is collected
+    let mut stdout = transcoder.stdout().expect("Failed to capture stdout");
+    let drain = tokio::spawn(async move {
+        let mut sink = Vec::new();
+        stdout.read_to_end(&mut sink).await.unwrap();
+        sink.len()
+    });
+
+    let updates: Vec<_> = tokio::time::timeout(Duration::from_secs(30), progress.collect())
+        .await
+        .expect("Timed out collecting progress");
+    assert!(drain.await.unwrap() > 0);
+    transcoder.wait().await.expect("Transcode failed");
+
+    let last = updates.last().expect("No progress reported");
+    assert!(last.frame > 0);
+    assert!(last.out_time > Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn test_missing_ffmpeg_path() {
+    let bogus = PathBuf::from("/nonexistent/ghostdrive/ffmpeg");
+    let options = TranscodeOptions { ffmpeg_path: Some(bogus), ..Default::default() };
+
+    let err = match Transcoder::new(PathBuf::from("/nonexistent/input.mp4"), options).await {
+        Ok(_) => panic!("Transcoder started with a bogus ffmpeg path"),
+        Err(e) => e,
+    };
+    assert_eq!(err, StreamError::DependencyMissing { tool: "/nonexistent/ghostdrive/ffmpeg".to_string() });
+    assert!(err.to_string().contains("/nonexistent/ghostdrive/ffmpeg"), "Error doesn't name the path: {}", err);
+
+    // Thumbnails use the configured binary too
+    let err = Transcoder::extract_thumbnail(PathBuf::from("/nonexistent/input.mp4"), Duration::ZERO, None, &options).await;
+    assert_eq!(err, Err(StreamError::DependencyMissing { tool: "/nonexistent/ghostdrive/ffmpeg".to_string() }));
+}
+
+#[tokio::test]
+async fn test_audio_only_transcode() {
+    use futures::StreamExt;
+
+    let temp_dir = std::env::temp_dir().join("ghostdrive_transcode_audio_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let audio_path = temp_dir.join("sine.wav");
+
+    let generated = Command::new("ffmpeg")
+        .args(["-y", "-f", "lavfi", "-i", "sine=frequency=440:duration=2"])
+        .arg(&audio_path)
+        .output()
+        .await
+        .expect("Failed to run ffmpeg generator");
+    assert!(generated.status.success(), "Failed to generate test audio");
+
+    let options = TranscodeOptions {
+        audio_codec: "libmp3lame".into(),
+        audio_bitrate: Some("128k".into()),
+        format: "mp3".into(),
+        ..TranscodeOptions::default().for_mime("audio/wav")
+    };
+    assert!(options.audio_only);
+
+    let stream = Transcoder::new(audio_path, options).await.expect("Failed to spawn transcoder").stream_chunks(4096);
+    tokio::pin!(stream);
+
+    let mut total = 0;
+    while let Some(chunk) = stream.next().await {
+        total += chunk.expect("Audio transcode failed").len();
+    }
+    assert!(total > 0, "No audio produced");
+
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}
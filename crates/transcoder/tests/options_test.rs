@@ -0,0 +1,191 @@
+use std::time::Duration;
+use ghostdrive_core::StreamError;
+use ghostdrive_transcoder::{HwAccel, TranscodeOptions};
+
+#[test]
+fn test_fingerprint_stable_and_sensitive() {
+    let base = TranscodeOptions::default();
+
+    // Clones (and independently built equal values) share a fingerprint
+    assert_eq!(base.fingerprint(), base.clone().fingerprint());
+    assert_eq!(base.fingerprint(), TranscodeOptions::default().fingerprint());
+
+    // Changing any single field changes the fingerprint
+    let variants = vec![
+        TranscodeOptions { video_codec: "libx265".into(), ..base.clone() },
+        TranscodeOptions { video_bitrate: "4M".into(), ..base.clone() },
+        TranscodeOptions { audio_codec: "opus".into(), ..base.clone() },
+        TranscodeOptions { format: "matroska".into(), ..base.clone() },
+        TranscodeOptions { resolution: None, ..base.clone() },
+        TranscodeOptions { resolution: Some("1920x1080".into()), ..base.clone() },
+        TranscodeOptions { frame_rate: None, ..base.clone() },
+        TranscodeOptions { frame_rate: Some(60), ..base.clone() },
+        TranscodeOptions { start_offset: Some(Duration::from_secs(1)), ..base.clone() },
+        TranscodeOptions { duration: Some(Duration::from_secs(1)), ..base.clone() },
+        TranscodeOptions { hw_accel: HwAccel::Nvenc, ..base.clone() },
+        TranscodeOptions { copy: true, ..base.clone() },
+        TranscodeOptions { extra_args: vec!["-movflags".into(), "faststart".into()], ..base.clone() },
+        TranscodeOptions { audio_only: true, ..base.clone() },
+        TranscodeOptions { audio_bitrate: Some("192k".into()), ..base.clone() },
+    ];
+
+    for variant in &variants {
+        assert_ne!(base.fingerprint(), variant.fingerprint(), "Fingerprint unchanged for {:?}", variant);
+    }
+}
+
+#[test]
+fn test_thread_count_in_command() {
+    let input = std::path::Path::new("/media/movie.mkv");
+
+    let args = |options: &TranscodeOptions| -> Vec<String> {
+        options.ffmpeg_args(input).iter().map(|a| a.to_string_lossy().to_string()).collect()
+    };
+
+    // Default leaves threading to ffmpeg
+    assert!(!args(&TranscodeOptions::default()).contains(&"-threads".to_string()));
+
+    let limited = TranscodeOptions { threads: Some(2), ..Default::default() };
+    let limited_args = args(&limited);
+    let pos = limited_args.iter().position(|a| a == "-threads").expect("-threads missing");
+    assert_eq!(limited_args[pos + 1], "2");
+
+    // Scheduling doesn't change the output, so cached transcodes are shared
+    assert_eq!(limited.fingerprint(), TranscodeOptions::default().fingerprint());
+}
+
+#[test]
+fn test_seek_args_placement() {
+    let input = std::path::Path::new("/media/movie.mkv");
+    let options = TranscodeOptions {
+        start_offset: Some(Duration::from_millis(1500)),
+        duration: Some(Duration::from_secs(10)),
+        ..Default::default()
+    };
+    let args: Vec<String> = options.ffmpeg_args(input).iter().map(|a| a.to_string_lossy().to_string()).collect();
+
+    let ss = args.iter().position(|a| a == "-ss").expect("-ss missing");
+    let i = args.iter().position(|a| a == "-i").expect("-i missing");
+    let t = args.iter().position(|a| a == "-t").expect("-t missing");
+    assert!(ss < i, "-ss must come before -i for fast seeking");
+    assert!(t > i);
+    assert_eq!(args[ss + 1], "1.500");
+    assert_eq!(args[t + 1], "10.000");
+}
+
+#[test]
+fn test_hw_accel_encoders() {
+    let input = std::path::Path::new("/media/movie.mkv");
+    let cases = [
+        (HwAccel::None, "libx264"),
+        (HwAccel::Nvenc, "h264_nvenc"),
+        (HwAccel::Vaapi, "h264_vaapi"),
+        (HwAccel::VideoToolbox, "h264_videotoolbox"),
+        (HwAccel::Qsv, "h264_qsv"),
+    ];
+
+    for (hw_accel, encoder) in cases {
+        let options = TranscodeOptions { hw_accel, ..Default::default() };
+        let args: Vec<String> = options.ffmpeg_args(input).iter().map(|a| a.to_string_lossy().to_string()).collect();
+
+        let pos = args.iter().position(|a| a == "-c:v").expect("-c:v missing");
+        assert_eq!(args[pos + 1], encoder, "Wrong encoder for {:?}", hw_accel);
+
+        // x264 tuning is only valid for x264
+        assert_eq!(args.contains(&"zerolatency".to_string()), hw_accel == HwAccel::None, "{:?}", hw_accel);
+    }
+}
+
+#[test]
+fn test_copy_mode_skips_encoding() {
+    let input = std::path::Path::new("/media/movie.mkv");
+
+    // Encoder settings left over from the defaults are ignored
+    for options in [TranscodeOptions::passthrough(), TranscodeOptions { copy: true, hw_accel: HwAccel::Nvenc, ..Default::default() }] {
+        let args: Vec<String> = options.ffmpeg_args(input).iter().map(|a| a.to_string_lossy().to_string()).collect();
+
+        let v = args.iter().position(|a| a == "-c:v").expect("-c:v missing");
+        let a = args.iter().position(|a| a == "-c:a").expect("-c:a missing");
+        assert_eq!(args[v + 1], "copy");
+        assert_eq!(args[a + 1], "copy");
+
+        for flag in ["-b:v", "-s", "-r", "-vf", "-preset", "-tune", "-hwaccel"] {
+            assert!(!args.contains(&flag.to_string()), "{} present in copy mode: {:?}", flag, args);
+        }
+        assert_eq!(&args[args.len() - 3..], ["-f", "mpegts", "pipe:1"]);
+    }
+}
+
+#[test]
+fn test_extra_args_before_output() {
+    let input = std::path::Path::new("/media/movie.mkv");
+    let options = TranscodeOptions {
+        extra_args: vec!["-max_muxing_queue_size".into(), "1024".into()],
+        ..Default::default()
+    };
+    let args: Vec<String> = options.ffmpeg_args(input).iter().map(|a| a.to_string_lossy().to_string()).collect();
+
+    assert_eq!(&args[args.len() - 5..], ["-max_muxing_queue_size", "1024", "-f", "mpegts", "pipe:1"]);
+}
+
+#[test]
+fn test_audio_only_args() {
+    let input = std::path::Path::new("/media/concert.mkv");
+    let options = TranscodeOptions {
+        audio_codec: "libmp3lame".into(),
+        audio_bitrate: Some("192k".into()),
+        format: "mp3".into(),
+        ..TranscodeOptions::default().for_mime("audio/flac")
+    };
+    let args: Vec<String> = options.ffmpeg_args(input).iter().map(|a| a.to_string_lossy().to_string()).collect();
+
+    // Video tracks (e.g. cover art, or a concert video) are stripped
+    assert!(args.contains(&"-vn".to_string()));
+    for flag in ["-c:v", "-b:v", "-s", "-r", "-preset"] {
+        assert!(!args.contains(&flag.to_string()), "{} present in audio-only mode: {:?}", flag, args);
+    }
+    let b = args.iter().position(|a| a == "-b:a").expect("-b:a missing");
+    assert_eq!(args[b + 1], "192k");
+
+    assert!(!TranscodeOptions::default().for_mime("video/mp4").audio_only);
+}
+
+#[test]
+fn test_validate_accepts_defaults() {
+    TranscodeOptions::default().validate().expect("Default options rejected");
+    TranscodeOptions::passthrough().validate().expect("Passthrough options rejected");
+    TranscodeOptions {
+        video_bitrate: "800k".into(),
+        audio_bitrate: Some("128K".into()),
+        resolution: Some("1920x1080".into()),
+        frame_rate: None,
+        ..Default::default()
+    }
+    .validate()
+    .expect("Valid options rejected");
+}
+
+#[test]
+fn test_validate_rejects_bad_options() {
+    let broken = vec![
+        ("Video codec", TranscodeOptions { video_codec: "".into(), ..Default::default() }),
+        ("Audio codec", TranscodeOptions { audio_codec: " ".into(), ..Default::default() }),
+        ("video bitrate", TranscodeOptions { video_bitrate: "fast".into(), ..Default::default() }),
+        ("video bitrate", TranscodeOptions { video_bitrate: "2G".into(), ..Default::default() }),
+        ("video bitrate", TranscodeOptions { video_bitrate: "M".into(), ..Default::default() }),
+        ("audio bitrate", TranscodeOptions { audio_bitrate: Some("128 kbps".into()), ..Default::default() }),
+        ("resolution", TranscodeOptions { resolution: Some("1280*720".into()), ..Default::default() }),
+        ("resolution", TranscodeOptions { resolution: Some("1280x".into()), ..Default::default() }),
+        ("resolution", TranscodeOptions { resolution: Some("0x720".into()), ..Default::default() }),
+        ("Frame rate", TranscodeOptions { frame_rate: Some(0), ..Default::default() }),
+    ];
+
+    for (problem, options) in broken {
+        match options.validate() {
+            Err(StreamError::Transcode(msg)) => {
+                assert!(msg.contains(problem), "Error {:?} doesn't mention {}", msg, problem)
+            }
+            other => panic!("Expected transcode error for {}, got {:?}", problem, other),
+        }
+    }
+}
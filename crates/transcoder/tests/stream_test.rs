@@ -52,4 +52,40 @@ async fn test_stream_chunks() {
 
     // Cleanup
     let _ = tokio::fs::remove_dir_all(temp_dir).await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_cancel_stream() {
+    let temp_dir = std::env::temp_dir().join("ghostdrive_stream_cancel_test");
+    let _ = tokio::fs::create_dir_all(&temp_dir).await;
+    let video_path = temp_dir.join("test_src.mp4");
+
+    ensure_test_video(&video_path).await;
+
+    let token = tokio_util::sync::CancellationToken::new();
+    let transcoder = Transcoder::new(video_path, TranscodeOptions::default())
+        .await
+        .expect("Failed to init transcoder")
+        .with_cancel(token.clone());
+
+    let stream = transcoder.stream_chunks(188);
+    tokio::pin!(stream);
+
+    // First chunk arrives, then the client goes away
+    stream.next().await.expect("No output").expect("Stream error");
+    token.cancel();
+
+    // The stream ends promptly and without an error
+    let rest = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+        let mut chunks = 0;
+        while let Some(chunk) = stream.next().await {
+            chunk.expect("Cancelled stream should end cleanly");
+            chunks += 1;
+        }
+        chunks
+    }).await.expect("Stream kept running after cancellation");
+    assert_eq!(rest, 0, "Chunks produced after cancellation");
+
+    // Cleanup
+    let _ = tokio::fs::remove_dir_all(temp_dir).await;
+}